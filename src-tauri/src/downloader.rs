@@ -1,7 +1,7 @@
 use futures::stream::{self, StreamExt};
 use regex::Regex;
 use reqwest::Client;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::fs;
@@ -17,32 +17,588 @@ pub struct DownloadProgress {
     pub message: String,
 }
 
-#[derive(Debug)]
+/// 동시에 진행할 다운로드 수 제한 (앱 전역 상태로 관리, 시작 시 설정에서 생성)
+///
+/// 실행 중에는 다시 만들지 않으므로 설정의 동시 실행 수는 앱을 다시 시작해야 적용된다.
+pub struct DownloadLimits {
+    /// 동시에 받을 VOD 수
+    pub vods: tokio::sync::Semaphore,
+    /// 모든 다운로드를 합친 동시 세그먼트 요청 수
+    pub segments: tokio::sync::Semaphore,
+}
+
+impl DownloadLimits {
+    pub fn new(max_vods: usize, max_segment_requests: usize) -> Self {
+        Self {
+            vods: tokio::sync::Semaphore::new(max_vods.max(1)),
+            segments: tokio::sync::Semaphore::new(max_segment_requests.max(1)),
+        }
+    }
+}
+
+/// 진행 중인 작업의 취소 요청 플래그 (앱 전역 상태로 관리)
+#[derive(Default)]
+pub struct CancelState {
+    pub ffmpeg_install: std::sync::atomic::AtomicBool,
+    /// 앱 종료 요청: 새 세그먼트 다운로드를 시작하지 않는다
+    pub shutdown: std::sync::atomic::AtomicBool,
+    /// 파일에 쓰는 중인 세그먼트 수 (종료 시 이 값이 0이 될 때까지 기다린다)
+    pub active_segments: std::sync::atomic::AtomicUsize,
+    /// 진행 중/대기 중인 다운로드 (등록 번호 → 항목)
+    downloads: std::sync::Mutex<std::collections::HashMap<u64, RegisteredDownload>>,
+    next_download: std::sync::atomic::AtomicU64,
+    /// 전체 취소 시 증가: 실행 중인 ffmpeg는 이 값이 바뀌면 종료된다
+    ffmpeg_generation: std::sync::atomic::AtomicU64,
+    /// 진행 중인 정보 조회 (요청 ID → 취소 알림)
+    info_fetches:
+        std::sync::Mutex<std::collections::HashMap<String, std::sync::Arc<tokio::sync::Notify>>>,
+}
+
+impl CancelState {
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// 정보 조회 `fut`을 `request_id`로 등록해 실행 (ID가 없으면 그냥 실행)
+    ///
+    /// [`cancel_info_fetch`](Self::cancel_info_fetch)나 같은 ID의 새 조회가 오면 요청을
+    /// 중단하고 [`INFO_FETCH_CANCELLED_ERROR`]를 반환한다. 먼저 시작한 조회가 나중 결과를
+    /// 덮어쓰지 않게 하기 위함.
+    pub async fn with_info_fetch<T>(
+        &self,
+        request_id: Option<&str>,
+        fut: impl std::future::Future<Output = Result<T, String>>,
+    ) -> Result<T, String> {
+        let Some(request_id) = request_id else {
+            return fut.await;
+        };
+        let notify = std::sync::Arc::new(tokio::sync::Notify::new());
+        if let Ok(mut fetches) = self.info_fetches.lock() {
+            if let Some(previous) = fetches.insert(request_id.to_string(), notify.clone()) {
+                previous.notify_one();
+            }
+        }
+
+        let result = tokio::select! {
+            result = fut => result,
+            _ = notify.notified() => {
+                eprintln!("🚫 Info fetch {} cancelled", request_id);
+                Err(INFO_FETCH_CANCELLED_ERROR.to_string())
+            }
+        };
+
+        if let Ok(mut fetches) = self.info_fetches.lock() {
+            if fetches
+                .get(request_id)
+                .is_some_and(|n| std::sync::Arc::ptr_eq(n, &notify))
+            {
+                fetches.remove(request_id);
+            }
+        }
+        result
+    }
+
+    /// 진행 중인 정보 조회 취소 (해당 ID의 조회가 있었으면 true)
+    pub fn cancel_info_fetch(&self, request_id: &str) -> bool {
+        let notify = self
+            .info_fetches
+            .lock()
+            .ok()
+            .and_then(|mut fetches| fetches.remove(request_id));
+        match notify {
+            Some(notify) => {
+                notify.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 차례를 기다리는 중이 아닌(받기/병합/ffmpeg 처리 중인) 다운로드 수
+    pub fn active_downloads(&self) -> usize {
+        self.downloads
+            .lock()
+            .map(|d| {
+                d.values()
+                    .filter(|d| {
+                        d.status
+                            .stage
+                            .lock()
+                            .is_ok_and(|stage| stage.as_str() != "queued")
+                    })
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    /// 다운로드 항목의 취소 토큰 등록 (반환된 토큰이 drop되면 등록 해제)
+    pub fn register_download(&self, id: &str) -> DownloadToken<'_> {
+        let key = self
+            .next_download
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let cancel = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let status = std::sync::Arc::new(LiveStatus::default());
+        if let Ok(mut downloads) = self.downloads.lock() {
+            downloads.insert(
+                key,
+                RegisteredDownload {
+                    id: id.to_string(),
+                    cancel: cancel.clone(),
+                    status: status.clone(),
+                },
+            );
+        }
+        DownloadToken {
+            state: self,
+            key,
+            cancel,
+            status,
+        }
+    }
+
+    /// 항목 id로 진행 중인 다운로드의 현재 상태 조회 (같은 id가 여럿이면 가장 최근 것)
+    pub fn download_status(&self, id: &str) -> Option<DownloadStatus> {
+        let downloads = self.downloads.lock().ok()?;
+        downloads
+            .iter()
+            .filter(|(_, d)| d.id == id)
+            .max_by_key(|(key, _)| **key)
+            .map(|(_, d)| d.status.snapshot())
+    }
+
+    /// 진행 중/대기 중인 다운로드의 (항목 id, 상태) 목록 (등록 순서)
+    pub fn list_downloads(&self) -> Vec<(String, DownloadStatus)> {
+        let Ok(downloads) = self.downloads.lock() else {
+            return Vec::new();
+        };
+        let mut entries: Vec<_> = downloads.iter().collect();
+        entries.sort_by_key(|(key, _)| **key);
+        entries
+            .into_iter()
+            .map(|(_, d)| (d.id.clone(), d.status.snapshot()))
+            .collect()
+    }
+
+    /// 등록된 모든 다운로드를 취소하고 실행 중인 ffmpeg를 종료, 취소된 항목 id 목록 반환
+    pub fn cancel_all_downloads(&self) -> Vec<String> {
+        use std::sync::atomic::Ordering;
+
+        self.ffmpeg_generation.fetch_add(1, Ordering::SeqCst);
+        let Ok(mut downloads) = self.downloads.lock() else {
+            return Vec::new();
+        };
+        downloads
+            .drain()
+            .map(|(_, d)| {
+                d.cancel.store(true, Ordering::SeqCst);
+                d.id
+            })
+            .collect()
+    }
+
+    /// 항목 id의 다운로드만 취소, 취소된 것이 있으면 그 다운로드가 기록한 출력 파일 경로 반환
+    pub fn cancel_download(&self, id: &str) -> Option<Vec<PathBuf>> {
+        let downloads = self.downloads.lock().ok()?;
+        let mut outputs = None;
+        for d in downloads.values().filter(|d| d.id == id) {
+            d.cancel.store(true, std::sync::atomic::Ordering::SeqCst);
+            outputs
+                .get_or_insert_with(Vec::new)
+                .extend(d.status.outputs());
+        }
+        outputs
+    }
+
+    /// 항목 id의 다운로드가 모두 끝날(등록 해제될) 때까지 최대 `timeout` 동안 대기
+    pub async fn wait_download_finished(&self, id: &str, timeout: std::time::Duration) -> bool {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            let active = self
+                .downloads
+                .lock()
+                .map(|d| d.values().any(|d| d.id == id))
+                .unwrap_or(false);
+            if !active {
+                return true;
+            }
+            if std::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+    }
+
+    /// cancel_ffmpeg_install이 호출될 때까지 대기 (요청 플래그는 소비한다)
+    async fn ffmpeg_install_cancelled(&self) {
+        use std::sync::atomic::Ordering;
+
+        while !self.ffmpeg_install.swap(false, Ordering::SeqCst) {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+    }
+
+    /// cancel_all_downloads가 호출될 때까지 대기
+    async fn ffmpeg_cancelled(&self) {
+        use std::sync::atomic::Ordering;
+
+        let generation = self.ffmpeg_generation.load(Ordering::SeqCst);
+        while self.ffmpeg_generation.load(Ordering::SeqCst) == generation {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+    }
+}
+
+struct RegisteredDownload {
+    id: String,
+    cancel: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    status: std::sync::Arc<LiveStatus>,
+}
+
+/// 등록된 다운로드의 취소 토큰과 상태 기록 (drop 시 등록 해제)
+pub struct DownloadToken<'a> {
+    state: &'a CancelState,
+    key: u64,
+    pub cancel: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    pub status: std::sync::Arc<LiveStatus>,
+}
+
+/// get_download_status 응답
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadStatus {
+    pub stage: String,
+    pub current: u32,
+    pub total: u32,
+    pub percent: f64,
+    pub bytes_done: u64,
+    /// 세그먼트 다운로드 평균 속도 (바이트/초)
+    pub speed_bps: f64,
+    /// 세그먼트 다운로드 남은 예상 시간 (초)
+    pub eta_secs: Option<f64>,
+}
+
+/// 앱 전체 다운로드 속도를 계산할 구간
+const THROUGHPUT_WINDOW: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 앱 전체에서 받은 바이트 수 (세그먼트/클립 다운로드)
+static TOTAL_BYTES: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// 최근 [`THROUGHPUT_WINDOW`] 동안 받은 (시각, 바이트 수) 기록
+static THROUGHPUT_SAMPLES: std::sync::Mutex<std::collections::VecDeque<(std::time::Instant, u64)>> =
+    std::sync::Mutex::new(std::collections::VecDeque::new());
+
+/// 받은 바이트 수를 앱 전체 속도 계산에 반영
+fn record_throughput(bytes: u64) {
+    TOTAL_BYTES.fetch_add(bytes, std::sync::atomic::Ordering::Relaxed);
+    let now = std::time::Instant::now();
+    if let Ok(mut samples) = THROUGHPUT_SAMPLES.lock() {
+        samples.push_back((now, bytes));
+        while samples
+            .front()
+            .is_some_and(|(t, _)| now.duration_since(*t) > THROUGHPUT_WINDOW)
+        {
+            samples.pop_front();
+        }
+    }
+}
+
+/// 진행 중인 모든 다운로드를 합친 현재 속도 (바이트/초, 최근 5초 평균)
+pub fn current_throughput() -> f64 {
+    let now = std::time::Instant::now();
+    let bytes: u64 = THROUGHPUT_SAMPLES
+        .lock()
+        .map(|samples| {
+            samples
+                .iter()
+                .filter(|(t, _)| now.duration_since(*t) <= THROUGHPUT_WINDOW)
+                .map(|(_, b)| b)
+                .sum()
+        })
+        .unwrap_or(0);
+    bytes as f64 / THROUGHPUT_WINDOW.as_secs_f64()
+}
+
+/// 앱을 켠 뒤 받은 전체 바이트 수
+pub fn total_downloaded_bytes() -> u64 {
+    TOTAL_BYTES.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// 진행 중인 다운로드의 실시간 상태 (진행 이벤트와 함께 갱신)
+#[derive(Debug, Default)]
+pub struct LiveStatus {
+    stage: std::sync::Mutex<String>,
+    current: std::sync::atomic::AtomicU32,
+    total: std::sync::atomic::AtomicU32,
+    bytes_done: std::sync::atomic::AtomicU64,
+    /// 세그먼트 다운로드 시작 시각
+    started: std::sync::Mutex<Option<std::time::Instant>>,
+    /// ffmpeg가 쓰고 있는 출력 파일 (취소 후 삭제용)
+    outputs: std::sync::Mutex<Vec<PathBuf>>,
+}
+
+impl LiveStatus {
+    pub fn set_stage(&self, stage: &str) {
+        if let Ok(mut current) = self.stage.lock() {
+            *current = stage.to_string();
+        }
+        if stage == "downloading" {
+            if let Ok(mut started) = self.started.lock() {
+                started.get_or_insert_with(std::time::Instant::now);
+            }
+        }
+    }
+
+    /// 이 다운로드가 만들 출력 파일 경로 기록
+    pub fn set_outputs(&self, paths: Vec<PathBuf>) {
+        if let Ok(mut outputs) = self.outputs.lock() {
+            *outputs = paths;
+        }
+    }
+
+    fn outputs(&self) -> Vec<PathBuf> {
+        self.outputs.lock().map(|o| o.clone()).unwrap_or_default()
+    }
+
+    fn record_segment(&self, done: u32, total: u32, bytes: u64) {
+        use std::sync::atomic::Ordering;
+
+        self.current.store(done, Ordering::SeqCst);
+        self.total.store(total, Ordering::SeqCst);
+        self.bytes_done.fetch_add(bytes, Ordering::SeqCst);
+    }
+
+    pub fn snapshot(&self) -> DownloadStatus {
+        use std::sync::atomic::Ordering;
+
+        let current = self.current.load(Ordering::SeqCst);
+        let total = self.total.load(Ordering::SeqCst);
+        let bytes_done = self.bytes_done.load(Ordering::SeqCst);
+        let elapsed = self
+            .started
+            .lock()
+            .ok()
+            .and_then(|s| *s)
+            .map(|s| s.elapsed().as_secs_f64())
+            .unwrap_or(0.0);
+
+        let percent = if total > 0 {
+            current as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        };
+        let speed_bps = if elapsed > 0.0 {
+            bytes_done as f64 / elapsed
+        } else {
+            0.0
+        };
+        // 지금까지의 세그먼트당 평균 시간으로 남은 시간 추정
+        let eta_secs = (current > 0 && elapsed > 0.0)
+            .then(|| elapsed / current as f64 * total.saturating_sub(current) as f64);
+
+        DownloadStatus {
+            stage: self.stage.lock().map(|s| s.clone()).unwrap_or_default(),
+            current,
+            total,
+            percent,
+            bytes_done,
+            speed_bps,
+            eta_secs,
+        }
+    }
+}
+
+impl DownloadToken<'_> {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// `fut`을 실행하되 이 다운로드가 취소되면 중단
+    /// (future가 drop되면서 kill_on_drop으로 띄운 ffmpeg도 함께 종료된다)
+    pub async fn until_cancelled<T>(
+        &self,
+        fut: impl std::future::Future<Output = Result<T, String>>,
+    ) -> Result<T, String> {
+        let cancelled = async {
+            while !self.is_cancelled() {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            }
+        };
+        tokio::select! {
+            result = fut => result,
+            _ = cancelled => Err(CANCELLED_ERROR.to_string()),
+        }
+    }
+}
+
+impl Drop for DownloadToken<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut downloads) = self.state.downloads.lock() {
+            downloads.remove(&self.key);
+        }
+    }
+}
+
+/// 진행 중인 세그먼트 수를 세는 guard (drop 시 감소)
+struct ActiveSegmentGuard<'a>(&'a std::sync::atomic::AtomicUsize, usize);
+
+impl<'a> ActiveSegmentGuard<'a> {
+    fn new(counter: &'a std::sync::atomic::AtomicUsize) -> Self {
+        Self::many(counter, 1)
+    }
+
+    /// 세그먼트 `count`개를 한꺼번에 진행 중으로 센다 (aria2c 실행 등)
+    fn many(counter: &'a std::sync::atomic::AtomicUsize, count: usize) -> Self {
+        counter.fetch_add(count, std::sync::atomic::Ordering::SeqCst);
+        Self(counter, count)
+    }
+}
+
+impl Drop for ActiveSegmentGuard<'_> {
+    fn drop(&mut self) {
+        self.0
+            .fetch_sub(self.1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// 사용자가 다운로드를 취소했을 때의 오류 (UI가 실패와 구분할 수 있도록 접두사 고정)
+pub const CANCELLED_ERROR: &str = "CANCELLED: 다운로드가 취소되었습니다";
+pub const INFO_FETCH_CANCELLED_ERROR: &str = "CANCELLED: 정보 조회가 취소되었습니다";
+
+const SHUTDOWN_ERROR: &str =
+    "앱 종료로 다운로드를 중단했습니다. 다시 실행하면 이어받을 수 있습니다.";
+
+#[derive(Debug, Clone, Default)]
 pub struct VideoInfo {
     pub title: String,
     pub channel: String,
     pub master_url: String,
     pub duration: u64,
     pub thumbnail: String,
+    /// 공개 일시 ("YYYY-MM-DD HH:MM:SS")
+    pub publish_date: Option<String>,
+    /// 방송 카테고리 (게임 이름 등)
+    pub category: Option<String>,
+    /// 챕터 (API에 없으면 빈 목록)
+    pub chapters: Vec<Chapter>,
     // DASH 정보
     pub is_dash: bool,
     pub dash_video_id: Option<String>,
     pub dash_in_key: Option<String>,
+    /// 로그인 쿠키 (NID_AUT, NID_SES). 암호화된 HLS의 키 요청에 사용
+    pub cookies: Option<(String, String)>,
+}
+
+/// VOD 챕터 마커 하나
+#[derive(Debug, Clone, Serialize)]
+pub struct Chapter {
+    pub title: String,
+    /// 영상 시작 기준 시작 시각 (초)
+    pub start_secs: f64,
 }
 
 #[derive(Debug)]
 pub struct ClipInfo {
     pub title: String,
     pub channel: String,
+    /// 기본으로 받을 MP4 URL (첫 번째 representation)
     pub mp4_url: String,
     pub thumbnail: String,
+    /// 화질별 MP4 (API가 주는 순서 그대로)
+    pub variants: Vec<ClipVariant>,
+    /// 클립 생성 일시 ("YYYY-MM-DD HH:MM:SS")
+    pub created_date: Option<String>,
+}
+
+/// 클립의 화질별 MP4 하나 (video/mp4 adaptationSet의 representation)
+#[derive(Debug, Clone)]
+pub struct ClipVariant {
+    /// representation ID (없으면 순번)
+    pub id: String,
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+    pub bandwidth: u64,
+}
+
+/// VOD 스크러빙용 썸네일 스프라이트 시트 한 장
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailSprite {
+    pub url: String,
+    /// 시트의 첫 프레임 시각 (초)
+    pub start_time: f64,
+    /// 시트의 마지막 프레임 이후 시각 (초)
+    pub end_time: f64,
+    /// 프레임 간격 (초)
+    pub interval: f64,
+    pub columns: u32,
+    pub rows: u32,
+    /// 프레임 한 칸의 크기
+    pub width: u32,
+    pub height: u32,
+}
+
+/// VOD에 포함된 자막(캡션) 트랙
+#[derive(Debug, Clone, Serialize)]
+pub struct SubtitleTrack {
+    pub id: String,
+    pub language: String,
+    pub label: String,
+    /// DASH는 VTT 파일 URL, HLS는 자막 미디어 플레이리스트 URL
+    pub url: String,
+    pub is_playlist: bool,
+}
+
+/// 접속에 사용할 IP 버전
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpMode {
+    /// 시스템 기본값
+    #[default]
+    Auto,
+    /// IPv4만 사용 (IPv6 경로 문제가 있는 ISP용)
+    Ipv4,
+    Ipv6,
+}
+
+/// HTTP 클라이언트 연결 설정 (설정 저장 시 갱신)
+#[derive(Debug, Clone)]
+pub struct NetworkOptions {
+    pub ip_mode: IpMode,
+    /// DNS 대신 사용할 호스트 → IP 지정
+    pub dns_overrides: Vec<(String, std::net::IpAddr)>,
+    /// 호스트별로 재사용을 위해 열어 둘 유휴 연결 수 (None이면 reqwest 기본값: 제한 없음)
+    pub pool_max_idle_per_host: Option<usize>,
+    /// 유휴 연결을 닫기까지의 시간 (None이면 reqwest 기본값: 90초)
+    pub pool_idle_timeout: Option<std::time::Duration>,
+    /// TCP keepalive 간격 (None이면 사용 안 함)
+    pub tcp_keepalive: Option<std::time::Duration>,
+    /// 모든 요청을 거칠 프록시 (예: "http://127.0.0.1:8080", 지역 제한 우회용)
+    pub proxy: Option<reqwest::Proxy>,
+}
+
+static NETWORK_OPTIONS: std::sync::RwLock<NetworkOptions> =
+    std::sync::RwLock::new(NetworkOptions {
+        ip_mode: IpMode::Auto,
+        dns_overrides: Vec::new(),
+        pool_max_idle_per_host: None,
+        pool_idle_timeout: None,
+        tcp_keepalive: None,
+        proxy: None,
+    });
+
+/// 이후 생성되는 HTTP 클라이언트에 적용할 연결 설정
+pub fn set_network_options(options: NetworkOptions) {
+    if let Ok(mut current) = NETWORK_OPTIONS.write() {
+        *current = options;
+    }
 }
 
 fn build_client() -> Client {
     build_client_with_cookies(None, None)
 }
 
-fn build_client_with_cookies(nid_aut: Option<String>, nid_ses: Option<String>) -> Client {
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+pub fn build_client_with_cookies(nid_aut: Option<String>, nid_ses: Option<String>) -> Client {
     let mut headers = reqwest::header::HeaderMap::new();
     headers.insert("Referer", "https://chzzk.naver.com/".parse().unwrap());
 
@@ -54,103 +610,677 @@ fn build_client_with_cookies(nid_aut: Option<String>, nid_ses: Option<String>) -
         }
     }
 
-    Client::builder()
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-        .default_headers(headers)
-        .build()
-        .expect("Failed to build HTTP client")
-}
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .default_headers(headers);
 
-fn time_to_sec(t: &str) -> f64 {
-    if t.is_empty() {
-        return 0.0;
-    }
-    let parts: Vec<f64> = t.split(':').filter_map(|p| p.parse().ok()).collect();
-    match parts.len() {
-        3 => parts[0] * 3600.0 + parts[1] * 60.0 + parts[2],
-        2 => parts[0] * 60.0 + parts[1],
-        1 => parts[0],
-        _ => 0.0,
+    if let Ok(network) = NETWORK_OPTIONS.read() {
+        builder = match network.ip_mode {
+            IpMode::Auto => builder,
+            IpMode::Ipv4 => {
+                builder.local_address(std::net::IpAddr::from(std::net::Ipv4Addr::UNSPECIFIED))
+            }
+            IpMode::Ipv6 => {
+                builder.local_address(std::net::IpAddr::from(std::net::Ipv6Addr::UNSPECIFIED))
+            }
+        };
+        for (host, ip) in &network.dns_overrides {
+            // 포트는 무시되고 URL의 포트가 사용된다
+            builder = builder.resolve(host, std::net::SocketAddr::new(*ip, 443));
+        }
+        if let Some(max_idle) = network.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(timeout) = network.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = network.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if let Some(proxy) = &network.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
     }
+
+    builder.build().expect("Failed to build HTTP client")
 }
 
-fn resolve_url(base: &str, relative: &str) -> String {
-    if relative.starts_with("http://") || relative.starts_with("https://") {
-        return relative.to_string();
+/// 앱 전체 동시 네트워크 요청 수 기본값
+pub const DEFAULT_MAX_REQUESTS: usize = 32;
+
+/// 앱 전체 동시 네트워크 요청 수 제한 (모든 외부 요청이 보내기 전에 허가를 받음)
+static REQUEST_PERMITS: std::sync::OnceLock<tokio::sync::Semaphore> = std::sync::OnceLock::new();
+
+/// 동시 요청 수 설정 (앱 시작 시 첫 요청 전에 한 번만 적용됨)
+///
+/// 이미 정해졌으면(두 번째 호출이거나 첫 요청이 먼저 나간 경우) 오류를 돌려준다.
+pub fn set_max_requests(max_requests: usize) -> Result<(), String> {
+    REQUEST_PERMITS
+        .set(tokio::sync::Semaphore::new(max_requests.max(1)))
+        .map_err(|_| "동시 요청 수는 앱을 다시 시작해야 적용됩니다".to_string())
+}
+
+/// 요청 하나를 보낼 허가 (응답을 다 읽을 때까지 들고 있을 것)
+///
+/// 허가를 든 채로 다른 요청의 허가를 기다리면 교착될 수 있으므로 한 번에 하나만 든다.
+pub async fn request_permit() -> Option<tokio::sync::SemaphorePermit<'static>> {
+    REQUEST_PERMITS
+        .get_or_init(|| tokio::sync::Semaphore::new(DEFAULT_MAX_REQUESTS))
+        .acquire()
+        .await
+        .ok()
+}
+
+/// API가 429(Too Many Requests)를 돌려줄 때 다시 시도하는 최대 횟수
+const RATE_LIMIT_RETRIES: u32 = 4;
+/// Retry-After가 너무 길게 오더라도 한 번에 기다리는 최대 시간
+const RATE_LIMIT_MAX_WAIT: std::time::Duration = std::time::Duration::from_secs(60);
+const RATE_LIMITED_ERROR: &str = "요청이 너무 많습니다. 잠시 후 다시 시도하세요";
+
+/// API에 GET 요청을 보내고, 429 응답이면 `Retry-After`(초)만큼 기다렸다가 다시 시도
+///
+/// 헤더가 없으면 2, 4, 8…초씩 늘려 가며 기다린다. 재시도를 모두 써도 429이면
+/// [`RATE_LIMITED_ERROR`]를, 요청 자체가 실패하면 `context`를 붙인 오류를 돌려준다.
+/// 본문은 요청 허가를 든 채로 [`parse_api_json`]으로 읽는다 (`parse_context`는 그 오류용).
+async fn api_get(
+    client: &Client,
+    url: &str,
+    context: &str,
+    parse_context: &str,
+) -> Result<serde_json::Value, String> {
+    for attempt in 0..=RATE_LIMIT_RETRIES {
+        let _permit = request_permit().await;
+        let resp = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("{}: {}", context, e))?;
+        if resp.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return parse_api_json(resp, parse_context).await;
+        }
+        if attempt == RATE_LIMIT_RETRIES {
+            break;
+        }
+
+        let wait = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(std::time::Duration::from_secs)
+            .unwrap_or_else(|| std::time::Duration::from_secs(2 << attempt))
+            .min(RATE_LIMIT_MAX_WAIT);
+        eprintln!(
+            "⏳ Rate limited (429), retrying in {}s ({}/{}): {}",
+            wait.as_secs(),
+            attempt + 1,
+            RATE_LIMIT_RETRIES,
+            url
+        );
+        tokio::time::sleep(wait).await;
     }
-    // Strip query string before finding last '/' in path
-    let base_path = match base.find('?') {
-        Some(q) => &base[..q],
-        None => base,
+
+    Err(RATE_LIMITED_ERROR.to_string())
+}
+
+pub const UNAVAILABLE_ERROR: &str = "Naver 서버 점검 중이거나 응답이 올바르지 않습니다";
+
+/// 지역 제한 오류 접두사 (UI가 프록시 설정을 안내할 수 있도록 구분)
+const GEO_BLOCKED_ERROR: &str = "GEO_BLOCKED: 지역 제한 콘텐츠입니다";
+
+/// 지역 제한을 나타내는 메시지/필드 값 (소문자로 비교)
+const GEO_BLOCK_HINTS: [&str; 6] = ["지역", "국가", "region", "country", "geo", "overseas"];
+/// 시청 제한 사유가 들어 있는 content 필드
+const GEO_BLOCK_FIELDS: [&str; 4] = [
+    "blindType",
+    "restrictType",
+    "restrictionType",
+    "geoBlockType",
+];
+
+/// API 응답이 지역 제한 때문에 막힌 것이면 [`GEO_BLOCKED_ERROR`]로 시작하는 오류 반환
+///
+/// 로그인 상태여도 최상위 `code`가 200이 아니고 `message`에 지역/국가 제한 문구가 오거나,
+/// content의 제한 사유 필드(`blindType`, `restrictType` 등)에 지역 제한 값이 온다.
+fn geo_block_error(resp: &serde_json::Value) -> Option<String> {
+    let mentions_geo = |text: &str| {
+        let text = text.to_lowercase();
+        GEO_BLOCK_HINTS.iter().any(|hint| text.contains(hint))
     };
-    if let Some(pos) = base_path.rfind('/') {
-        format!("{}/{}", &base[..pos], relative)
+
+    let code = resp.get("code").and_then(|v| v.as_i64()).unwrap_or(200);
+    let message = resp.get("message").and_then(|v| v.as_str()).unwrap_or("");
+    let blocked_by_message = code != 200 && mentions_geo(message);
+
+    let blocked_by_content = resp.get("content").is_some_and(|content| {
+        GEO_BLOCK_FIELDS
+            .iter()
+            .filter_map(|key| content.get(*key).and_then(|v| v.as_str()))
+            .any(mentions_geo)
+    });
+
+    if !blocked_by_message && !blocked_by_content {
+        return None;
+    }
+    eprintln!("🌍 Geo-blocked response (code {}): {}", code, message);
+
+    let has_proxy = NETWORK_OPTIONS
+        .read()
+        .map(|n| n.proxy.is_some())
+        .unwrap_or(false);
+    Some(if has_proxy {
+        format!(
+            "{}. 설정한 프록시도 같은 지역에서 접속되는 것 같습니다. 다른 지역의 프록시를 지정해주세요.",
+            GEO_BLOCKED_ERROR
+        )
     } else {
-        relative.to_string()
+        format!(
+            "{}. 설정에서 시청 가능한 지역의 프록시를 지정하면 받을 수 있습니다.",
+            GEO_BLOCKED_ERROR
+        )
+    })
+}
+
+/// API 응답 본문을 JSON으로 읽기
+///
+/// 점검 중에는 JSON 대신 HTML 페이지가 오므로, `Content-Type`이나 본문이 HTML이면
+/// 파싱 오류 대신 [`UNAVAILABLE_ERROR`]와 HTTP 상태를 돌려준다.
+async fn parse_api_json(
+    resp: reqwest::Response,
+    context: &str,
+) -> Result<serde_json::Value, String> {
+    let status = resp.status();
+    let is_html = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|t| t.to_ascii_lowercase().contains("html"));
+    let body = resp
+        .text()
+        .await
+        .map_err(|e| format!("{}: {}", context, e))?;
+
+    if is_html || body.trim_start().starts_with('<') {
+        return Err(format!("{} (HTTP {})", UNAVAILABLE_ERROR, status));
     }
+    serde_json::from_str(&body).map_err(|e| format!("{} (HTTP {}): {}", context, status, e))
 }
 
-fn sanitize_filename(s: &str) -> String {
-    let re = Regex::new(r#"[\\/*?:"<>|]"#).unwrap();
-    re.replace_all(s, "").to_string()
+/// API 응답 전체를 로그로 남길지 (진단용, 기본 꺼짐)
+static VERBOSE_LOGGING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+pub fn set_verbose_logging(enabled: bool) {
+    VERBOSE_LOGGING.store(enabled, std::sync::atomic::Ordering::Relaxed);
 }
 
-// ── ffmpeg 관련 ─────────────────────────────────────────
+/// 값을 가려서 출력할 키 (소문자로 비교, 부분 일치)
+const REDACTED_KEY_PARTS: [&str; 6] = ["key", "token", "auth", "session", "cookie", "hash"];
 
-fn app_ffmpeg_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let data_dir = app
-        .path()
-        .app_local_data_dir()
-        .map_err(|e| format!("앱 데이터 경로를 가져올 수 없습니다: {}", e))?;
-    Ok(data_dir.join("ffmpeg.exe"))
+/// 진단 로그가 켜져 있을 때만 API 응답을 출력 (키/토큰 값과 URL 쿼리는 가림)
+///
+/// 꺼져 있으면 직렬화도 하지 않는다.
+pub fn dump_api_response(label: &str, content: &serde_json::Value) {
+    let verbose = VERBOSE_LOGGING.load(std::sync::atomic::Ordering::Relaxed);
+    if !verbose && !download_log_active() {
+        return;
+    }
+    let dump = format!(
+        "{}: {}",
+        label,
+        serde_json::to_string_pretty(&redact_json(content)).unwrap_or_default()
+    );
+    log_download(&dump);
+    if verbose {
+        eprintln!("{}", dump);
+    }
 }
 
-pub async fn find_ffmpeg(app: &AppHandle) -> Option<PathBuf> {
-    // 1. 시스템 PATH 체크 (spawn_blocking으로 안정적으로)
-    let found_in_path = tokio::task::spawn_blocking(|| {
-        std::process::Command::new("ffmpeg")
-            .arg("-version")
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false)
-    })
-    .await
-    .unwrap_or(false);
+/// 다운로드 하나의 진단 로그 파일 (`download_<video_id>.log`)
+///
+/// [`with_download_log`] 안에서 실행되는 동안 API 응답(민감한 값은 가림), 세그먼트 실패,
+/// ffmpeg stderr가 이 파일에 기록된다.
+pub struct DownloadLog {
+    path: PathBuf,
+    file: std::sync::Mutex<std::fs::File>,
+    started: std::time::Instant,
+}
 
-    if found_in_path {
-        return Some(PathBuf::from("ffmpeg"));
+impl DownloadLog {
+    /// `dir`에 로그 파일 생성 (이미 있으면 덮어씀)
+    pub fn create(dir: &Path, video_id: &str) -> Result<Self, String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("로그 폴더 생성 실패: {}", e))?;
+        let path = dir.join(format!("download_{}.log", sanitize_filename(video_id)));
+        let file =
+            std::fs::File::create(&path).map_err(|e| format!("로그 파일 생성 실패: {}", e))?;
+        Ok(Self {
+            path,
+            file: std::sync::Mutex::new(file),
+            started: std::time::Instant::now(),
+        })
     }
 
-    // 2. 앱 로컬 데이터 폴더 체크
-    if let Ok(path) = app_ffmpeg_path(app) {
-        if path.exists() {
-            return Some(path);
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&self, message: &str) {
+        use std::io::Write;
+        if let Ok(mut file) = self.file.lock() {
+            let elapsed = self.started.elapsed().as_secs_f64();
+            let _ = writeln!(file, "[{:>9.3}s] {}", elapsed, message);
         }
     }
 
-    None
+    /// 성공한 다운로드의 로그 삭제
+    pub fn remove(&self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
 }
 
-pub async fn download_ffmpeg(app: &AppHandle) -> Result<PathBuf, String> {
-    let ffmpeg_dest = app_ffmpeg_path(app)?;
+tokio::task_local! {
+    static DOWNLOAD_LOG: std::sync::Arc<DownloadLog>;
+}
 
-    if ffmpeg_dest.exists() {
-        return Ok(ffmpeg_dest);
+/// `fut`을 실행하는 동안 [`log_download`] 기록을 `log`에 남김 (None이면 그냥 실행)
+pub async fn with_download_log<F: std::future::Future>(
+    log: Option<std::sync::Arc<DownloadLog>>,
+    fut: F,
+) -> F::Output {
+    match log {
+        Some(log) => DOWNLOAD_LOG.scope(log, fut).await,
+        None => fut.await,
     }
+}
 
-    if let Some(parent) = ffmpeg_dest.parent() {
-        fs::create_dir_all(parent)
-            .await
-            .map_err(|e| format!("폴더 생성 실패: {}", e))?;
+fn download_log_active() -> bool {
+    DOWNLOAD_LOG.try_with(|_| ()).is_ok()
+}
+
+/// 현재 다운로드의 로그 파일에 한 줄 기록 (로그가 없으면 무시)
+pub fn log_download(message: &str) {
+    let _ = DOWNLOAD_LOG.try_with(|log| log.write(message));
+}
+
+/// ffmpeg 실행 결과의 stderr 전체를 다운로드 로그에 기록
+fn log_ffmpeg_stderr(stderr: &[u8]) {
+    if download_log_active() {
+        log_download(&format!(
+            "ffmpeg stderr:\n{}",
+            String::from_utf8_lossy(stderr)
+        ));
     }
+}
 
-    let _ = app.emit(
-        "download-progress",
-        DownloadProgress {
-            stage: "ffmpeg-install".into(),
-            current: 0,
+fn redact_json(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| {
+                let lower = k.to_ascii_lowercase();
+                let sensitive =
+                    v.is_string() && REDACTED_KEY_PARTS.iter().any(|p| lower.contains(p));
+                let v = if sensitive {
+                    serde_json::Value::String("<redacted>".into())
+                } else {
+                    redact_json(v)
+                };
+                (k.clone(), v)
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().map(redact_json).collect(),
+        serde_json::Value::String(s) => serde_json::Value::String(redact_url_queries(s)),
+        other => other.clone(),
+    }
+}
+
+/// 문자열 안의 URL 쿼리(서명 토큰 등)를 `?<redacted>`로 바꿈 (playback JSON 문자열 포함)
+fn redact_url_queries(s: &str) -> String {
+    if !s.contains("://") {
+        return s.to_string();
+    }
+    let re = Regex::new(r#"(https?://[^\s"'?]+)\?[^\s"'\\]*"#).unwrap();
+    re.replace_all(s, "$1?<redacted>").to_string()
+}
+
+/// 쿠키로 로그인 상태 조회 API를 호출해 실제로 로그인된 세션인지 확인
+pub async fn check_login(nid_aut: &str, nid_ses: &str) -> Result<bool, String> {
+    let client = build_client_with_cookies(Some(nid_aut.to_string()), Some(nid_ses.to_string()));
+
+    let resp = api_get(
+        &client,
+        "https://comm-api.game.naver.com/nng_main/v1/user/getUserStatus",
+        "로그인 확인 요청 실패",
+        "로그인 확인 JSON 파싱 실패",
+    )
+    .await?;
+
+    Ok(resp
+        .get("content")
+        .and_then(|c| c.get("loggedIn"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false))
+}
+
+async fn fetch_playback(
+    client: &Client,
+    video_id: &str,
+    in_key: &str,
+) -> Result<serde_json::Value, String> {
+    let playback_url = format!(
+        "https://apis.naver.com/neonplayer/vodplay/v2/playback/{}?key={}",
+        video_id, in_key
+    );
+
+    api_get(
+        client,
+        &playback_url,
+        "재생 정보 요청 실패",
+        "재생 정보 JSON 파싱 실패",
+    )
+    .await
+}
+
+/// playback 응답의 첫 period에서 조건에 맞는 adaptationSet 찾기
+fn find_adaptation_set(
+    playback_resp: &serde_json::Value,
+    matches: impl Fn(&serde_json::Value) -> bool,
+) -> Option<&serde_json::Value> {
+    playback_resp
+        .get("period")
+        .and_then(|p| p.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|period| period.get("adaptationSet"))
+        .and_then(|a| a.as_array())
+        .and_then(|sets| sets.iter().find(|s| matches(s)))
+}
+
+/// adaptationSet의 mimeType이 `mime_type`인지
+fn has_mime_type(set: &serde_json::Value, mime_type: &str) -> bool {
+    set.get("mimeType").and_then(|m| m.as_str()) == Some(mime_type)
+}
+
+/// period의 supplementalProperty → thumbnailSet 배열
+fn thumbnail_sets(period: &serde_json::Value) -> Option<&Vec<serde_json::Value>> {
+    period
+        .get("supplementalProperty")
+        .and_then(|sp| sp.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|sp| sp.get("any"))
+        .and_then(|any| any.as_array())
+        .and_then(|arr| arr.iter().find(|item| item.get("thumbnailSet").is_some()))
+        .and_then(|item| item.get("thumbnailSet"))
+        .and_then(|ts| ts.as_array())
+}
+
+pub fn time_to_sec(t: &str) -> f64 {
+    if t.is_empty() {
+        return 0.0;
+    }
+    let parts: Vec<f64> = t.split(':').filter_map(|p| p.parse().ok()).collect();
+    match parts.len() {
+        3 => parts[0] * 3600.0 + parts[1] * 60.0 + parts[2],
+        2 => parts[0] * 60.0 + parts[1],
+        1 => parts[0],
+        _ => 0.0,
+    }
+}
+
+/// 플레이리스트(base) 기준으로 상대 URI 해석
+///
+/// base의 쿼리는 버리고 relative의 쿼리는 유지한다. 예: `init.mp4?token=abc`를
+/// `.../video/index.m3u8?sig=xyz` 기준으로 풀면 `.../video/init.mp4?token=abc`.
+/// `/`로 시작하는 경로나 `../`도 URL 규칙대로 해석한다.
+fn resolve_url(base: &str, relative: &str) -> String {
+    if relative.starts_with("http://") || relative.starts_with("https://") {
+        return relative.to_string();
+    }
+    if let Ok(url) = reqwest::Url::parse(base).and_then(|b| b.join(relative)) {
+        return url.to_string();
+    }
+    // Strip query string before finding last '/' in path
+    let base_path = match base.find('?') {
+        Some(q) => &base[..q],
+        None => base,
+    };
+    if let Some(pos) = base_path.rfind('/') {
+        format!("{}/{}", &base[..pos], relative)
+    } else {
+        relative.to_string()
+    }
+}
+
+fn sanitize_filename(s: &str) -> String {
+    let re = Regex::new(r#"[\\/*?:"<>|]"#).unwrap();
+    re.replace_all(s, "").to_string()
+}
+
+/// 결과 파일 이름 템플릿 (없으면 기본 `채널_제목[_시작_끝]` 형식)
+static FILENAME_TEMPLATE: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
+
+/// 이후 저장하는 파일 이름에 적용할 템플릿
+///
+/// `{channel}`, `{title}`, `{date}`(YYYY-MM-DD), `{start}`, `{end}`를 쓸 수 있다.
+/// 일괄 다운로드에서는 `{index}`(대기열 순번, 전체 개수 자릿수만큼 0으로 채움)도 쓸 수 있다.
+pub fn set_filename_template(template: Option<String>) {
+    if let Ok(mut current) = FILENAME_TEMPLATE.write() {
+        *current = template.filter(|t| !t.trim().is_empty());
+    }
+}
+
+/// "YYYY-MM-DD HH:MM:SS" 같은 일시에서 날짜 부분만
+fn date_part(date: Option<&str>) -> Option<&str> {
+    let date = date?.trim().get(..10)?;
+    let valid = date.char_indices().all(|(i, c)| match i {
+        4 | 7 => c == '-',
+        _ => c.is_ascii_digit(),
+    });
+    valid.then_some(date)
+}
+
+/// 일괄 다운로드 순번 (1부터, 전체 개수). 예: (3, 120) → "003"
+pub fn batch_index_tag(position: usize, total: usize) -> String {
+    let width = total.max(position).to_string().len();
+    format!("{:0width$}", position, width = width)
+}
+
+/// 설정된 템플릿으로 파일 이름(확장자 제외) 만들기, 템플릿이 없으면 None
+///
+/// 값이 없는 자리표시자는 지우고, 그 자리에 남은 구분자(`_`, `-`, 공백)도 정리한다.
+fn render_filename_template(fields: &[(&str, Option<&str>)]) -> Option<String> {
+    let template = FILENAME_TEMPLATE.read().ok()?.clone()?;
+    let mut name = template;
+    let mut omitted = false;
+    for (key, value) in fields {
+        let placeholder = format!("{{{}}}", key);
+        if name.contains(&placeholder) {
+            omitted |= value.is_none();
+            name = name.replace(&placeholder, value.unwrap_or(""));
+        }
+    }
+    if omitted {
+        let is_sep = |c: char| matches!(c, '_' | '-' | ' ');
+        let mut cleaned = String::with_capacity(name.len());
+        for c in name.chars() {
+            if !(is_sep(c) && cleaned.chars().last().is_some_and(is_sep)) {
+                cleaned.push(c);
+            }
+        }
+        name = cleaned.trim_matches(is_sep).to_string();
+    }
+    let name = sanitize_filename(&name);
+    (!name.trim().is_empty()).then_some(name)
+}
+
+// ── ffmpeg 관련 ─────────────────────────────────────────
+
+/// 앱이 직접 설치하는 ffmpeg 위치 (앱 로컬 데이터 폴더)
+pub fn app_ffmpeg_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| format!("앱 데이터 경로를 가져올 수 없습니다: {}", e))?;
+    Ok(data_dir.join("ffmpeg.exe"))
+}
+
+/// `ffmpeg -version`이 정상 종료되는지 확인 (spawn_blocking으로 안정적으로)
+async fn ffmpeg_runs(path: PathBuf) -> bool {
+    tokio::task::spawn_blocking(move || {
+        std::process::Command::new(path)
+            .arg("-version")
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// 리먹싱에 쓰는 비트스트림 필터 (`-bsfs` 목록에 있어야 함)
+const REQUIRED_BSFS: [&str; 1] = ["aac_adtstoasc"];
+
+/// PATH의 ffmpeg가 실제 ffmpeg이고 필요한 기능이 있는지 확인
+///
+/// avconv로 연결된 경우나 기능을 뺀 래퍼는 실행은 되지만 리먹싱에서 실패한다.
+async fn check_ffmpeg_features(path: &Path) -> Result<(), String> {
+    let banner = ffmpeg_version(path).await.unwrap_or_default();
+    if !banner.starts_with("ffmpeg version") {
+        return Err(format!("ffmpeg가 아닙니다 ({})", banner));
+    }
+
+    let run = |args: &'static [&'static str]| {
+        tokio::process::Command::new(path)
+            .args(args)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .output()
+    };
+    let bsfs = run(&["-hide_banner", "-bsfs"])
+        .await
+        .map_err(|e| format!("ffmpeg 실행 실패: {}", e))?;
+    let bsfs = String::from_utf8_lossy(&bsfs.stdout);
+    for bsf in REQUIRED_BSFS {
+        if !bsfs.lines().any(|l| l.trim() == bsf) {
+            return Err(format!("{} 비트스트림 필터가 없습니다", bsf));
+        }
+    }
+
+    let mp4_muxer = run(&["-hide_banner", "-h", "muxer=mp4"])
+        .await
+        .map_err(|e| format!("ffmpeg 실행 실패: {}", e))?;
+    if !String::from_utf8_lossy(&mp4_muxer.stdout).contains("movflags") {
+        return Err("mp4 muxer의 movflags 옵션이 없습니다".into());
+    }
+    Ok(())
+}
+
+/// PATH의 ffmpeg 사용 가능 여부 (앱 실행 중 한 번만 확인)
+static SYSTEM_FFMPEG_USABLE: tokio::sync::OnceCell<bool> = tokio::sync::OnceCell::const_new();
+
+async fn system_ffmpeg_usable() -> bool {
+    *SYSTEM_FFMPEG_USABLE
+        .get_or_init(|| async {
+            let path = PathBuf::from("ffmpeg");
+            if !ffmpeg_runs(path.clone()).await {
+                return false;
+            }
+            match check_ffmpeg_features(&path).await {
+                Ok(()) => true,
+                Err(e) => {
+                    eprintln!("⚠️ System ffmpeg rejected, using bundled build: {}", e);
+                    false
+                }
+            }
+        })
+        .await
+}
+
+/// 찾은 ffmpeg의 출처: PATH의 ffmpeg면 "system", 앱이 설치한 것이면 "bundled"
+pub fn ffmpeg_source(path: &Path) -> &'static str {
+    if path == Path::new("ffmpeg") {
+        "system"
+    } else {
+        "bundled"
+    }
+}
+
+pub async fn find_ffmpeg(app: &AppHandle) -> Option<PathBuf> {
+    // 1. 시스템 PATH 체크 (avconv/기능이 빠진 ffmpeg는 건너뜀)
+    if system_ffmpeg_usable().await {
+        return Some(PathBuf::from("ffmpeg"));
+    }
+
+    // 2. 앱 로컬 데이터 폴더 체크
+    if let Ok(path) = app_ffmpeg_path(app) {
+        if path.exists() {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+/// ffmpeg ZIP 압축 해제 시도 횟수 (백신 잠금 등 일시적인 실패 대비)
+const FFMPEG_EXTRACT_ATTEMPTS: u32 = 3;
+/// 압축 해제 재시도 사이 대기 시간
+const FFMPEG_EXTRACT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// ZIP에서 bin/ffmpeg.exe를 꺼내 `dest_path`에 저장 (시도마다 ZIP을 새로 연다)
+fn extract_ffmpeg_zip(zip_path: &Path, dest_path: &Path) -> Result<(), String> {
+    let file = std::fs::File::open(zip_path).map_err(|e| format!("ZIP 파일 열기 실패: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("ZIP 파싱 실패: {}", e))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("ZIP 엔트리 읽기 실패: {}", e))?;
+
+        if entry.name().ends_with("bin/ffmpeg.exe") {
+            let mut out = std::fs::File::create(dest_path)
+                .map_err(|e| format!("ffmpeg.exe 생성 실패: {}", e))?;
+            std::io::copy(&mut entry, &mut out)
+                .map_err(|e| format!("ffmpeg.exe 추출 실패: {}", e))?;
+            return Ok(());
+        }
+    }
+
+    Err("ZIP에서 ffmpeg.exe를 찾을 수 없습니다".into())
+}
+
+/// ffmpeg 설치 취소를 알리고 돌려줄 오류 메시지
+fn ffmpeg_install_cancelled_error(app: &AppHandle) -> String {
+    let message = "ffmpeg 설치가 취소되었습니다";
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "cancelled".into(),
+            current: 0,
+            total: 100,
+            message: message.into(),
+        },
+    );
+    message.into()
+}
+
+pub async fn download_ffmpeg(app: &AppHandle) -> Result<PathBuf, String> {
+    let ffmpeg_dest = app_ffmpeg_path(app)?;
+
+    if ffmpeg_dest.exists() {
+        return Ok(ffmpeg_dest);
+    }
+
+    if let Some(parent) = ffmpeg_dest.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("폴더 생성 실패: {}", e))?;
+    }
+
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "ffmpeg-install".into(),
+            current: 0,
             total: 100,
             message: "ffmpeg 다운로드 중...".into(),
         },
@@ -161,11 +1291,26 @@ pub async fn download_ffmpeg(app: &AppHandle) -> Result<PathBuf, String> {
         .build()
         .map_err(|e| format!("HTTP 클라이언트 생성 실패: {}", e))?;
 
-    let resp = client
-        .get(FFMPEG_DOWNLOAD_URL)
-        .send()
-        .await
-        .map_err(|e| format!("ffmpeg 다운로드 요청 실패: {}", e))?;
+    // 이전 설치에서 남은 취소 요청은 지우고, 응답을 기다리는 동안 온 취소는 살린다
+    let cancel_state = app.state::<CancelState>();
+    cancel_state
+        .ffmpeg_install
+        .store(false, std::sync::atomic::Ordering::SeqCst);
+
+    // 허가 대기나 응답 헤더를 기다리는 중에도 취소할 수 있게 함께 기다림
+    let (_permit, resp) = tokio::select! {
+        result = async {
+            let permit = request_permit().await;
+            let resp = client.get(FFMPEG_DOWNLOAD_URL).send().await;
+            (permit, resp)
+        } => result,
+        _ = cancel_state.ffmpeg_install_cancelled() => {
+            return Err(ffmpeg_install_cancelled_error(app));
+        }
+    };
+    let resp = resp
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("ffmpeg 다운로드 실패: {}", e))?;
 
     let total_size = resp.content_length().unwrap_or(0);
     let temp_zip = ffmpeg_dest.with_file_name("ffmpeg_temp.zip");
@@ -177,11 +1322,24 @@ pub async fn download_ffmpeg(app: &AppHandle) -> Result<PathBuf, String> {
     let mut downloaded: u64 = 0;
     let mut stream = resp.bytes_stream();
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("다운로드 중 오류: {}", e))?;
+    loop {
+        // 스트림이 멈춰 있어도 취소할 수 있게 취소 요청과 함께 기다림
+        let chunk = tokio::select! {
+            chunk = stream.next() => chunk,
+            _ = cancel_state.ffmpeg_install_cancelled() => {
+                drop(file);
+                let _ = fs::remove_file(&temp_zip).await;
+                return Err(ffmpeg_install_cancelled_error(app));
+            }
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+
+        let chunk = chunk.map_err(|e| format!("ffmpeg 다운로드 실패: {}", e))?;
         file.write_all(&chunk)
             .await
-            .map_err(|e| format!("파일 쓰기 실패: {}", e))?;
+            .map_err(|e| format!("ffmpeg 다운로드 실패: 파일 쓰기 오류: {}", e))?;
 
         downloaded += chunk.len() as u64;
         let percent = if total_size > 0 {
@@ -215,41 +1373,45 @@ pub async fn download_ffmpeg(app: &AppHandle) -> Result<PathBuf, String> {
         },
     );
 
-    let zip_path = temp_zip.clone();
-    let dest_path = ffmpeg_dest.clone();
-
-    tokio::task::spawn_blocking(move || {
-        let file =
-            std::fs::File::open(&zip_path).map_err(|e| format!("ZIP 파일 열기 실패: {}", e))?;
-        let mut archive =
-            zip::ZipArchive::new(file).map_err(|e| format!("ZIP 파싱 실패: {}", e))?;
-
-        let mut found = false;
-        for i in 0..archive.len() {
-            let mut entry = archive
-                .by_index(i)
-                .map_err(|e| format!("ZIP 엔트리 읽기 실패: {}", e))?;
-
-            let name = entry.name().to_string();
-            if name.ends_with("bin/ffmpeg.exe") {
-                let mut out = std::fs::File::create(&dest_path)
-                    .map_err(|e| format!("ffmpeg.exe 생성 실패: {}", e))?;
-                std::io::copy(&mut entry, &mut out)
-                    .map_err(|e| format!("ffmpeg.exe 추출 실패: {}", e))?;
-                found = true;
+    let mut last_error = None;
+    for attempt in 1..=FFMPEG_EXTRACT_ATTEMPTS {
+        let zip_path = temp_zip.clone();
+        let dest_path = ffmpeg_dest.clone();
+        let result = tokio::task::spawn_blocking(move || extract_ffmpeg_zip(&zip_path, &dest_path))
+            .await
+            .unwrap_or_else(|e| Err(format!("추출 작업 실패: {}", e)));
+        match result {
+            Ok(()) => {
+                last_error = None;
                 break;
             }
+            Err(e) => {
+                eprintln!(
+                    "⚠️ ffmpeg extraction attempt {}/{} failed: {}",
+                    attempt, FFMPEG_EXTRACT_ATTEMPTS, e
+                );
+                let _ = fs::remove_file(&ffmpeg_dest).await;
+                last_error = Some(e);
+                if attempt < FFMPEG_EXTRACT_ATTEMPTS {
+                    tokio::time::sleep(FFMPEG_EXTRACT_RETRY_DELAY).await;
+                }
+            }
         }
+    }
+    // 실패 시에도 ZIP은 지워서 다음 설치 때 손상됐을 수 있는 파일을 재사용하지 않게 한다
+    let _ = fs::remove_file(&temp_zip).await;
+    if let Some(e) = last_error {
+        return Err(format!("ffmpeg 압축 해제 실패: {}", e));
+    }
 
-        if !found {
-            return Err("ZIP에서 ffmpeg.exe를 찾을 수 없습니다".into());
-        }
-
-        let _ = std::fs::remove_file(&zip_path);
-        Ok::<(), String>(())
-    })
-    .await
-    .map_err(|e| format!("추출 작업 실패: {}", e))??;
+    // 추출한 ffmpeg.exe가 실제로 실행되는지 확인 (백신 격리, 아키텍처 불일치 등)
+    if !ffmpeg_runs(ffmpeg_dest.clone()).await {
+        let _ = fs::remove_file(&ffmpeg_dest).await;
+        return Err(
+            "설치한 ffmpeg.exe를 실행할 수 없습니다. 백신 프로그램의 차단 여부를 확인해주세요."
+                .into(),
+        );
+    }
 
     let _ = app.emit(
         "download-progress",
@@ -264,47 +1426,228 @@ pub async fn download_ffmpeg(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(ffmpeg_dest)
 }
 
-// ── 클립 관련 ─────────────────────────────────────────
+/// `ffmpeg -version` 출력의 첫 줄 (예: "ffmpeg version 6.1 ...")
+pub async fn ffmpeg_version(ffmpeg_path: &Path) -> Option<String> {
+    let output = tokio::process::Command::new(ffmpeg_path)
+        .arg("-version")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .await
+        .ok()?;
 
-pub async fn get_clip_info(clip_uid: &str) -> Result<ClipInfo, String> {
-    let client = build_client();
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|l| l.trim().to_string())
+}
 
-    // 1단계: play-info에서 videoId, inKey, 제목, 채널 가져오기
-    let api_url = format!(
-        "https://api.chzzk.naver.com/service/v1/play-info/clip/{}",
-        clip_uid
-    );
+// ── 환경 진단 ─────────────────────────────────────────
 
-    let resp: serde_json::Value = client
-        .get(&api_url)
+/// chzzk API 서버에 연결 가능한지 확인
+pub async fn check_api_reachable() -> Result<(), String> {
+    let _permit = request_permit().await;
+    build_client()
+        .get("https://api.chzzk.naver.com/")
+        .timeout(std::time::Duration::from_secs(10))
         .send()
         .await
-        .map_err(|e| format!("클립 API 요청 실패: {}", e))?
-        .json()
+        .map(|_| ())
+        .map_err(|e| format!("api.chzzk.naver.com 연결 실패: {}", e))
+}
+
+/// 디버그용 조회를 허용할 호스트인지 (naver.com 및 하위 도메인만)
+fn is_debug_host_allowed(url: &reqwest::Url) -> bool {
+    url.scheme() == "https"
+        && url
+            .host_str()
+            .map(|h| h == "naver.com" || h.ends_with(".naver.com"))
+            .unwrap_or(false)
+}
+
+/// 이슈 첨부용으로 API 응답 원문을 가져온다
+///
+/// 저장된 쿠키로 인증된 GET 요청을 보내고, JSON이면 보기 좋게 정리해 반환한다.
+/// 응답에 쿠키 값이 그대로 들어 있으면 가려서 반환한다.
+pub async fn debug_fetch(
+    url: &str,
+    nid_aut: Option<String>,
+    nid_ses: Option<String>,
+) -> Result<String, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("잘못된 URL: {}", e))?;
+    if !is_debug_host_allowed(&parsed) {
+        return Err("chzzk/naver API 주소(https://*.naver.com)만 조회할 수 있습니다".into());
+    }
+
+    let secrets: Vec<String> = [&nid_aut, &nid_ses]
+        .into_iter()
+        .flatten()
+        .filter(|v| !v.is_empty())
+        .cloned()
+        .collect();
+
+    let _permit = request_permit().await;
+    let resp = build_client_with_cookies(nid_aut, nid_ses)
+        .get(parsed)
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
         .await
-        .map_err(|e| format!("클립 JSON 파싱 실패: {}", e))?;
+        .map_err(|e| format!("요청 실패: {}", e))?;
 
-    let content = resp
-        .get("content")
-        .ok_or("클립 API 응답에 content가 없습니다")?;
+    let status = resp.status();
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| format!("응답 읽기 실패: {}", e))?;
 
-    // 디버깅: 사용 가능한 모든 필드 출력
-    eprintln!("📋 Clip API response content: {}", serde_json::to_string_pretty(content).unwrap_or_default());
+    let body = serde_json::from_str::<serde_json::Value>(&text)
+        .ok()
+        .and_then(|json| serde_json::to_string_pretty(&json).ok())
+        .unwrap_or(text);
 
-    let title = content
-        .get("contentTitle")
-        .and_then(|v| v.as_str())
-        .unwrap_or("clip")
-        .to_string();
+    let body = secrets.iter().fold(body, |body, secret| {
+        body.replace(secret.as_str(), "[REDACTED]")
+    });
 
-    let channel = content
-        .get("ownerChannel")
-        .and_then(|c| c.get("channelName"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("channel")
-        .to_string();
+    Ok(format!("HTTP {}\n\n{}", status, body))
+}
 
-    let video_id = content
+/// 디렉토리에 파일을 쓸 수 있는지 확인 (임시 파일 생성 후 삭제)
+pub async fn is_dir_writable(dir: &Path) -> bool {
+    let probe = dir.join(".chzzk_write_test");
+    let ok = fs::write(&probe, b"ok").await.is_ok();
+    let _ = fs::remove_file(&probe).await;
+    ok
+}
+
+/// 경로가 속한 디스크의 남은 용량 (바이트)
+pub async fn available_disk_space(path: &Path) -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    let output = {
+        let script = format!(
+            "([System.IO.DriveInfo]::new('{}')).AvailableFreeSpace",
+            path.to_string_lossy().replace('\'', "''")
+        );
+        tokio::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", &script])
+            .output()
+            .await
+            .ok()?
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let output = tokio::process::Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    #[cfg(target_os = "windows")]
+    {
+        stdout.trim().parse().ok()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        // Filesystem 1024-blocks Used Available Capacity Mounted-on
+        stdout
+            .lines()
+            .nth(1)
+            .and_then(|l| l.split_whitespace().nth(3))
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+    }
+}
+
+// ── 클립 관련 ─────────────────────────────────────────
+
+pub async fn get_clip_info(clip_uid: &str) -> Result<ClipInfo, String> {
+    let client = build_client();
+
+    // 1단계: play-info에서 videoId, inKey, 제목, 채널 가져오기
+    let api_url = format!(
+        "https://api.chzzk.naver.com/service/v1/play-info/clip/{}",
+        clip_uid
+    );
+
+    let resp = api_get(
+        &client,
+        &api_url,
+        "클립 API 요청 실패",
+        "클립 JSON 파싱 실패",
+    )
+    .await?;
+    let play_info = parse_clip_play_info(&resp)?;
+
+    // 2단계: vodplay API에서 직접 MP4 URL 가져오기
+    let playback_resp = fetch_playback(&client, &play_info.video_id, &play_info.in_key).await?;
+    let (variants, thumbnail) = parse_clip_playback(&playback_resp);
+    let mp4_url = variants
+        .first()
+        .map(|v| v.url.clone())
+        .ok_or("클립 MP4 URL을 찾을 수 없습니다")?;
+    let thumbnail = large_thumbnail_url(&client, &thumbnail).await;
+
+    Ok(ClipInfo {
+        title: play_info.title,
+        channel: play_info.channel,
+        mp4_url,
+        thumbnail,
+        variants,
+        created_date: play_info.created_date,
+    })
+}
+
+/// 클립 play-info 응답에서 꺼낸 정보
+pub struct ClipPlayInfo {
+    pub title: String,
+    pub channel: String,
+    pub created_date: Option<String>,
+    pub video_id: String,
+    pub in_key: String,
+}
+
+/// 클립 play-info 응답 해석 (네트워크 요청 없음)
+pub fn parse_clip_play_info(resp: &serde_json::Value) -> Result<ClipPlayInfo, String> {
+    if let Some(e) = geo_block_error(resp) {
+        return Err(e);
+    }
+
+    let content = resp
+        .get("content")
+        .ok_or("클립 API 응답에 content가 없습니다")?;
+
+    // 디버깅: 사용 가능한 모든 필드 출력 (진단 로그를 켠 경우만)
+    dump_api_response("📋 Clip API response content", content);
+
+    let title = content
+        .get("contentTitle")
+        .and_then(|v| v.as_str())
+        .unwrap_or("clip")
+        .to_string();
+
+    let channel = content
+        .get("ownerChannel")
+        .and_then(|c| c.get("channelName"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("channel")
+        .to_string();
+
+    let created_date = content
+        .get("createdDate")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    let video_id = content
         .get("videoId")
         .and_then(|v| v.as_str())
         .ok_or("클립 videoId를 찾을 수 없습니다")?;
@@ -314,58 +1657,54 @@ pub async fn get_clip_info(clip_uid: &str) -> Result<ClipInfo, String> {
         .and_then(|v| v.as_str())
         .ok_or("클립 inKey를 찾을 수 없습니다")?;
 
-    // 2단계: vodplay API에서 직접 MP4 URL 가져오기
-    let playback_url = format!(
-        "https://apis.naver.com/neonplayer/vodplay/v2/playback/{}?key={}",
-        video_id, in_key
-    );
-
-    let playback_resp: serde_json::Value = client
-        .get(&playback_url)
-        .send()
-        .await
-        .map_err(|e| format!("재생 정보 요청 실패: {}", e))?
-        .json()
-        .await
-        .map_err(|e| format!("재생 정보 JSON 파싱 실패: {}", e))?;
-
-    let first_period = playback_resp
-        .get("period")
-        .and_then(|p| p.as_array())
-        .and_then(|arr| arr.first());
+    Ok(ClipPlayInfo {
+        title,
+        channel,
+        created_date,
+        video_id: video_id.to_string(),
+        in_key: in_key.to_string(),
+    })
+}
 
-    // period[0].adaptationSet에서 mimeType이 "video/mp4"인 항목 찾기
-    let mp4_url = first_period
-        .and_then(|period| period.get("adaptationSet"))
-        .and_then(|a| a.as_array())
-        .and_then(|sets| {
-            sets.iter().find(|s| {
-                s.get("mimeType")
-                    .and_then(|m| m.as_str())
-                    == Some("video/mp4")
-            })
-        })
+/// 클립 playback 응답에서 MP4 화질 목록과 썸네일 URL 꺼내기 (네트워크 요청 없음)
+pub fn parse_clip_playback(playback_resp: &serde_json::Value) -> (Vec<ClipVariant>, String) {
+    // period[0].adaptationSet에서 mimeType이 "video/mp4"인 항목의 representation 전체
+    let reps = find_adaptation_set(playback_resp, |s| has_mime_type(s, "video/mp4"))
         .and_then(|set| set.get("representation"))
         .and_then(|r| r.as_array())
-        .and_then(|reps| reps.first())
-        .and_then(|rep| rep.get("baseURL"))
-        .and_then(|b| b.as_array())
-        .and_then(|urls| urls.first())
-        .and_then(|url| url.get("value"))
-        .and_then(|v| v.as_str())
-        .ok_or("클립 MP4 URL을 찾을 수 없습니다")?
-        .to_string();
+        .map(|v| v.as_slice())
+        .unwrap_or_default();
+    let variants: Vec<ClipVariant> = reps
+        .iter()
+        .enumerate()
+        .filter_map(|(i, rep)| {
+            let url = rep
+                .get("baseURL")
+                .and_then(|b| b.as_array())
+                .and_then(|urls| urls.first())
+                .and_then(|url| url.get("value"))
+                .and_then(|v| v.as_str())?;
+            let number = |key: &str| rep.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+            Some(ClipVariant {
+                id: rep
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .map(|id| id.to_string())
+                    .unwrap_or_else(|| i.to_string()),
+                url: url.to_string(),
+                width: number("width") as u32,
+                height: number("height") as u32,
+                bandwidth: number("bandwidth"),
+            })
+        })
+        .collect();
 
     // supplementalProperty → thumbnailSet → 첫 번째 썸네일 URL
-    let thumbnail = first_period
-        .and_then(|p| p.get("supplementalProperty"))
-        .and_then(|sp| sp.as_array())
+    let thumbnail = playback_resp
+        .get("period")
+        .and_then(|p| p.as_array())
         .and_then(|arr| arr.first())
-        .and_then(|sp| sp.get("any"))
-        .and_then(|any| any.as_array())
-        .and_then(|arr| arr.iter().find(|item| item.get("thumbnailSet").is_some()))
-        .and_then(|item| item.get("thumbnailSet"))
-        .and_then(|ts| ts.as_array())
+        .and_then(thumbnail_sets)
         .and_then(|arr| arr.first())
         .and_then(|tset| tset.get("thumbnail"))
         .and_then(|t| t.as_array())
@@ -373,21 +1712,136 @@ pub async fn get_clip_info(clip_uid: &str) -> Result<ClipInfo, String> {
         .and_then(|thumb| thumb.get("source"))
         .and_then(|s| s.get("value"))
         .and_then(|v| v.as_str())
-        .map(|url| {
-            // ?type=s80 → 제거하거나 큰 사이즈로 변경
-            if let Some(pos) = url.find("?type=") {
-                url[..pos].to_string()
-            } else {
-                url.to_string()
-            }
+        .unwrap_or_default()
+        .to_string();
+
+    (variants, thumbnail)
+}
+
+/// 큰 썸네일을 요청할 때 쓰는 `type` 값
+const LARGE_THUMBNAIL_TYPE: &str = "f1920_1080";
+
+/// 썸네일 URL의 크기 지정 (`type=s80`, `type={type}`)
+static THUMBNAIL_TYPE_RE: std::sync::LazyLock<Regex> =
+    std::sync::LazyLock::new(|| Regex::new(r"type=(\{type\}|[^&]*)").unwrap());
+
+/// 썸네일 URL의 `type=s80` 같은 크기 지정(또는 `{type}` 자리표시자)을 큰 크기로 바꾼 URL
+///
+/// CDN이 바꾼 URL을 받아 주는지 확인하고, 실패하면 원래 URL을 그대로 쓴다.
+/// 크기 지정이 없는 URL은 확인 없이 그대로 반환한다.
+async fn large_thumbnail_url(client: &Client, url: &str) -> String {
+    if url.is_empty() || !THUMBNAIL_TYPE_RE.is_match(url) {
+        return url.to_string();
+    }
+    let large = THUMBNAIL_TYPE_RE
+        .replace(url, format!("type={}", LARGE_THUMBNAIL_TYPE))
+        .to_string();
+
+    let _permit = request_permit().await;
+    let ok = client
+        .get(&large)
+        .header(reqwest::header::RANGE, "bytes=0-0")
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .is_ok_and(|resp| resp.status().is_success());
+    if ok {
+        large
+    } else {
+        eprintln!("⚠️ Large thumbnail unavailable, keeping {}", url);
+        url.to_string()
+    }
+}
+
+/// 썸네일을 큰 크기 URL로 바꾼 VideoInfo (.nfo용, CDN 확인 요청은 이때 한 번만)
+pub async fn with_large_thumbnail(info: &VideoInfo) -> VideoInfo {
+    VideoInfo {
+        thumbnail: large_thumbnail_url(&build_client(), &info.thumbnail).await,
+        ..info.clone()
+    }
+}
+
+/// 채널 클립 목록의 한 항목
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelClip {
+    pub clip_uid: String,
+    pub title: String,
+    pub duration: u64,
+    pub thumbnail: String,
+}
+
+/// 채널 클립 목록 한 페이지
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipPage {
+    pub clips: Vec<ChannelClip>,
+    pub page: u32,
+    pub total_pages: u32,
+    pub total_count: u64,
+}
+
+pub async fn list_channel_clips(
+    channel_id: &str,
+    page: u32,
+    size: u32,
+    nid_aut: Option<String>,
+    nid_ses: Option<String>,
+) -> Result<ClipPage, String> {
+    let client = build_client_with_cookies(nid_aut, nid_ses);
+    let api_url = format!(
+        "https://api.chzzk.naver.com/service/v1/channels/{}/clips?page={}&size={}&orderType=RECENT",
+        channel_id, page, size
+    );
+
+    let resp = api_get(
+        &client,
+        &api_url,
+        "클립 목록 요청 실패",
+        "클립 목록 JSON 파싱 실패",
+    )
+    .await?;
+
+    let content = resp
+        .get("content")
+        .ok_or("클립 목록 응답에 content가 없습니다")?;
+
+    // 클립이 없는 채널은 data가 비어 있거나 없음
+    let clips = content
+        .get("data")
+        .and_then(|d| d.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    Some(ChannelClip {
+                        clip_uid: item.get("clipUID")?.as_str()?.to_string(),
+                        title: item
+                            .get("clipTitle")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("clip")
+                            .to_string(),
+                        duration: item.get("duration").and_then(|v| v.as_u64()).unwrap_or(0),
+                        thumbnail: item
+                            .get("thumbnailImageUrl")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_string(),
+                    })
+                })
+                .collect()
         })
         .unwrap_or_default();
 
-    Ok(ClipInfo {
-        title,
-        channel,
-        mp4_url,
-        thumbnail,
+    Ok(ClipPage {
+        clips,
+        page,
+        total_pages: content
+            .get("totalPages")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
+        total_count: content
+            .get("totalCount")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0),
     })
 }
 
@@ -395,11 +1849,22 @@ pub async fn download_clip(
     app: &AppHandle,
     clip_info: &ClipInfo,
     output_dir: &str,
+    batch: Option<(usize, usize)>,
 ) -> Result<String, String> {
     let safe_channel = sanitize_filename(&clip_info.channel);
     let safe_title = sanitize_filename(&clip_info.title);
-    let filename = format!("{}_{}.mp4", safe_channel, safe_title);
-    let output_path = Path::new(output_dir).join(&filename);
+    let date = date_part(clip_info.created_date.as_deref());
+    let index = batch.map(|(position, total)| batch_index_tag(position, total));
+    let stem = render_filename_template(&[
+        ("channel", Some(&safe_channel)),
+        ("title", Some(&safe_title)),
+        ("date", date),
+        ("start", None),
+        ("end", None),
+        ("index", index.as_deref()),
+    ])
+    .unwrap_or_else(|| format!("{}_{}", safe_channel, safe_title));
+    let output_path = Path::new(output_dir).join(format!("{}.mp4", stem));
 
     let _ = app.emit(
         "download-progress",
@@ -412,6 +1877,7 @@ pub async fn download_clip(
     );
 
     let client = build_client();
+    let _permit = request_permit().await;
     let resp = client
         .get(&clip_info.mp4_url)
         .send()
@@ -433,6 +1899,7 @@ pub async fn download_clip(
             .map_err(|e| format!("파일 쓰기 실패: {}", e))?;
 
         downloaded += chunk.len() as u64;
+        record_throughput(chunk.len() as u64);
         let percent = if total_size > 0 {
             (downloaded * 100 / total_size) as u32
         } else {
@@ -459,531 +1926,4395 @@ pub async fn download_clip(
 
 // ── VOD 관련 ─────────────────────────────────────────
 
-pub async fn get_video_info(video_id: &str) -> Result<VideoInfo, String> {
-    get_video_info_with_cookies(video_id, None, None).await
+/// HLS와 DASH가 모두 가능할 때 어느 쪽을 쓸지
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreferFormat {
+    /// HLS(liveRewindPlaybackJson)가 있으면 HLS, 없으면 DASH
+    #[default]
+    Auto,
+    Hls,
+    Dash,
+}
+
+pub async fn get_video_info(video_id: &str, prefer: PreferFormat) -> Result<VideoInfo, String> {
+    get_video_info_with_cookies(video_id, None, None, prefer).await
 }
 
+/// 비디오 정보를 조회할 API 버전 (v3 우선, 실패 시 v2)
+const VIDEO_API_VERSIONS: [&str; 2] = ["v3", "v2"];
+
 pub async fn get_video_info_with_cookies(
     video_id: &str,
     nid_aut: Option<String>,
     nid_ses: Option<String>,
+    prefer: PreferFormat,
 ) -> Result<VideoInfo, String> {
+    let cookies = nid_aut.clone().zip(nid_ses.clone());
     let client = build_client_with_cookies(nid_aut, nid_ses);
-    let api_url = format!(
-        "https://api.chzzk.naver.com/service/v3/videos/{}",
-        video_id
-    );
+    let mut last_error = String::new();
+
+    for version in VIDEO_API_VERSIONS {
+        match fetch_video_info_version(&client, video_id, version, prefer).await {
+            Ok(mut info) => {
+                eprintln!("📹 Video info loaded via {} API", version);
+                info.cookies = cookies;
+                fill_missing_duration(&mut info).await;
+                return Ok(info);
+            }
+            // 속도 제한과 지역 제한은 다른 버전으로 바꿔도 풀리지 않음
+            Err(e) if e == RATE_LIMITED_ERROR || e.starts_with(GEO_BLOCKED_ERROR) => return Err(e),
+            Err(e) => {
+                eprintln!("⚠️ {} videos API failed: {}", version, e);
+                last_error = e;
+            }
+        }
+    }
+
+    // 최후 수단: API 응답 형식이 바뀌어도 시청 페이지에 담긴 JSON에서 정보를 찾아봄
+    match fetch_video_info_from_watch_page(&client, video_id, prefer).await {
+        Ok(mut info) => {
+            eprintln!("🛟 Video info loaded from watch page HTML (API fallback)");
+            info.cookies = cookies;
+            fill_missing_duration(&mut info).await;
+            Ok(info)
+        }
+        Err(e) => {
+            eprintln!("⚠️ Watch page fallback failed: {}", e);
+            Err(last_error)
+        }
+    }
+}
+
+/// 시청 페이지 HTML을 받아 그 안의 JSON에서 VideoInfo 추출 (API가 모두 실패했을 때만)
+async fn fetch_video_info_from_watch_page(
+    client: &Client,
+    video_id: &str,
+    prefer: PreferFormat,
+) -> Result<VideoInfo, String> {
+    let content = fetch_watch_page_content(client, video_id).await?;
+    parse_video_content(&content, prefer)
+}
 
-    let resp: serde_json::Value = client
-        .get(&api_url)
+/// 시청 페이지 HTML에서 videos API content와 같은 모양의 객체 꺼내기
+async fn fetch_watch_page_content(
+    client: &Client,
+    video_id: &str,
+) -> Result<serde_json::Value, String> {
+    let page_url = format!("https://chzzk.naver.com/video/{}", video_id);
+    let _permit = request_permit().await;
+    let html = client
+        .get(&page_url)
         .send()
         .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?
-        .json()
+        .map_err(|e| format!("시청 페이지 요청 실패: {}", e))?
+        .text()
         .await
-        .map_err(|e| format!("JSON 파싱 실패: {}", e))?;
+        .map_err(|e| format!("시청 페이지 읽기 실패: {}", e))?;
 
-    let content = resp
-        .get("content")
-        .ok_or("API 응답에 content가 없습니다")?;
+    let content =
+        watch_page_content(&html).ok_or("시청 페이지에서 영상 정보를 찾을 수 없습니다")?;
+    dump_api_response("Watch page content", &content);
+    Ok(content)
+}
 
-    let title = content
-        .get("videoTitle")
-        .and_then(|v| v.as_str())
-        .unwrap_or("video")
-        .to_string();
+/// 시청 페이지 HTML의 `__NEXT_DATA__`(또는 다른 JSON 스크립트)에서 videos API의
+/// content와 같은 모양의 객체(`videoTitle`이 있는 객체) 찾기 (네트워크 요청 없음)
+pub fn watch_page_content(html: &str) -> Option<serde_json::Value> {
+    let script_re =
+        Regex::new(r#"(?s)<script[^>]*type="application/json"[^>]*>(.*?)</script>"#).unwrap();
+    let mut scripts: Vec<(bool, &str)> = script_re
+        .captures_iter(html)
+        .filter_map(|cap| {
+            let is_next_data = cap.get(0)?.as_str().contains("__NEXT_DATA__");
+            Some((is_next_data, cap.get(1)?.as_str()))
+        })
+        .collect();
+    // __NEXT_DATA__를 먼저 확인
+    scripts.sort_by_key(|(is_next_data, _)| !is_next_data);
 
-    let channel = content
-        .get("channel")
-        .and_then(|c| c.get("channelName"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("channel")
-        .to_string();
+    scripts.into_iter().find_map(|(_, text)| {
+        let json: serde_json::Value = serde_json::from_str(text.trim()).ok()?;
+        find_video_content(&json, 0).cloned()
+    })
+}
 
-    let duration = content
-        .get("duration")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(0);
+/// JSON 트리에서 `videoTitle`과 재생 정보 키가 있는 객체를 깊이 우선으로 찾기
+fn find_video_content(value: &serde_json::Value, depth: usize) -> Option<&serde_json::Value> {
+    const MAX_DEPTH: usize = 12;
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match value {
+        serde_json::Value::Object(map) => {
+            let has_playback = map.contains_key("inKey")
+                || HLS_PLAYBACK_KEYS.iter().any(|key| map.contains_key(*key));
+            if map.get("videoTitle").is_some_and(|t| t.is_string()) && has_playback {
+                return Some(value);
+            }
+            map.values().find_map(|v| find_video_content(v, depth + 1))
+        }
+        serde_json::Value::Array(items) => {
+            items.iter().find_map(|v| find_video_content(v, depth + 1))
+        }
+        _ => None,
+    }
+}
 
-    let thumbnail = content
-        .get("thumbnailImageUrl")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
+/// API가 duration을 0으로 줄 때(방금 끝난 VOD 등) 타임라인/플레이리스트로 계산한 길이로 채움
+///
+/// 구간 끝을 비워 둔 다운로드가 길이 0으로 계산되지 않도록 한다. 계산에 실패하면 그대로 둔다.
+pub async fn fill_missing_duration(info: &mut VideoInfo) {
+    if info.duration > 0 {
+        return;
+    }
+    match playback_duration(info).await {
+        Ok(secs) if secs > 0.0 => {
+            eprintln!(
+                "⏱️ API duration is 0 - using playback duration {:.1}s",
+                secs
+            );
+            info.duration = secs.ceil() as u64;
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("⚠️ Could not compute VOD duration: {}", e),
+    }
+}
 
-    // liveRewindPlaybackJson이 있으면 HLS, 없으면 DASH
-    let (master_url, is_dash, dash_video_id, dash_in_key) = if let Some(media_json_str) = content
-        .get("liveRewindPlaybackJson")
-        .and_then(|v| v.as_str())
-    {
-        // 기존 HLS 방식
-        let media_data: serde_json::Value = serde_json::from_str(media_json_str)
-            .map_err(|e| format!("미디어 JSON 파싱 실패: {}", e))?;
+/// DASH 타임라인 또는 최고 화질 HLS 플레이리스트의 전체 길이 (초)
+async fn playback_duration(info: &VideoInfo) -> Result<f64, String> {
+    if info.is_dash {
+        let video_id = info
+            .dash_video_id
+            .as_ref()
+            .ok_or("DASH videoId가 없습니다")?;
+        let in_key = info.dash_in_key.as_ref().ok_or("DASH inKey가 없습니다")?;
+        let (_, _, end_secs) = dash_timeline_bounds(video_id, in_key, None).await?;
+        return Ok(end_secs);
+    }
 
-        let url = media_data
-            .get("media")
-            .and_then(|m| m.as_array())
-            .and_then(|arr| arr.first())
-            .and_then(|item| item.get("path"))
-            .and_then(|v| v.as_str())
-            .ok_or("Master playlist URL을 찾을 수 없습니다")?
-            .to_string();
+    let client = build_client();
+    let _permit = request_permit().await;
+    let master_text = client
+        .get(&info.master_url)
+        .send()
+        .await
+        .map_err(|e| format!("Master playlist 요청 실패: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Master playlist 읽기 실패: {}", e))?;
+    let quality_url = select_variant_url(&info.master_url, &master_text, None)?;
+    let playlist_text = client
+        .get(&quality_url)
+        .send()
+        .await
+        .map_err(|e| format!("Quality playlist 요청 실패: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Quality playlist 읽기 실패: {}", e))?;
+    Ok(playlist_duration(&playlist_text))
+}
 
-        (url, false, None, None)
-    } else {
-        // DASH 방식 - videoId와 inKey 저장
-        let video_id_key = content
-            .get("videoId")
-            .and_then(|v| v.as_str())
-            .ok_or("videoId를 찾을 수 없습니다")?
-            .to_string();
+async fn fetch_video_info_version(
+    client: &Client,
+    video_id: &str,
+    version: &str,
+    prefer: PreferFormat,
+) -> Result<VideoInfo, String> {
+    let content = fetch_video_content_version(client, video_id, version).await?;
+    parse_video_content(&content, prefer)
+}
 
-        let in_key = content
-            .get("inKey")
-            .and_then(|v| v.as_str())
-            .ok_or("inKey를 찾을 수 없습니다")?
-            .to_string();
+/// videos API 한 버전의 응답에서 content 객체 꺼내기
+async fn fetch_video_content_version(
+    client: &Client,
+    video_id: &str,
+    version: &str,
+) -> Result<serde_json::Value, String> {
+    let api_url = format!(
+        "https://api.chzzk.naver.com/service/{}/videos/{}",
+        version, video_id
+    );
 
-        // placeholder URL (실제로는 사용 안 함)
-        ("DASH".to_string(), true, Some(video_id_key), Some(in_key))
-    };
+    let resp = api_get(client, &api_url, "API 요청 실패", "JSON 파싱 실패").await?;
+    if let Some(e) = geo_block_error(&resp) {
+        return Err(e);
+    }
 
-    Ok(VideoInfo {
-        title,
+    resp.get("content")
+        .filter(|c| c.is_object())
+        .cloned()
+        .ok_or_else(|| "API 응답에 content가 없습니다".to_string())
+}
+
+/// videos API content 조회 ([`VIDEO_API_VERSIONS`] 순서로 시도하고, 모두 실패하면 시청 페이지)
+///
+/// get_video_info_with_cookies와 같은 순서로 찾지만 VideoInfo로 해석하지 않은 원본을 돌려준다.
+pub async fn fetch_video_content(
+    client: &Client,
+    video_id: &str,
+) -> Result<serde_json::Value, String> {
+    let mut last_error = String::new();
+
+    for version in VIDEO_API_VERSIONS {
+        match fetch_video_content_version(client, video_id, version).await {
+            Ok(content) => {
+                eprintln!("📹 Video content loaded via {} API", version);
+                return Ok(content);
+            }
+            // 속도 제한과 지역 제한은 다른 버전이나 시청 페이지로도 풀리지 않음
+            Err(e) if e == RATE_LIMITED_ERROR || e.starts_with(GEO_BLOCKED_ERROR) => return Err(e),
+            Err(e) => {
+                eprintln!("⚠️ {} videos API failed: {}", version, e);
+                last_error = e;
+            }
+        }
+    }
+
+    match fetch_watch_page_content(client, video_id).await {
+        Ok(content) => {
+            eprintln!(
+                "🛟 Video content loaded from watch page HTML (API failed: {})",
+                last_error
+            );
+            Ok(content)
+        }
+        Err(e) => {
+            eprintln!("⚠️ Watch page fallback failed: {}", e);
+            Err(last_error)
+        }
+    }
+}
+
+/// HLS 미디어 JSON이 들어 있을 수 있는 content 키 (앞쪽 우선)
+const HLS_PLAYBACK_KEYS: [&str; 3] = [
+    "liveRewindPlaybackJson",
+    "videoPlaybackJson",
+    "playbackJson",
+];
+
+/// content에서 HLS 미디어 JSON 찾기
+///
+/// [`HLS_PLAYBACK_KEYS`]를 순서대로 확인하고, 없으면 `vodStatus` 같은 한 단계
+/// 아래 객체 안도 찾는다. 값이 문자열이 아니라 객체로 와도 JSON 문자열로 돌려준다.
+pub fn hls_playback_json(content: &serde_json::Value) -> Option<String> {
+    let lookup = |obj: &serde_json::Value, key: &str| match obj.get(key)? {
+        serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+        v @ serde_json::Value::Object(_) => Some(v.to_string()),
+        _ => None,
+    };
+
+    for key in HLS_PLAYBACK_KEYS {
+        if let Some(json) = lookup(content, key) {
+            eprintln!("🎞️ HLS playback found under \"{}\"", key);
+            return Some(json);
+        }
+    }
+
+    let nested = content.as_object()?.iter().filter(|(_, v)| v.is_object());
+    for (wrapper, obj) in nested {
+        for key in HLS_PLAYBACK_KEYS {
+            if let Some(json) = lookup(obj, key) {
+                eprintln!("🎞️ HLS playback found under \"{}.{}\"", wrapper, key);
+                return Some(json);
+            }
+        }
+    }
+    None
+}
+
+/// content가 로그인(성인 인증) 없이는 볼 수 없는 영상이라고 알려주는지
+///
+/// 로그인하지 않았다는 상태(`NOT_LOGIN_USER`)이거나, 성인 영상인데 성인 인증 상태가 아닌 경우.
+pub fn requires_login(content: &serde_json::Value) -> bool {
+    let adult = content
+        .get("adult")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let adult_status = content
+        .get("userAdultStatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    adult_status == "NOT_LOGIN_USER" || (adult && adult_status != "ADULT")
+}
+
+/// 재생 정보를 얻지 못했을 때 content에서 원인을 추정해 구분되는 오류 메시지 생성
+///
+/// 오류는 UI가 구분할 수 있도록 `LOGIN_REQUIRED` / `PROCESSING` /
+/// `UNSUPPORTED_FORMAT` 접두사로 시작한다.
+pub fn no_playback_reason(content: &serde_json::Value, has_credentials: bool) -> String {
+    let has_playback = hls_playback_json(content).is_some()
+        || content.get("inKey").and_then(|v| v.as_str()).is_some();
+    let adult = content.get("adult").and_then(|v| v.as_bool()).unwrap_or(false);
+    let adult_status = content
+        .get("userAdultStatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let vod_status = content
+        .get("vodStatus")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+
+    if !has_playback && (adult || adult_status == "NOT_LOGIN_USER") {
+        return if has_credentials {
+            "LOGIN_REQUIRED: 저장된 로그인 정보로 볼 수 없는 영상입니다. 다시 로그인하거나 성인 인증을 확인해주세요.".into()
+        } else {
+            "LOGIN_REQUIRED: 로그인(성인 인증)이 필요한 영상입니다.".into()
+        };
+    }
+
+    if matches!(vod_status, "UPLOAD" | "PROCESSING" | "ENCODING" | "NONE") {
+        return format!(
+            "PROCESSING: 아직 처리 중인 영상입니다 ({}). 잠시 후 다시 시도해주세요.",
+            vod_status
+        );
+    }
+
+    "UNSUPPORTED_FORMAT: 재생 정보를 해석할 수 없어 화질 목록을 가져오지 못했습니다.".into()
+}
+
+/// content의 챕터 목록 (`videoChapterList` 또는 `chapters`, 없으면 빈 목록)
+///
+/// 각 항목의 시작 시각은 `startTime`/`time`(초) 또는 `startTimeMs`(밀리초)에서 읽는다.
+fn parse_chapters(content: &serde_json::Value) -> Vec<Chapter> {
+    let Some(items) = ["videoChapterList", "chapters"]
+        .iter()
+        .find_map(|key| content.get(*key).and_then(|v| v.as_array()))
+    else {
+        return Vec::new();
+    };
+
+    let mut chapters: Vec<Chapter> = items
+        .iter()
+        .filter_map(|item| {
+            let start_secs = item
+                .get("startTime")
+                .or_else(|| item.get("time"))
+                .and_then(|v| v.as_f64())
+                .or_else(|| {
+                    item.get("startTimeMs")
+                        .and_then(|v| v.as_f64())
+                        .map(|ms| ms / 1000.0)
+                })?;
+            let title = item
+                .get("title")
+                .or_else(|| item.get("chapterTitle"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            Some(Chapter { title, start_secs })
+        })
+        .collect();
+    chapters.sort_by(|a, b| a.start_secs.total_cmp(&b.start_secs));
+    chapters
+}
+
+/// 다운로드 구간(start..end)에 걸치는 챕터를 구간 시작 기준 시각으로 옮긴 목록
+pub fn chapters_in_range(chapters: &[Chapter], start_secs: f64, end_secs: f64) -> Vec<Chapter> {
+    let mut shifted: Vec<Chapter> = Vec::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let next_start = chapters
+            .get(i + 1)
+            .map(|c| c.start_secs)
+            .unwrap_or(f64::MAX);
+        // 구간과 겹치지 않는 챕터는 제외
+        if next_start <= start_secs || chapter.start_secs >= end_secs {
+            continue;
+        }
+        shifted.push(Chapter {
+            title: chapter.title.clone(),
+            start_secs: (chapter.start_secs - start_secs).max(0.0),
+        });
+    }
+    shifted
+}
+
+/// ffmpeg 메타데이터(FFMETADATA1) 형식의 챕터 파일 내용
+fn ffmetadata_chapters(chapters: &[Chapter], total_secs: f64) -> String {
+    let escape = |s: &str| {
+        s.replace('\\', "\\\\")
+            .replace('=', "\\=")
+            .replace(';', "\\;")
+            .replace('#', "\\#")
+            .replace('\n', "\\\n")
+    };
+
+    let mut text = String::from(";FFMETADATA1\n");
+    for (i, chapter) in chapters.iter().enumerate() {
+        let end_secs = chapters
+            .get(i + 1)
+            .map(|c| c.start_secs)
+            .unwrap_or(total_secs)
+            .max(chapter.start_secs);
+        text.push_str(&format!(
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            (chapter.start_secs * 1000.0) as u64,
+            (end_secs * 1000.0) as u64,
+            escape(&chapter.title)
+        ));
+    }
+    text
+}
+
+/// videos API(v2/v3 공통)의 content 객체에서 VideoInfo 추출
+pub fn parse_video_content(
+    content: &serde_json::Value,
+    prefer: PreferFormat,
+) -> Result<VideoInfo, String> {
+    let title = content
+        .get("videoTitle")
+        .and_then(|v| v.as_str())
+        .unwrap_or("video")
+        .to_string();
+
+    let channel = content
+        .get("channel")
+        .and_then(|c| c.get("channelName"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("channel")
+        .to_string();
+
+    let duration = content
+        .get("duration")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    let thumbnail = content
+        .get("thumbnailImageUrl")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let publish_date = content
+        .get("publishDate")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    let category = content
+        .get("videoCategoryValue")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string());
+
+    let chapters = parse_chapters(content);
+
+    // HLS 미디어 JSON(liveRewindPlaybackJson 등)이 있으면 HLS, 없으면 DASH
+    // (둘 다 가능하고 DASH를 선호하면 DASH)
+    let has_dash = content.get("videoId").and_then(|v| v.as_str()).is_some()
+        && content.get("inKey").and_then(|v| v.as_str()).is_some();
+    let hls_json =
+        hls_playback_json(content).filter(|_| !(prefer == PreferFormat::Dash && has_dash));
+    if prefer == PreferFormat::Hls && hls_json.is_none() {
+        eprintln!("⚠️ HLS preferred but unavailable - using DASH");
+    }
+
+    let (master_url, is_dash, dash_video_id, dash_in_key) = if let Some(media_json_str) = hls_json
+    {
+        // 기존 HLS 방식
+        let media_data: serde_json::Value = serde_json::from_str(&media_json_str)
+            .map_err(|e| format!("미디어 JSON 파싱 실패: {}", e))?;
+
+        let url = media_data
+            .get("media")
+            .and_then(|m| m.as_array())
+            .and_then(|arr| arr.first())
+            .and_then(|item| item.get("path"))
+            .and_then(|v| v.as_str())
+            .ok_or("Master playlist URL을 찾을 수 없습니다")?
+            .to_string();
+
+        (url, false, None, None)
+    } else {
+        // DASH 방식 - videoId와 inKey 저장
+        let video_id_key = content
+            .get("videoId")
+            .and_then(|v| v.as_str())
+            .ok_or("videoId를 찾을 수 없습니다")?
+            .to_string();
+
+        let in_key = content
+            .get("inKey")
+            .and_then(|v| v.as_str())
+            .ok_or("inKey를 찾을 수 없습니다")?
+            .to_string();
+
+        // placeholder URL (실제로는 사용 안 함)
+        ("DASH".to_string(), true, Some(video_id_key), Some(in_key))
+    };
+
+    Ok(VideoInfo {
+        title,
         channel,
         master_url,
         duration,
         thumbnail,
+        publish_date,
+        category,
+        chapters,
         is_dash,
         dash_video_id,
         dash_in_key,
+        cookies: None,
     })
 }
 
-pub async fn parse_dash_segments(
-    video_id: &str,
-    in_key: &str,
-    start_time: &str,
-    end_time: &str,
-    quality_id: Option<&str>,
-) -> Result<Vec<String>, String> {
-    let client = build_client();
-
-    // DASH playback API 호출
-    let playback_url = format!(
-        "https://apis.naver.com/neonplayer/vodplay/v2/playback/{}?key={}",
-        video_id, in_key
-    );
+/// DASH VOD의 썸네일 스프라이트 시트 목록과 시간 매핑
+///
+/// HLS(다시보기) VOD는 썸네일 세트가 없으므로 빈 목록을 반환한다.
+pub async fn get_thumbnail_sprites(info: &VideoInfo) -> Result<Vec<ThumbnailSprite>, String> {
+    let (Some(video_id), Some(in_key)) = (&info.dash_video_id, &info.dash_in_key) else {
+        return Ok(Vec::new());
+    };
 
-    let playback_resp: serde_json::Value = client
-        .get(&playback_url)
-        .send()
-        .await
-        .map_err(|e| format!("재생 정보 요청 실패: {}", e))?
-        .json()
-        .await
-        .map_err(|e| format!("재생 정보 JSON 파싱 실패: {}", e))?;
+    let client = build_client();
+    let playback_resp = fetch_playback(&client, video_id, in_key).await?;
 
-    // HLS adaptationSet 찾기 (video/mp2t)
-    let first_period = playback_resp
+    let Some(sets) = playback_resp
         .get("period")
         .and_then(|p| p.as_array())
         .and_then(|arr| arr.first())
-        .ok_or("period를 찾을 수 없습니다")?;
+        .and_then(thumbnail_sets)
+    else {
+        return Ok(Vec::new());
+    };
 
-    let hls_set = first_period
-        .get("adaptationSet")
-        .and_then(|a| a.as_array())
-        .and_then(|sets| {
-            sets.iter().find(|s| {
-                s.get("mimeType").and_then(|m| m.as_str()) == Some("video/mp2t")
-            })
-        })
-        .ok_or("HLS adaptationSet을 찾을 수 없습니다")?;
+    let read_u32 = |v: &serde_json::Value, key: &str| {
+        v.get(key).and_then(|n| n.as_u64()).unwrap_or(0) as u32
+    };
 
-    // representation 선택 (화질 ID 지정 또는 최고 화질)
-    let representations = hls_set
-        .get("representation")
+    let mut sprites = Vec::new();
+    for tset in sets {
+        let columns = read_u32(tset, "columnCount").max(1);
+        let rows = read_u32(tset, "rowCount").max(1);
+        let width = read_u32(tset, "width");
+        let height = read_u32(tset, "height");
+        // interval은 밀리초 단위
+        let interval = tset
+            .get("interval")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0)
+            / 1000.0;
+        let sheet_span = interval * (columns * rows) as f64;
+
+        let thumbs = tset
+            .get("thumbnail")
+            .and_then(|t| t.as_array())
+            .map(|v| v.as_slice())
+            .unwrap_or_default();
+
+        for (i, thumb) in thumbs.iter().enumerate() {
+            let Some(url) = thumb
+                .get("source")
+                .and_then(|s| s.get("value"))
+                .and_then(|v| v.as_str())
+            else {
+                continue;
+            };
+
+            let start_time = sheet_span * i as f64;
+            sprites.push(ThumbnailSprite {
+                url: url.to_string(),
+                start_time,
+                end_time: (start_time + sheet_span).min(info.duration.max(1) as f64),
+                interval,
+                columns,
+                rows,
+                width,
+                height,
+            });
+        }
+    }
+
+    Ok(sprites)
+}
+
+/// 화질 선택 후보 하나 (DASH는 representation ID, HLS는 variant playlist 경로)
+#[derive(Debug, Clone)]
+pub struct QualityVariant {
+    pub id: String,
+    pub bandwidth: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl QualityVariant {
+    /// 화면 표시용 이름 (`1080p (8.0Mbps)`, 해상도를 모르면 `8.0Mbps`)
+    pub fn label(&self) -> String {
+        let mbps = self.bandwidth as f64 / 1_000_000.0;
+        if self.height > 0 {
+            format!("{}p ({:.1}Mbps)", self.height, mbps)
+        } else {
+            format!("{:.1}Mbps", mbps)
+        }
+    }
+}
+
+/// 화질 후보 정렬 기준: bandwidth → 해상도 높은 순, 같으면 ID 사전순
+///
+/// 같은 bandwidth의 후보가 여러 개여도 실행마다 같은 결과가 나오도록 한다.
+fn compare_variants(a: &QualityVariant, b: &QualityVariant) -> std::cmp::Ordering {
+    b.bandwidth
+        .cmp(&a.bandwidth)
+        .then(b.height.cmp(&a.height))
+        .then(a.id.cmp(&b.id))
+}
+
+fn dash_rep_variant(rep: &serde_json::Value) -> QualityVariant {
+    QualityVariant {
+        id: rep
+            .get("id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        bandwidth: rep.get("bandwidth").and_then(|b| b.as_u64()).unwrap_or(0),
+        width: rep.get("width").and_then(|w| w.as_u64()).unwrap_or(0) as u32,
+        height: rep.get("height").and_then(|h| h.as_u64()).unwrap_or(0) as u32,
+    }
+}
+
+/// 선택 가능한 화질 목록 (최고 화질 먼저, 안정 정렬)
+pub async fn quality_variants(info: &VideoInfo) -> Result<Vec<QualityVariant>, String> {
+    fetch_quality_variants(&build_client(), info).await
+}
+
+/// `client`(쿠키/연결 설정 포함)로 화질 목록 조회 (최고 화질 먼저, 안정 정렬)
+pub async fn fetch_quality_variants(
+    client: &Client,
+    info: &VideoInfo,
+) -> Result<Vec<QualityVariant>, String> {
+    let mut variants = if info.is_dash {
+        let video_id = info
+            .dash_video_id
+            .as_ref()
+            .ok_or("DASH videoId가 없습니다")?;
+        let in_key = info.dash_in_key.as_ref().ok_or("DASH inKey가 없습니다")?;
+        let playback_resp = fetch_playback(client, video_id, in_key).await?;
+        parse_dash_quality_variants(&playback_resp)
+    } else {
+        let _permit = request_permit().await;
+        let master_text = client
+            .get(&info.master_url)
+            .send()
+            .await
+            .map_err(|e| format!("Master playlist 요청 실패: {}", e))?
+            .text()
+            .await
+            .map_err(|e| format!("Master playlist 읽기 실패: {}", e))?;
+        parse_master_playlist_variants(&master_text)
+    };
+
+    variants.sort_by(compare_variants);
+    Ok(variants)
+}
+
+/// DASH playback 응답의 화질 목록 (정렬 전, 네트워크 요청 없음)
+pub fn parse_dash_quality_variants(playback_resp: &serde_json::Value) -> Vec<QualityVariant> {
+    find_adaptation_set(playback_resp, |s| has_mime_type(s, "video/mp2t"))
+        .and_then(|set| set.get("representation"))
         .and_then(|r| r.as_array())
-        .ok_or("representation을 찾을 수 없습니다")?;
+        .map(|reps| {
+            reps.iter()
+                .map(dash_rep_variant)
+                .filter(|v| !v.id.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
-    let selected_rep = if let Some(qid) = quality_id {
-        // 지정된 화질 ID로 찾기
-        representations
-            .iter()
-            .find(|r| r.get("id").and_then(|v| v.as_str()) == Some(qid))
-            .ok_or(format!("화질 ID '{}'를 찾을 수 없습니다", qid))?
+/// HLS master playlist 본문의 화질 목록 (정렬 전, ID는 variant playlist 경로)
+pub fn parse_master_playlist_variants(master_text: &str) -> Vec<QualityVariant> {
+    let mut variants = Vec::new();
+    let lines: Vec<&str> = master_text.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(params) = line.strip_prefix("#EXT-X-STREAM-INF:") else {
+            continue;
+        };
+        let Some(variant) = lines.get(i + 1).map(|l| l.trim()) else {
+            continue;
+        };
+        if variant.is_empty() || variant.starts_with('#') {
+            continue;
+        }
+        let bandwidth = hls_attribute(params, "BANDWIDTH")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let (width, height) = hls_attribute(params, "RESOLUTION")
+            .and_then(|r| {
+                let (w, h) = r.split_once('x')?;
+                Some((w.parse().unwrap_or(0), h.parse().unwrap_or(0)))
+            })
+            .unwrap_or((0, 0));
+        variants.push(QualityVariant {
+            id: variant.to_string(),
+            bandwidth,
+            width,
+            height,
+        });
+    }
+    variants
+}
+
+/// 가장 높은 화질 ID
+pub async fn highest_quality_id(info: &VideoInfo) -> Result<Option<String>, String> {
+    Ok(quality_variants(info).await?.into_iter().next().map(|v| v.id))
+}
+
+/// 높이가 `max_height` 이하인 화질 중 가장 높은 화질 ID
+///
+/// 조건에 맞는 화질이 없으면(높이를 모르는 화질은 제외) 가장 낮은 화질을 고른다.
+pub async fn capped_quality_id(
+    info: &VideoInfo,
+    max_height: u32,
+) -> Result<Option<String>, String> {
+    let variants = quality_variants(info).await?;
+    let capped = variants
+        .iter()
+        .find(|v| v.height > 0 && v.height <= max_height)
+        .or_else(|| variants.last());
+    Ok(capped.map(|v| v.id.clone()))
+}
+
+/// `failed_quality`보다 낮은 화질 중 첫 세그먼트 확인을 통과하는 가장 높은 화질과 그 세그먼트 URL
+///
+/// 선택한 화질의 세그먼트에 접근할 수 없을 때(403/404 등) 대신 받을 화질을 찾는다.
+/// 통과하는 화질이 없으면 None.
+pub async fn fallback_quality(
+    info: &VideoInfo,
+    start_time: &str,
+    end_time: &str,
+    failed_quality: Option<&str>,
+) -> Result<Option<(QualityVariant, Vec<String>)>, String> {
+    let variants = quality_variants(info).await?;
+    let lower = match failed_quality.and_then(|q| variants.iter().position(|v| v.id == q)) {
+        Some(pos) => &variants[pos + 1..],
+        None => &variants[..],
+    };
+
+    for variant in lower {
+        let segments = match resolve_segments(info, start_time, end_time, Some(&variant.id)).await {
+            Ok(segments) if !segments.is_empty() => segments,
+            Ok(_) => continue,
+            Err(e) => {
+                eprintln!("⚠️ Fallback quality {} unavailable: {}", variant.id, e);
+                continue;
+            }
+        };
+        match preflight_segment(&segments[0]).await {
+            Ok(()) => return Ok(Some((variant.clone(), segments))),
+            Err(e) => eprintln!("⚠️ Fallback quality {} failed preflight: {}", variant.id, e),
+        }
+    }
+    Ok(None)
+}
+
+/// 가장 낮은 bandwidth의 화질 ID
+pub async fn lowest_quality_id(info: &VideoInfo) -> Result<Option<String>, String> {
+    Ok(quality_variants(info).await?.into_iter().last().map(|v| v.id))
+}
+
+/// HLS 속성 목록(`KEY=VALUE,KEY="VALUE"`)에서 값 하나 읽기
+fn hls_attribute(params: &str, key: &str) -> Option<String> {
+    let re = Regex::new(&format!(r#"(?:^|,){}=("([^"]*)"|[^,]*)"#, key)).unwrap();
+    re.captures(params).map(|c| {
+        c.get(2)
+            .or_else(|| c.get(1))
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_default()
+    })
+}
+
+/// VOD의 자막 트랙 목록 (자막이 없으면 빈 목록)
+///
+/// DASH는 playback 응답의 `text/vtt` adaptationSet, HLS는 master playlist의
+/// `#EXT-X-MEDIA:TYPE=SUBTITLES` 항목에서 찾는다.
+pub async fn list_subtitle_tracks(info: &VideoInfo) -> Result<Vec<SubtitleTrack>, String> {
+    let client = build_client();
+    let mut tracks = Vec::new();
+
+    if info.is_dash {
+        let video_id = info.dash_video_id.as_ref().ok_or("DASH videoId가 없습니다")?;
+        let in_key = info.dash_in_key.as_ref().ok_or("DASH inKey가 없습니다")?;
+        let playback_resp = fetch_playback(&client, video_id, in_key).await?;
+
+        let sets = playback_resp
+            .get("period")
+            .and_then(|p| p.as_array())
+            .and_then(|arr| arr.first())
+            .and_then(|period| period.get("adaptationSet"))
+            .and_then(|a| a.as_array())
+            .map(|v| v.as_slice())
+            .unwrap_or_default();
+
+        for set in sets {
+            if set.get("mimeType").and_then(|m| m.as_str()) != Some("text/vtt") {
+                continue;
+            }
+            let language = set
+                .get("lang")
+                .and_then(|v| v.as_str())
+                .unwrap_or("und")
+                .to_string();
+            let reps = set
+                .get("representation")
+                .and_then(|r| r.as_array())
+                .map(|v| v.as_slice())
+                .unwrap_or_default();
+
+            for rep in reps {
+                let Some(url) = rep
+                    .get("baseURL")
+                    .and_then(|b| b.as_array())
+                    .and_then(|arr| arr.first())
+                    .and_then(|u| u.get("value"))
+                    .and_then(|v| v.as_str())
+                else {
+                    continue;
+                };
+                let id = rep
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| format!("{}-{}", language, tracks.len()));
+                let label = set
+                    .get("label")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(&language)
+                    .to_string();
+
+                tracks.push(SubtitleTrack {
+                    id,
+                    language: language.clone(),
+                    label,
+                    url: url.to_string(),
+                    is_playlist: false,
+                });
+            }
+        }
     } else {
-        // 최고 bandwidth의 representation 선택
-        representations
-            .iter()
-            .max_by_key(|r| r.get("bandwidth").and_then(|b| b.as_u64()).unwrap_or(0))
-            .ok_or("최고 품질 representation을 찾을 수 없습니다")?
+        let _permit = request_permit().await;
+        let master_text = client
+            .get(&info.master_url)
+            .send()
+            .await
+            .map_err(|e| format!("Master playlist 요청 실패: {}", e))?
+            .text()
+            .await
+            .map_err(|e| format!("Master playlist 읽기 실패: {}", e))?;
+
+        for line in master_text.lines() {
+            let Some(params) = line.strip_prefix("#EXT-X-MEDIA:") else {
+                continue;
+            };
+            if hls_attribute(params, "TYPE").as_deref() != Some("SUBTITLES") {
+                continue;
+            }
+            let Some(uri) = hls_attribute(params, "URI") else {
+                continue;
+            };
+            let language = hls_attribute(params, "LANGUAGE").unwrap_or_else(|| "und".into());
+            let label = hls_attribute(params, "NAME").unwrap_or_else(|| language.clone());
+
+            tracks.push(SubtitleTrack {
+                id: format!("{}-{}", language, tracks.len()),
+                language,
+                label,
+                url: resolve_url(&info.master_url, &uri),
+                is_playlist: true,
+            });
+        }
+    }
+
+    Ok(tracks)
+}
+
+/// 자막 트랙을 내려받아 하나의 .vtt 파일로 저장
+pub async fn download_subtitle_track(
+    track: &SubtitleTrack,
+    output_path: &Path,
+) -> Result<(), String> {
+    let client = build_client();
+
+    let fetch_text = |url: String| {
+        let client = client.clone();
+        async move {
+            let _permit = request_permit().await;
+            client
+                .get(&url)
+                .send()
+                .await
+                .map_err(|e| format!("자막 요청 실패: {}", e))?
+                .text()
+                .await
+                .map_err(|e| format!("자막 읽기 실패: {}", e))
+        }
+    };
+
+    let vtt = if track.is_playlist {
+        // 자막 플레이리스트의 VTT 조각을 순서대로 이어붙이고, 중복 WEBVTT 헤더는 제거
+        let playlist = fetch_text(track.url.clone()).await?;
+        let mut merged = String::from("WEBVTT\n\n");
+        for line in playlist.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let part = fetch_text(resolve_url(&track.url, line)).await?;
+            let body = part
+                .trim_start_matches('\u{feff}')
+                .strip_prefix("WEBVTT")
+                .map(|rest| rest.split_once("\n\n").map(|(_, b)| b).unwrap_or(""))
+                .unwrap_or(&part);
+            merged.push_str(body.trim_end());
+            merged.push_str("\n\n");
+        }
+        merged
+    } else {
+        fetch_text(track.url.clone()).await?
     };
 
-    let best_rep = selected_rep;
+    fs::write(output_path, vtt)
+        .await
+        .map_err(|e| format!("자막 파일 쓰기 실패: {}", e))
+}
+
+/// VOD 종류(DASH/HLS)에 맞게 구간의 세그먼트 URL 목록 가져오기
+pub async fn resolve_segments(
+    info: &VideoInfo,
+    start_time: &str,
+    end_time: &str,
+    quality_id: Option<&str>,
+) -> Result<Vec<String>, String> {
+    if info.is_dash {
+        let dash_video_id = info.dash_video_id.as_ref().ok_or("DASH videoId가 없습니다")?;
+        let dash_in_key = info.dash_in_key.as_ref().ok_or("DASH inKey가 없습니다")?;
+        parse_dash_segments(dash_video_id, dash_in_key, start_time, end_time, quality_id).await
+    } else {
+        let cookies = info.cookies.as_ref();
+        parse_segments(&info.master_url, start_time, end_time, quality_id, cookies).await
+    }
+}
+
+/// resolve_segments와 같은 순서의 세그먼트별 길이 (초, 초기화 세그먼트는 0)
+pub async fn resolve_segment_durations(
+    info: &VideoInfo,
+    start_time: &str,
+    end_time: &str,
+    quality_id: Option<&str>,
+) -> Result<Vec<f64>, String> {
+    if info.is_dash {
+        let dash_video_id = info
+            .dash_video_id
+            .as_ref()
+            .ok_or("DASH videoId가 없습니다")?;
+        let dash_in_key = info.dash_in_key.as_ref().ok_or("DASH inKey가 없습니다")?;
+        let timeline = fetch_dash_timeline(dash_video_id, dash_in_key, quality_id).await?;
+        let (segments, _) = timeline_segments(&timeline, start_time, end_time);
+        Ok(segments.into_iter().map(|(_, duration)| duration).collect())
+    } else {
+        let (quality_url, playlist_text) =
+            fetch_media_playlist(&info.master_url, quality_id).await?;
+        let segments = parse_media_playlist(&playlist_text, &quality_url, start_time, end_time)?;
+        Ok(segments.into_iter().map(|s| s.duration).collect())
+    }
+}
+
+/// 로컬 재생 목록의 세그먼트 폴더: `<이름>_segments`
+pub fn local_playlist_segment_dir(playlist_path: &Path) -> PathBuf {
+    let stem = playlist_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "playlist".into());
+    playlist_path.with_file_name(format!("{}_segments", stem))
+}
+
+/// 받은 세그먼트 파일을 가리키는 로컬 `.m3u8` 본문 (네트워크 요청 없음)
+///
+/// `segments`는 (세그먼트 폴더 기준 상대 경로, 길이) 목록으로, 빠진 세그먼트는 None.
+/// 길이가 0인 첫 항목은 fMP4 초기화 세그먼트로 보고 `#EXT-X-MAP`으로 쓴다.
+pub fn build_local_playlist(segments: &[Option<(String, f64)>]) -> String {
+    let target_duration = segments
+        .iter()
+        .flatten()
+        .map(|(_, d)| d.ceil() as u64)
+        .max()
+        .unwrap_or(0);
+    let mut lines = vec![
+        "#EXTM3U".to_string(),
+        "#EXT-X-VERSION:7".to_string(),
+        format!("#EXT-X-TARGETDURATION:{}", target_duration),
+        "#EXT-X-MEDIA-SEQUENCE:0".to_string(),
+        "#EXT-X-PLAYLIST-TYPE:VOD".to_string(),
+    ];
+    let mut gap = false;
+    for (i, segment) in segments.iter().enumerate() {
+        let Some((uri, duration)) = segment else {
+            gap = true;
+            continue;
+        };
+        if i == 0 && *duration == 0.0 {
+            lines.push(format!("#EXT-X-MAP:URI=\"{}\"", uri));
+            continue;
+        }
+        // 빠진 세그먼트 뒤에서는 타임스탬프가 이어지지 않으므로 표시
+        if std::mem::take(&mut gap) {
+            lines.push("#EXT-X-DISCONTINUITY".to_string());
+        }
+        lines.push(format!("#EXTINF:{:.3},", duration));
+        lines.push(uri.clone());
+    }
+    lines.push("#EXT-X-ENDLIST".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// 병합/리먹싱 없이 임시 폴더를 `<이름>_segments`로 옮기고 이를 가리키는 `.m3u8` 작성
+///
+/// `durations`는 resolve_segment_durations 결과로 세그먼트 수와 같아야 한다.
+pub async fn write_local_playlist(
+    temp_dir: &Path,
+    durations: &[f64],
+    playlist_path: &Path,
+) -> Result<(), String> {
+    let segment_dir = local_playlist_segment_dir(playlist_path);
+    if segment_dir.exists() {
+        return Err(format!(
+            "세그먼트 폴더가 이미 있습니다: {}",
+            segment_dir.display()
+        ));
+    }
+    let _ = fs::remove_file(ResumeManifest::path(temp_dir)).await;
+    fs::rename(temp_dir, &segment_dir)
+        .await
+        .map_err(|e| format!("세그먼트 폴더 이동 실패: {}", e))?;
+
+    let dir_name = segment_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let segments: Vec<Option<(String, f64)>> = durations
+        .iter()
+        .enumerate()
+        .map(|(i, &duration)| {
+            let file_name = segment_file_name(i);
+            segment_dir
+                .join(&file_name)
+                .exists()
+                .then(|| (format!("{}/{}", dir_name, file_name), duration))
+        })
+        .collect();
+
+    fs::write(playlist_path, build_local_playlist(&segments))
+        .await
+        .map_err(|e| format!("재생 목록 쓰기 실패: {}", e))?;
+    eprintln!(
+        "📝 Local playlist written: {} ({} segments)",
+        playlist_path.display(),
+        segments.len()
+    );
+    Ok(())
+}
+
+/// 첫 세그먼트에 1바이트만 요청해 접근 가능한지 미리 확인
+///
+/// 권한/토큰 문제(401/403/404/410)면 바로 실패해 빈 파일을 한참 받는 일을 막는다.
+/// 네트워크 오류 등 그 밖의 실패는 본 다운로드의 재시도에 맡기고 통과시킨다.
+pub async fn preflight_segment(url: &str) -> Result<(), String> {
+    let _permit = request_permit().await;
+    let resp = match build_client()
+        .get(url)
+        .header(reqwest::header::RANGE, "bytes=0-0")
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+    {
+        Ok(resp) => resp,
+        Err(e) => {
+            eprintln!("⚠️ Segment preflight request failed: {}", e);
+            return Ok(());
+        }
+    };
+
+    let status = resp.status();
+    if matches!(status.as_u16(), 401 | 403 | 404 | 410) {
+        return Err(format!(
+            "선택한 화질의 세그먼트에 접근할 수 없습니다 (로그인 필요?) (HTTP {})",
+            status
+        ));
+    }
+    Ok(())
+}
+
+/// 세그먼트가 MPEG-TS인지 (리먹싱 없이 그대로 저장 가능한지)
+///
+/// 초기화 세그먼트 없이 TS 컨테이너로 받는지로 판단한다. DASH 영상은 `video/mp2t`
+/// adaptationSet에서 초기화 세그먼트 없이 받으므로 fMP4 조각이 섞이지 않으면 TS이고,
+/// HLS는 모든 세그먼트가 `.ts`여야 한다 (`#EXT-X-MAP` 초기화 세그먼트가 있는 fMP4는 아님).
+pub fn is_ts_source(info: &VideoInfo, segments: &[String]) -> bool {
+    let extension = |url: &str| {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        file_name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+    };
+    if info.is_dash {
+        segments
+            .iter()
+            .all(|url| !matches!(extension(url).as_deref(), Some("mp4" | "m4s" | "m4v")))
+    } else {
+        segments
+            .iter()
+            .all(|url| extension(url).as_deref() == Some("ts"))
+    }
+}
+
+/// 세그먼트 URL 목록을 다시 받아오는 함수 (CDN 호스트 장애 시 재해석용)
+pub type SegmentResolver =
+    Box<dyn Fn() -> futures::future::BoxFuture<'static, Result<Vec<String>, String>> + Send + Sync>;
+
+/// 구간 정보로 세그먼트 URL을 다시 받아오는 SegmentResolver 생성
+pub fn segment_resolver(
+    info: &VideoInfo,
+    start_time: &str,
+    end_time: &str,
+    quality_id: Option<&str>,
+) -> SegmentResolver {
+    let info = info.clone();
+    let start_time = start_time.to_string();
+    let end_time = end_time.to_string();
+    let quality_id = quality_id.map(|q| q.to_string());
+
+    Box::new(move || {
+        let info = info.clone();
+        let start_time = start_time.clone();
+        let end_time = end_time.clone();
+        let quality_id = quality_id.clone();
+        Box::pin(async move {
+            resolve_segments(&info, &start_time, &end_time, quality_id.as_deref()).await
+        })
+    })
+}
+
+/// 이어받기용 매니페스트 (임시 폴더의 manifest.json)
+///
+/// 이어받을 때 처음과 같은 화질을 선택하도록 선택한 화질 ID를 기록한다.
+/// 구간과 결과 파일 경로는 실패한 세그먼트만 다시 받을 때(retry_failed_segments) 쓴다.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResumeManifest {
+    pub quality_id: Option<String>,
+    pub segment_count: usize,
+    pub start_time: String,
+    pub end_time: String,
+    /// 일부 세그먼트가 빠진 채 완료된 결과 파일
+    pub output_path: Option<String>,
+    /// 영상 없이 오디오 트랙만 받은 다운로드
+    pub audio_only: bool,
+}
+
+impl ResumeManifest {
+    fn path(temp_dir: &Path) -> PathBuf {
+        temp_dir.join("manifest.json")
+    }
+
+    /// 받아 둔 세그먼트가 이번에 받을 화질(또는 오디오만 받기)과 같은 것인지
+    ///
+    /// 화질을 기록하지 않은 예전 매니페스트는 비교할 수 없으므로 같다고 본다.
+    pub fn matches_quality(&self, quality_id: Option<&str>, audio_only: bool) -> bool {
+        self.audio_only == audio_only
+            && (self.quality_id.is_none() || self.quality_id.as_deref() == quality_id)
+    }
+
+    pub async fn load(temp_dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(temp_dir)).await.ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub async fn save(&self, temp_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(temp_dir)
+            .await
+            .map_err(|e| format!("임시 폴더 생성 실패: {}", e))?;
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("JSON 직렬화 실패: {}", e))?;
+        fs::write(Self::path(temp_dir), json)
+            .await
+            .map_err(|e| format!("매니페스트 쓰기 실패: {}", e))
+    }
+}
+
+/// VOD 다운로드 임시 폴더: `temp_<video_id>_<해시>`
+///
+/// 해시는 구간과 요청한 화질 조건(`quality_key`: 지정한 화질 ID, 최대 해상도 등)으로
+/// 정해진다. 같은 VOD라도 구간/화질이 다르면 폴더가 달라 동시에 받아도 세그먼트가
+/// 섞이지 않고, 같은 요청을 다시 하면 같은 폴더가 되어 이어받을 수 있다.
+/// VOD 전체 구간은 (`""`, `"00:00:00"` 등) 표기와 상관없이 같은 폴더가 된다.
+pub fn vod_temp_dir(
+    output_dir: &str,
+    video_id: &str,
+    start_time: &str,
+    end_time: &str,
+    quality_key: &str,
+) -> PathBuf {
+    let (start_time, end_time) = if is_full_range(start_time, end_time) {
+        ("", "")
+    } else {
+        (start_time.trim(), end_time.trim())
+    };
+    let hash = stable_hash(&[start_time, end_time, quality_key]);
+    Path::new(output_dir).join(format!("temp_{}_{:08x}", video_id, hash as u32))
+}
+
+/// 실행/플랫폼이 바뀌어도 같은 값이 나오는 문자열 해시 (FNV-1a, 항목 사이는 0으로 구분)
+fn stable_hash(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// `output_dir`에 있는 이 VOD의 다운로드 임시 폴더 목록
+/// (vod_temp_dir 형식과 이전 형식 `temp_<video_id>`, 이름 순)
+pub async fn find_vod_temp_dirs(output_dir: &str, video_id: &str) -> Vec<PathBuf> {
+    let legacy = format!("temp_{}", video_id);
+    let prefix = format!("temp_{}_", video_id);
+    let Ok(mut entries) = fs::read_dir(output_dir).await else {
+        return Vec::new();
+    };
+    let mut dirs = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let matches = name == legacy
+            || name
+                .strip_prefix(&prefix)
+                .is_some_and(|hash| hash.len() == 8 && hash.chars().all(|c| c.is_ascii_hexdigit()));
+        if matches && entry.path().is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+    dirs.sort();
+    dirs
+}
+
+pub async fn parse_dash_segments(
+    video_id: &str,
+    in_key: &str,
+    start_time: &str,
+    end_time: &str,
+    quality_id: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let (segment_urls, report) =
+        parse_dash_segments_with_report(video_id, in_key, start_time, end_time, quality_id).await?;
+    if segment_urls.is_empty() {
+        eprintln!("⚠️ No DASH segments in range: {:?}", report);
+    }
+    Ok(segment_urls)
+}
+
+/// DASH 세그먼트 계산 진단 정보 (세그먼트가 비었을 때 원인 확인용)
+#[derive(Debug, Clone, Serialize)]
+pub struct DashSegmentReport {
+    pub representation_id: String,
+    pub timescale: f64,
+    /// segmentTimeline 전체 길이 (초)
+    pub timeline_secs: f64,
+    /// segmentTimeline 전체 세그먼트 수 (반복 포함)
+    pub timeline_segments: u64,
+    /// 요청한 구간 (초). 끝이 없으면 None
+    pub range_start_secs: f64,
+    pub range_end_secs: Option<f64>,
+    /// 구간에 해당하는 세그먼트 수
+    pub matched_segments: usize,
+    /// 첫/마지막 해당 세그먼트의 시작 시각 (초)
+    pub first_segment_secs: Option<f64>,
+    pub last_segment_secs: Option<f64>,
+}
+
+/// 선택한 representation의 segmentTimeline
+struct DashTimeline {
+    representation_id: String,
+    base_url: String,
+    /// baseURL 전체 (CDN 복제본, 첫 항목이 base_url)
+    base_urls: Vec<String>,
+    media_template: String,
+    timescale: f64,
+    /// (세그먼트 길이(timescale 단위), 반복 포함 개수)
+    entries: Vec<(u64, u64)>,
+    /// fMP4 초기화 세그먼트 URL (TS 스트림이면 없음)
+    initialization_url: Option<String>,
+}
+
+/// DASH playback을 받아 화질에 맞는 representation의 타임라인을 꺼냄
+async fn fetch_dash_timeline(
+    video_id: &str,
+    in_key: &str,
+    quality_id: Option<&str>,
+) -> Result<DashTimeline, String> {
+    let client = build_client();
+
+    // DASH playback API 호출
+    let playback_resp = fetch_playback(&client, video_id, in_key).await?;
+    dash_timeline_from_playback(&playback_resp, quality_id)
+}
+
+/// playback 응답에서 화질에 맞는 representation의 타임라인 꺼내기 (네트워크 요청 없음)
+fn dash_timeline_from_playback(
+    playback_resp: &serde_json::Value,
+    quality_id: Option<&str>,
+) -> Result<DashTimeline, String> {
+    // HLS adaptationSet 찾기 (video/mp2t)
+    playback_resp
+        .get("period")
+        .and_then(|p| p.as_array())
+        .and_then(|arr| arr.first())
+        .ok_or("period를 찾을 수 없습니다")?;
+
+    let hls_set = find_adaptation_set(playback_resp, |s| has_mime_type(s, "video/mp2t"))
+        .ok_or("HLS adaptationSet을 찾을 수 없습니다")?;
+
+    // representation 선택 (화질 ID 지정 또는 최고 화질)
+    let representations = hls_set
+        .get("representation")
+        .and_then(|r| r.as_array())
+        .ok_or("representation을 찾을 수 없습니다")?;
+
+    let selected_rep = if let Some(qid) = quality_id {
+        // 지정된 화질 ID로 찾기
+        representations
+            .iter()
+            .find(|r| r.get("id").and_then(|v| v.as_str()) == Some(qid))
+            .ok_or(format!("화질 ID '{}'를 찾을 수 없습니다", qid))?
+    } else {
+        // 최고 bandwidth의 representation 선택 (동률이면 해상도, ID 순으로 고정)
+        representations
+            .iter()
+            .min_by(|a, b| compare_variants(&dash_rep_variant(a), &dash_rep_variant(b)))
+            .ok_or("최고 품질 representation을 찾을 수 없습니다")?
+    };
+
+    dash_rep_timeline(selected_rep)
+}
+
+/// representation 하나의 segmentTemplate에서 타임라인 꺼내기
+fn dash_rep_timeline(rep: &serde_json::Value) -> Result<DashTimeline, String> {
+    let rep_id = rep
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or("representation ID를 찾을 수 없습니다")?;
+
+    let base_urls: Vec<String> = rep
+        .get("baseURL")
+        .and_then(|b| b.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|u| u.get("value").and_then(|v| v.as_str()))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let base_url = base_urls
+        .first()
+        .cloned()
+        .ok_or("baseURL을 찾을 수 없습니다")?;
+
+    let seg_template = rep
+        .get("segmentTemplate")
+        .ok_or("segmentTemplate을 찾을 수 없습니다")?;
+
+    let media_template = seg_template
+        .get("media")
+        .and_then(|v| v.as_str())
+        .ok_or("media template을 찾을 수 없습니다")?;
+
+    let timescale = seg_template
+        .get("timescale")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1000) as f64;
+
+    let timeline = seg_template
+        .get("segmentTimeline")
+        .and_then(|t| t.get("s"))
+        .and_then(|s| s.as_array())
+        .ok_or("segmentTimeline을 찾을 수 없습니다")?;
+
+    let entries = timeline
+        .iter()
+        .map(|seg| {
+            let d = seg.get("d").and_then(|v| v.as_u64()).unwrap_or(0);
+            let repeat = seg.get("r").and_then(|v| v.as_i64()).unwrap_or(0);
+            (d, repeat.max(0) as u64 + 1)
+        })
+        .collect();
+
+    let initialization_url = seg_template
+        .get("initialization")
+        .and_then(|v| v.as_str())
+        .map(|init| format!("{}{}", base_url, init.replace("$RepresentationID$", rep_id)));
+
+    Ok(DashTimeline {
+        representation_id: rep_id.to_string(),
+        base_url,
+        base_urls,
+        media_template: media_template.to_string(),
+        timescale,
+        entries,
+        initialization_url,
+    })
+}
+
+/// VOD에서 실제로 받을 수 있는 구간 (초)
+#[derive(Debug, Clone, Serialize)]
+pub struct VodBounds {
+    /// API가 알려주는 영상 길이
+    pub duration_secs: f64,
+    /// 타임라인 첫 세그먼트의 시작 시각 (DASH만)
+    pub first_segment_secs: Option<f64>,
+    /// 타임라인 마지막 세그먼트의 시작 시각 (DASH만)
+    pub last_segment_secs: Option<f64>,
+    /// 타임라인 끝 시각. 다시보기 VOD는 duration과 다를 수 있음 (DASH만)
+    pub timeline_end_secs: Option<f64>,
+}
+
+/// DASH 타임라인에서 시작/끝 경계만 계산 (세그먼트 URL은 만들지 않음)
+pub async fn dash_timeline_bounds(
+    video_id: &str,
+    in_key: &str,
+    quality_id: Option<&str>,
+) -> Result<(Option<f64>, Option<f64>, f64), String> {
+    let timeline = fetch_dash_timeline(video_id, in_key, quality_id).await?;
+    Ok(timeline_bounds(&timeline))
+}
+
+/// 타임라인의 (첫 세그먼트 시작, 마지막 세그먼트 시작, 끝) 시각 (초, 네트워크 요청 없음)
+fn timeline_bounds(timeline: &DashTimeline) -> (Option<f64>, Option<f64>, f64) {
+    let mut end = 0u64;
+    let mut last_start = None;
+    for &(d, count) in &timeline.entries {
+        if count > 0 {
+            last_start = Some(end + d * (count - 1));
+        }
+        end += d * count;
+    }
+    let first_start = (!timeline.entries.is_empty()).then_some(0.0);
+    (
+        first_start,
+        last_start.map(|t| t as f64 / timeline.timescale),
+        end as f64 / timeline.timescale,
+    )
+}
+
+/// parse_dash_segments와 같지만 계산 과정의 진단 정보도 함께 반환
+pub async fn parse_dash_segments_with_report(
+    video_id: &str,
+    in_key: &str,
+    start_time: &str,
+    end_time: &str,
+    quality_id: Option<&str>,
+) -> Result<(Vec<String>, DashSegmentReport), String> {
+    let client = build_client();
+    let playback_resp = fetch_playback(&client, video_id, in_key).await?;
+    let (mut segment_urls, report) =
+        compute_dash_segments(&playback_resp, start_time, end_time, quality_id)?;
+    if let Ok(timeline) = dash_timeline_from_playback(&playback_resp, quality_id) {
+        use_fastest_cdn(&client, &timeline.base_urls, &mut segment_urls).await;
+    }
+    Ok((segment_urls, report))
+}
+
+/// 여러 CDN 복제본 중 가장 빨리 응답하는 곳에서 받을지 (기본 꺼짐)
+static PREFER_FASTEST_CDN: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+pub fn set_prefer_fastest_cdn(enabled: bool) {
+    PREFER_FASTEST_CDN.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// CDN 복제본 HEAD 요청 응답 대기 시간
+const CDN_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// baseURL이 여러 개면 첫 세그먼트에 HEAD 요청을 동시에 보내 가장 먼저 성공한 복제본으로
+/// `urls`의 호스트를 바꿈. 모두 실패하거나 동시에 응답하면 첫 baseURL을 유지한다.
+async fn use_fastest_cdn(client: &Client, base_urls: &[String], urls: &mut [String]) {
+    if !PREFER_FASTEST_CDN.load(std::sync::atomic::Ordering::Relaxed) || base_urls.len() < 2 {
+        return;
+    }
+    let primary = &base_urls[0];
+    let Some(probe_path) = urls.first().and_then(|u| u.strip_prefix(primary.as_str())) else {
+        return;
+    };
+
+    let probes = base_urls.iter().map(|base| {
+        let url = format!("{}{}", base, probe_path);
+        Box::pin(async move {
+            let _permit = request_permit().await;
+            let resp = tokio::time::timeout(CDN_PROBE_TIMEOUT, client.head(&url).send())
+                .await
+                .map_err(|_| format!("{} 응답 시간 초과", base))?
+                .map_err(|e| format!("{}: {}", base, e))?;
+            if resp.status().is_success() {
+                Ok(base)
+            } else {
+                Err(format!("{}: HTTP {}", base, resp.status()))
+            }
+        })
+    });
+    let fastest = match futures::future::select_ok(probes).await {
+        Ok((base, _)) => base,
+        Err(e) => {
+            eprintln!("⚠️ CDN probe failed on all replicas, using first: {}", e);
+            return;
+        }
+    };
+
+    eprintln!(
+        "🌐 Fastest CDN replica: {} ({} candidates)",
+        fastest,
+        base_urls.len()
+    );
+    if fastest == primary {
+        return;
+    }
+    for url in urls.iter_mut() {
+        if let Some(rest) = url.strip_prefix(primary.as_str()) {
+            *url = format!("{}{}", fastest, rest);
+        }
+    }
+}
+
+/// playback 응답에서 구간의 세그먼트 URL과 진단 정보 계산 (네트워크 요청 없음)
+pub fn compute_dash_segments(
+    playback_resp: &serde_json::Value,
+    start_time: &str,
+    end_time: &str,
+    quality_id: Option<&str>,
+) -> Result<(Vec<String>, DashSegmentReport), String> {
+    let timeline = dash_timeline_from_playback(playback_resp, quality_id)?;
+    Ok(timeline_segment_urls(&timeline, start_time, end_time))
+}
+
+/// 타임라인에서 구간에 해당하는 세그먼트 URL 목록과 진단 정보 계산
+fn timeline_segment_urls(
+    timeline: &DashTimeline,
+    start_time: &str,
+    end_time: &str,
+) -> (Vec<String>, DashSegmentReport) {
+    let (segments, report) = timeline_segments(timeline, start_time, end_time);
+    (segments.into_iter().map(|(url, _)| url).collect(), report)
+}
+
+/// 타임라인에서 구간에 해당하는 (세그먼트 URL, 길이(초)) 목록과 진단 정보 계산
+fn timeline_segments(
+    timeline: &DashTimeline,
+    start_time: &str,
+    end_time: &str,
+) -> (Vec<(String, f64)>, DashSegmentReport) {
+    let rep_id = timeline.representation_id.as_str();
+    let timescale = timeline.timescale;
+
+    // 시작/종료 시간을 초 단위로 변환
+    let s_limit = time_to_sec(start_time);
+    let e_limit = if end_time.is_empty() {
+        f64::MAX
+    } else {
+        time_to_sec(end_time)
+    };
+
+    let mut report = DashSegmentReport {
+        representation_id: rep_id.to_string(),
+        timescale,
+        timeline_secs: 0.0,
+        timeline_segments: 0,
+        range_start_secs: s_limit,
+        range_end_secs: (!end_time.is_empty()).then_some(e_limit),
+        matched_segments: 0,
+        first_segment_secs: None,
+        last_segment_secs: None,
+    };
+    for &(d, count) in &timeline.entries {
+        report.timeline_secs += (d * count) as f64 / timescale;
+        report.timeline_segments += count;
+    }
+
+    let mut segment_urls = Vec::new();
+    let mut seg_number = 1u32;
+    let mut curr_time = 0.0;
+
+    for &(d, count) in &timeline.entries {
+        let duration = d as f64 / timescale;
+
+        for _ in 0..count {
+            if curr_time + duration >= s_limit && curr_time <= e_limit {
+                let url = timeline
+                    .media_template
+                    .replace("$RepresentationID$", rep_id)
+                    .replace("$Number%06d$", &format!("{:06}", seg_number))
+                    .replace("$Number$", &seg_number.to_string());
+
+                segment_urls.push((format!("{}{}", timeline.base_url, url), duration));
+                report.first_segment_secs.get_or_insert(curr_time);
+                report.last_segment_secs = Some(curr_time);
+            }
+
+            curr_time += duration;
+            seg_number += 1;
+
+            if curr_time > e_limit {
+                break;
+            }
+        }
+
+        if curr_time > e_limit {
+            break;
+        }
+    }
+
+    report.matched_segments = segment_urls.len();
+    (segment_urls, report)
+}
+
+pub const AUDIO_UNAVAILABLE_ERROR: &str =
+    "이 VOD에는 별도의 오디오 트랙이 없어 오디오만 받을 수 없습니다";
+
+/// DASH 오디오 adaptationSet에서 가장 낮은 bandwidth의 representation 구간 세그먼트 URL
+///
+/// 영상 세그먼트는 전혀 받지 않는다. fMP4라면 초기화 세그먼트를 맨 앞에 넣어
+/// 이어 붙인 파일을 그대로 ffmpeg에 넘길 수 있게 한다.
+pub async fn parse_dash_audio_segments(
+    video_id: &str,
+    in_key: &str,
+    start_time: &str,
+    end_time: &str,
+) -> Result<Vec<String>, String> {
+    let client = build_client();
+    let playback_resp = fetch_playback(&client, video_id, in_key).await?;
+    let mut segment_urls = compute_dash_audio_segments(&playback_resp, start_time, end_time)?;
+    if let Some(base_urls) = dash_audio_rep(&playback_resp)
+        .ok()
+        .and_then(|rep| dash_rep_timeline(rep).ok())
+        .map(|timeline| timeline.base_urls)
+    {
+        use_fastest_cdn(&client, &base_urls, &mut segment_urls).await;
+    }
+    Ok(segment_urls)
+}
+
+/// 오디오만 받을 때의 bitrate (DASH 오디오 representation 기준, 모르면 0)
+pub async fn audio_bandwidth(info: &VideoInfo) -> Result<u64, String> {
+    let (Some(video_id), Some(in_key)) = (&info.dash_video_id, &info.dash_in_key) else {
+        return Ok(0);
+    };
+    let playback_resp = fetch_playback(&build_client(), video_id, in_key).await?;
+    Ok(dash_audio_rep(&playback_resp)?
+        .get("bandwidth")
+        .and_then(|b| b.as_u64())
+        .unwrap_or(0))
+}
+
+/// 오디오 adaptationSet에서 가장 낮은 bandwidth의 representation
+fn dash_audio_rep(playback_resp: &serde_json::Value) -> Result<&serde_json::Value, String> {
+    let audio_set = find_adaptation_set(playback_resp, |s| {
+        s.get("mimeType")
+            .and_then(|m| m.as_str())
+            .is_some_and(|m| m.starts_with("audio/"))
+            || s.get("contentType").and_then(|c| c.as_str()) == Some("audio")
+    })
+    .ok_or(AUDIO_UNAVAILABLE_ERROR)?;
+
+    audio_set
+        .get("representation")
+        .and_then(|r| r.as_array())
+        .and_then(|reps| {
+            reps.iter()
+                .min_by_key(|r| r.get("bandwidth").and_then(|b| b.as_u64()).unwrap_or(0))
+        })
+        .ok_or_else(|| "오디오 representation을 찾을 수 없습니다".to_string())
+}
+
+/// playback 응답에서 오디오만 받기용 구간 세그먼트 URL 계산 (네트워크 요청 없음)
+pub fn compute_dash_audio_segments(
+    playback_resp: &serde_json::Value,
+    start_time: &str,
+    end_time: &str,
+) -> Result<Vec<String>, String> {
+    let timeline = dash_rep_timeline(dash_audio_rep(playback_resp)?)?;
+    let (mut segment_urls, report) = timeline_segment_urls(&timeline, start_time, end_time);
+    if segment_urls.is_empty() {
+        eprintln!("⚠️ No DASH audio segments in range: {:?}", report);
+        return Ok(segment_urls);
+    }
+    eprintln!(
+        "🎧 Audio-only: representation {} ({} segments)",
+        report.representation_id, report.matched_segments
+    );
+    if let Some(init_url) = timeline.initialization_url {
+        segment_urls.insert(0, init_url);
+    }
+    Ok(segment_urls)
+}
+
+/// 오디오만 받기용 구간 세그먼트 URL (DASH 전용)
+pub async fn resolve_audio_segments(
+    info: &VideoInfo,
+    start_time: &str,
+    end_time: &str,
+) -> Result<Vec<String>, String> {
+    let (Some(dash_video_id), Some(dash_in_key)) = (&info.dash_video_id, &info.dash_in_key) else {
+        return Err(AUDIO_UNAVAILABLE_ERROR.into());
+    };
+    parse_dash_audio_segments(dash_video_id, dash_in_key, start_time, end_time).await
+}
+
+/// 오디오만 받기에서 세그먼트 URL을 다시 받아오는 SegmentResolver 생성
+pub fn audio_segment_resolver(
+    info: &VideoInfo,
+    start_time: &str,
+    end_time: &str,
+) -> SegmentResolver {
+    let info = info.clone();
+    let start_time = start_time.to_string();
+    let end_time = end_time.to_string();
+
+    Box::new(move || {
+        let info = info.clone();
+        let start_time = start_time.clone();
+        let end_time = end_time.clone();
+        Box::pin(async move { resolve_audio_segments(&info, &start_time, &end_time).await })
+    })
+}
+
+pub async fn parse_segments(
+    master_url: &str,
+    start_time: &str,
+    end_time: &str,
+    quality_id: Option<&str>,
+    cookies: Option<&(String, String)>,
+) -> Result<Vec<String>, String> {
+    let (quality_url, playlist_text) = fetch_media_playlist(master_url, quality_id).await?;
+
+    // 암호화된 세그먼트는 키를 받아 URL별로 등록
+    let segments = parse_media_playlist(&playlist_text, &quality_url, start_time, end_time)?;
+    let mut fetched_keys = std::collections::HashMap::<String, [u8; 16]>::new();
+    let mut segment_urls = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if let Some((key_url, iv)) = segment.key {
+            let key = match fetched_keys.get(&key_url) {
+                Some(key) => *key,
+                None => {
+                    let key = fetch_segment_key(&key_url, cookies).await?;
+                    fetched_keys.insert(key_url, key);
+                    key
+                }
+            };
+            register_segment_key(&segment.url, SegmentKey { key, iv });
+        }
+        segment_urls.push(segment.url);
+    }
+
+    Ok(segment_urls)
+}
+
+/// master playlist에서 화질에 맞는 variant playlist를 받아 (URL, 본문) 반환
+async fn fetch_media_playlist(
+    master_url: &str,
+    quality_id: Option<&str>,
+) -> Result<(String, String), String> {
+    let client = build_client();
+
+    // master → variant playlist 두 요청을 한 허가로 순서대로 보냄
+    let _permit = request_permit().await;
+    let master_text = client
+        .get(master_url)
+        .send()
+        .await
+        .map_err(|e| format!("Master playlist 요청 실패: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Master playlist 읽기 실패: {}", e))?;
+
+    let quality_url = select_variant_url(master_url, &master_text, quality_id)?;
+
+    let playlist_text = client
+        .get(&quality_url)
+        .send()
+        .await
+        .map_err(|e| format!("Quality playlist 요청 실패: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Quality playlist 읽기 실패: {}", e))?;
+
+    Ok((quality_url, playlist_text))
+}
+
+/// 미디어 플레이리스트의 `#EXTINF` 길이 합계 (초)
+pub fn playlist_duration(playlist_text: &str) -> f64 {
+    let extinf_re = Regex::new(r"([\d.]+)").unwrap();
+    playlist_text
+        .lines()
+        .filter(|l| l.starts_with("#EXTINF"))
+        .filter_map(|l| extinf_re.find(l))
+        .filter_map(|m| m.as_str().parse::<f64>().ok())
+        .sum()
+}
+
+/// master playlist에서 받을 variant playlist URL 고르기 (네트워크 요청 없음)
+///
+/// quality_id가 있으면 해당 variant, 없으면 최고 화질(마지막 variant)을 고른다.
+pub fn select_variant_url(
+    master_url: &str,
+    master_text: &str,
+    quality_id: Option<&str>,
+) -> Result<String, String> {
+    let quality_path = if let Some(qid) = quality_id {
+        // 지정된 variant playlist URL 사용
+        qid
+    } else {
+        // 최고 화질 (마지막 variant) 선택
+        master_text
+            .lines()
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .last()
+            .ok_or("Quality playlist를 찾을 수 없습니다")?
+    };
+
+    if quality_path.starts_with("http://") || quality_path.starts_with("https://") {
+        Ok(quality_path.to_string())
+    } else {
+        Ok(resolve_url(master_url, quality_path))
+    }
+}
+
+/// 미디어 플레이리스트의 세그먼트 하나
+pub struct PlaylistSegment {
+    pub url: String,
+    /// `#EXTINF` 길이 (초, 초기화 세그먼트는 0)
+    pub duration: f64,
+    /// AES-128로 암호화되어 있으면 (키 URL, IV)
+    pub key: Option<(String, [u8; 16])>,
+}
+
+/// 미디어 플레이리스트 본문에서 구간에 해당하는 세그먼트 목록 계산 (네트워크 요청 없음)
+///
+/// 상대 경로는 `playlist_url` 기준으로 풀고, `#EXT-X-MAP` 초기화 세그먼트가 있으면 맨 앞에 둔다.
+pub fn parse_media_playlist(
+    playlist_text: &str,
+    playlist_url: &str,
+    start_time: &str,
+    end_time: &str,
+) -> Result<Vec<PlaylistSegment>, String> {
+    let mut segments = Vec::new();
+
+    let map_re = Regex::new(r#"#EXT-X-MAP:URI="([^"]+)""#).unwrap();
+    if let Some(cap) = map_re.captures(playlist_text) {
+        segments.push(PlaylistSegment {
+            url: resolve_url(playlist_url, &cap[1]),
+            duration: 0.0,
+            key: None,
+        });
+    }
+
+    let lines: Vec<&str> = playlist_text.lines().collect();
+    let extinf_re = Regex::new(r"([\d.]+)").unwrap();
+    let total_duration = playlist_duration(playlist_text);
+
+    let s_limit = time_to_sec(start_time);
+    let e_limit = if end_time.is_empty() {
+        total_duration
+    } else {
+        time_to_sec(end_time)
+    };
+
+    let mut curr_time: f64 = 0.0;
+    // AES-128 암호화: 현재 적용 중인 키 URL과 명시적 IV, 세그먼트 시퀀스 번호
+    let mut sequence = lines
+        .iter()
+        .find_map(|l| l.strip_prefix("#EXT-X-MEDIA-SEQUENCE:"))
+        .and_then(|n| n.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    let mut current_key: Option<(String, Option<[u8; 16]>)> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("#EXT-X-KEY:") {
+            let tag = parse_key_tag(line)?;
+            current_key = tag.uri.map(|uri| (resolve_url(playlist_url, &uri), tag.iv));
+        } else if line.starts_with("#EXTINF") {
+            if let Some(m) = extinf_re.find(line) {
+                if let Ok(dur) = m.as_str().parse::<f64>() {
+                    if curr_time + dur >= s_limit && curr_time <= e_limit {
+                        if i + 1 < lines.len() {
+                            let seg_line = lines[i + 1].trim();
+                            if !seg_line.starts_with('#') {
+                                // IV가 없으면 세그먼트 시퀀스 번호(128비트 빅엔디언)를 사용
+                                let key = current_key.as_ref().map(|(key_url, iv)| {
+                                    let iv = iv.unwrap_or_else(|| (sequence as u128).to_be_bytes());
+                                    (key_url.clone(), iv)
+                                });
+                                segments.push(PlaylistSegment {
+                                    url: resolve_url(playlist_url, seg_line),
+                                    duration: dur,
+                                    key,
+                                });
+                            }
+                        }
+                    }
+                    sequence += 1;
+                    curr_time += dur;
+                    if curr_time > e_limit {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+/// `#EXT-X-KEY` 태그의 속성
+struct KeyTag {
+    /// 키 URI (METHOD=NONE이면 None)
+    uri: Option<String>,
+    iv: Option<[u8; 16]>,
+}
+
+/// `#EXT-X-KEY:METHOD=AES-128,URI="...",IV=0x...` 파싱 (AES-128과 NONE만 지원)
+fn parse_key_tag(line: &str) -> Result<KeyTag, String> {
+    let attr_re = Regex::new(r#"([A-Z0-9-]+)=("[^"]*"|[^,]*)"#).unwrap();
+    let mut method = "";
+    let mut uri = None;
+    let mut iv = None;
+    for cap in attr_re.captures_iter(line) {
+        let value = cap.get(2).map_or("", |m| m.as_str().trim_matches('"'));
+        match &cap[1] {
+            "METHOD" => method = value,
+            "URI" => uri = Some(value.to_string()),
+            "IV" => {
+                let hex_iv = value.trim_start_matches("0x").trim_start_matches("0X");
+                let bytes = hex::decode(hex_iv).map_err(|_| format!("잘못된 IV: {}", value))?;
+                iv = Some(
+                    <[u8; 16]>::try_from(bytes.as_slice())
+                        .map_err(|_| format!("IV 길이가 16바이트가 아닙니다: {}", value))?,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    match method {
+        "NONE" => Ok(KeyTag {
+            uri: None,
+            iv: None,
+        }),
+        "AES-128" => {
+            let uri = uri.ok_or("암호화 키 URI가 없습니다")?;
+            Ok(KeyTag { uri: Some(uri), iv })
+        }
+        other => Err(format!(
+            "UNSUPPORTED_FORMAT: 지원하지 않는 HLS 암호화 방식입니다 ({})",
+            other
+        )),
+    }
+}
+
+/// AES-128 키(16바이트) 받기. 로그인이 필요한 키일 수 있어 쿠키를 함께 보낸다
+async fn fetch_segment_key(
+    url: &str,
+    cookies: Option<&(String, String)>,
+) -> Result<[u8; 16], String> {
+    let (nid_aut, nid_ses) = cookies.cloned().unzip();
+    let _permit = request_permit().await;
+    let resp = build_client_with_cookies(nid_aut, nid_ses)
+        .get(url)
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .map_err(|e| format!("암호화 키 요청 실패: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("암호화 키 요청 실패: HTTP {}", resp.status()));
+    }
+    let bytes = resp
+        .bytes()
+        .await
+        .map_err(|e| format!("암호화 키 읽기 실패: {}", e))?;
+    <[u8; 16]>::try_from(bytes.as_ref())
+        .map_err(|_| format!("암호화 키 길이가 잘못되었습니다 ({}바이트)", bytes.len()))
+}
+
+/// HLS AES-128 세그먼트 복호화 정보
+#[derive(Clone, Copy)]
+struct SegmentKey {
+    key: [u8; 16],
+    iv: [u8; 16],
+}
+
+impl SegmentKey {
+    /// AES-128-CBC(PKCS#7 패딩) 복호화
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+
+        let mut buf = data.to_vec();
+        let len = cbc::Decryptor::<aes::Aes128>::new(&self.key.into(), &self.iv.into())
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .map_err(|_| "복호화 실패 (키 또는 IV가 맞지 않습니다)".to_string())?
+            .len();
+        buf.truncate(len);
+        Ok(buf)
+    }
+}
+
+/// parse_segments가 찾은 암호화 세그먼트 (URL → 키). 다운로드할 때 이 키로 복호화한다
+type SegmentKeys = std::sync::Mutex<std::collections::HashMap<String, SegmentKey>>;
+static SEGMENT_KEYS: std::sync::OnceLock<SegmentKeys> = std::sync::OnceLock::new();
+
+fn register_segment_key(url: &str, key: SegmentKey) {
+    if let Ok(mut keys) = SEGMENT_KEYS.get_or_init(Default::default).lock() {
+        keys.insert(url.to_string(), key);
+    }
+}
+
+fn segment_key(url: &str) -> Option<SegmentKey> {
+    SEGMENT_KEYS.get()?.lock().ok()?.get(url).copied()
+}
+
+/// AES-128로 암호화되어 받을 때 복호화가 필요한 세그먼트인지
+pub fn is_encrypted_segment(url: &str) -> bool {
+    segment_key(url).is_some()
+}
+
+/// 세그먼트 다운로드 진행률 카운터
+///
+/// 세그먼트마다 완료 여부를 기록해 재시도나 재개로 같은 세그먼트가 여러 번
+/// 완료 처리되어도 한 번만 집계하고, 보고 값이 total을 넘지 않게 한다.
+pub struct SegmentProgress {
+    completed: Vec<std::sync::atomic::AtomicBool>,
+    done: std::sync::atomic::AtomicU32,
+    total: u32,
+    /// 마지막으로 세그먼트가 완료된 시각 (멈춤 감지용)
+    last_progress: std::sync::Mutex<std::time::Instant>,
+}
+
+impl SegmentProgress {
+    pub fn new(total: usize) -> Self {
+        Self {
+            completed: (0..total)
+                .map(|_| std::sync::atomic::AtomicBool::new(false))
+                .collect(),
+            done: std::sync::atomic::AtomicU32::new(0),
+            total: total as u32,
+            last_progress: std::sync::Mutex::new(std::time::Instant::now()),
+        }
+    }
+
+    /// 멈춤 감지 기준 시각을 지금으로 갱신
+    pub fn touch(&self) {
+        if let Ok(mut last) = self.last_progress.lock() {
+            *last = std::time::Instant::now();
+        }
+    }
+
+    /// 마지막 세그먼트 완료 이후 지난 시간
+    pub fn idle_for(&self) -> std::time::Duration {
+        self.last_progress
+            .lock()
+            .map(|last| last.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// 세그먼트 `idx` 완료 처리 후 현재 완료 수 반환
+    pub fn complete(&self, idx: usize) -> u32 {
+        use std::sync::atomic::Ordering;
+
+        let first_time = self
+            .completed
+            .get(idx)
+            .map(|flag| !flag.swap(true, Ordering::SeqCst))
+            .unwrap_or(false);
+
+        self.touch();
+        let done = if first_time {
+            self.done.fetch_add(1, Ordering::SeqCst) + 1
+        } else {
+            self.done.load(Ordering::SeqCst)
+        };
+        done.min(self.total)
+    }
+
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+}
+
+/// 세그먼트를 받는 순서
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadOrder {
+    /// 완료 순서와 상관없이 최대한 동시에 받기 (가장 빠름)
+    #[default]
+    Unordered,
+    /// 시간 순서대로 받으며 앞부분부터 combined.raw에 바로 이어 붙임
+    /// (다운로드 중에도 재생 가능, 대신 최대 속도는 낮아짐)
+    Sequential,
+    /// 뒤쪽 세그먼트부터 받기 (긴 다운로드에서 토큰 만료 위험이 큰 끝부분을 먼저 확보)
+    Reverse,
+}
+
+impl DownloadOrder {
+    /// 인덱스 순서로 만든 작업 목록을 시작할 순서로 정렬 (작업은 목록 순서대로 시작된다)
+    fn schedule<T>(self, jobs: &mut [T]) {
+        if self == DownloadOrder::Reverse {
+            jobs.reverse();
+        }
+    }
+
+    /// 동시에 요청하는 세그먼트 수 (순서대로 받기는 앞쪽 몇 개만)
+    fn concurrency(self) -> usize {
+        match self {
+            DownloadOrder::Sequential => ORDERED_WINDOW,
+            DownloadOrder::Unordered | DownloadOrder::Reverse => SEGMENT_CONCURRENCY,
+        }
+    }
+
+    /// 순서 이름(`"reverse"` 등)이나 예전 옵션 `ordered`의 true/false 읽기
+    /// (`#[serde(alias = "ordered", deserialize_with = ...)]`용)
+    pub fn deserialize_with_flag<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OrderOrFlag {
+            Order(DownloadOrder),
+            Flag(bool),
+        }
+
+        Ok(match OrderOrFlag::deserialize(deserializer)? {
+            OrderOrFlag::Order(order) => order,
+            OrderOrFlag::Flag(true) => DownloadOrder::Sequential,
+            OrderOrFlag::Flag(false) => DownloadOrder::Unordered,
+        })
+    }
+}
+
+/// 세그먼트 다운로드 동작 설정
+#[derive(Debug, Clone)]
+pub struct SegmentDownloadConfig {
+    /// 실패한 세그먼트를 건너뛰고 계속 진행 (false면 첫 실패에서 중단)
+    pub continue_on_error: bool,
+    /// 세그먼트 하나의 요청 제한 시간
+    pub segment_timeout: std::time::Duration,
+    /// 이 시간 동안 완료된 세그먼트가 하나도 없으면 멈춘 것으로 보고 중단
+    pub stall_timeout: std::time::Duration,
+    /// true가 되면 새 세그먼트를 받지 않고 중단
+    pub cancel: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+    /// 있으면 세그먼트 완료 시 진행 상태를 기록
+    pub status: Option<std::sync::Arc<LiveStatus>>,
+    /// 세그먼트를 받는 순서
+    pub order: DownloadOrder,
+    /// aria2c가 설치되어 있으면 세그먼트를 aria2c로 받기 (없으면 내장 다운로더)
+    pub use_aria2c: bool,
+}
+
+impl SegmentDownloadConfig {
+    /// 순서대로 받으며 병합 파일에 바로 이어 붙이는지
+    fn is_sequential(&self) -> bool {
+        self.order == DownloadOrder::Sequential
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|c| c.load(std::sync::atomic::Ordering::SeqCst))
+    }
+}
+
+impl Default for SegmentDownloadConfig {
+    fn default() -> Self {
+        Self {
+            continue_on_error: false,
+            segment_timeout: std::time::Duration::from_secs(30),
+            stall_timeout: std::time::Duration::from_secs(60),
+            cancel: None,
+            status: None,
+            order: DownloadOrder::Unordered,
+            use_aria2c: false,
+        }
+    }
+}
+
+/// 한 호스트에서 이 개수 이상 실패하면 CDN 장애로 보고 URL을 다시 받아온다
+const CDN_FAILOVER_MIN_FAILURES: usize = 3;
+
+/// gzip 스트림의 시작 바이트 (TS/fMP4 세그먼트는 이렇게 시작하지 않음)
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// 무압축으로 요청했는데도 CDN이 gzip/deflate로 보낸 세그먼트를 원래 바이트로 복원
+///
+/// `Content-Encoding` 헤더가 없어도 본문이 gzip으로 시작하면 압축을 푼다.
+fn decode_segment_body<'a>(
+    encoding: &str,
+    body: &'a [u8],
+) -> Result<std::borrow::Cow<'a, [u8]>, String> {
+    use std::io::Read;
+
+    let encoding = encoding.trim().to_ascii_lowercase();
+    let mut decoded = Vec::new();
+    if encoding == "gzip" || encoding == "x-gzip" || body.starts_with(&GZIP_MAGIC) {
+        flate2::read::MultiGzDecoder::new(body)
+            .read_to_end(&mut decoded)
+            .map_err(|e| e.to_string())?;
+    } else if encoding == "deflate" {
+        // HTTP deflate는 zlib 형식이 표준이지만 raw deflate를 보내는 서버도 있음
+        if flate2::read::ZlibDecoder::new(body)
+            .read_to_end(&mut decoded)
+            .is_err()
+        {
+            decoded.clear();
+            flate2::read::DeflateDecoder::new(body)
+                .read_to_end(&mut decoded)
+                .map_err(|e| e.to_string())?;
+        }
+    } else {
+        return Ok(std::borrow::Cow::Borrowed(body));
+    }
+
+    eprintln!(
+        "🗜️ Decompressed segment ({} → {} bytes)",
+        body.len(),
+        decoded.len()
+    );
+    Ok(std::borrow::Cow::Owned(decoded))
+}
+
+/// 순서대로 받기에서 앞서 요청해 두는 세그먼트 수
+const ORDERED_WINDOW: usize = 6;
+
+/// 한 다운로드에서 동시에 요청하는 세그먼트 수
+const SEGMENT_CONCURRENCY: usize = 20;
+
+/// 세그먼트를 이어 붙인 병합 파일 이름 (트랙 폴더마다 하나)
+const COMBINED_FILE_NAME: &str = "combined.raw";
+
+/// 순서대로 받기에서 트랙 폴더별로 병합 파일에 이미 이어 붙인 세그먼트 수
+type ProgressiveMerge = std::sync::Mutex<std::collections::HashMap<PathBuf, usize>>;
+
+/// 다운로드할 세그먼트 트랙 하나 (DASH 영상/오디오 등)
+pub struct SegmentTrack<'a> {
+    /// 로그용 트랙 이름 (예: "video", "audio")
+    pub name: &'a str,
+    pub urls: &'a [String],
+    /// 이 트랙의 세그먼트를 저장할 폴더
+    pub dir: PathBuf,
+    /// `urls[0]`의 세그먼트 파일 번호 (VOD 중간의 일부 세그먼트만 받을 때 0이 아님)
+    pub first_index: usize,
+    /// 있으면 CDN 호스트 장애 시 이 트랙의 세그먼트 URL을 다시 받아온다
+    pub resolver: Option<&'a SegmentResolver>,
+}
+
+/// 세그먼트 하나의 다운로드 작업
+struct SegmentJob {
+    /// 전체 트랙을 합친 진행률 인덱스
+    idx: usize,
+    target_path: PathBuf,
+    url: String,
+}
+
+/// 세그먼트 `idx`가 저장되는 파일 이름
+///
+/// 다운로드와 병합 사이의 순서 계약: 세그먼트는 완료 순서와 상관없이 파싱된 URL 목록의
+/// 인덱스로 저장되고, `merge_segments`는 0부터 인덱스 순서대로 이어 붙인다. 같은 URL이
+/// 여러 번 나와도 인덱스가 다르므로 각각 따로 저장된다.
+///
+/// 번호는 [`SEGMENT_INDEX_WIDTH`]자리로 채워 아주 긴 VOD(2초 세그먼트로 수백 시간)도
+/// 이름 순서와 인덱스 순서가 같게 한다. 읽을 때는 [`segment_index`]로 되돌린다.
+fn segment_file_name(idx: usize) -> String {
+    format!("seg_{:0width$}.m4s", idx, width = SEGMENT_INDEX_WIDTH)
+}
+
+/// 세그먼트 파일 번호 자릿수 (예전 버전은 5자리)
+const SEGMENT_INDEX_WIDTH: usize = 7;
+
+/// 세그먼트 파일 이름에서 인덱스 읽기 (자릿수와 무관)
+fn segment_index(file_name: &str) -> Option<usize> {
+    file_name
+        .strip_prefix("seg_")
+        .and_then(|n| n.strip_suffix(".m4s"))
+        .filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|n| n.parse().ok())
+}
+
+/// 세그먼트를 병렬로 다운로드
+///
+/// 세그먼트 `i`는 `segment_urls[i]`에서 받아 `segment_file_name(i)`로 저장된다.
+/// `config.continue_on_error`가 true이면 실패한 세그먼트를 건너뛰고 계속 진행하며,
+/// 실패한 세그먼트 인덱스 목록을 반환한다. false이면 첫 실패에서 중단한다.
+/// `resolver`가 있으면 한 CDN 호스트에서 실패가 몰릴 때 세그먼트 URL을 다시
+/// 받아와 (다른 호스트로) 실패한 세그먼트를 한 번 더 시도한다.
+pub async fn download_segments(
+    app: &AppHandle,
+    segment_urls: &[String],
+    temp_dir: &Path,
+    config: &SegmentDownloadConfig,
+    resolver: Option<&SegmentResolver>,
+) -> Result<Vec<usize>, String> {
+    let track = SegmentTrack {
+        name: "video",
+        urls: segment_urls,
+        dir: temp_dir.to_path_buf(),
+        first_index: 0,
+        resolver,
+    };
+    let mut failed = download_tracks(app, &[track], config).await?;
+    Ok(failed.pop().unwrap_or_default())
+}
+
+/// 전체 세그먼트 목록 중 `first..=last` 번호의 세그먼트만 받기 (문제 세그먼트 진단/재다운로드용)
+///
+/// 파일 이름은 전체 목록 기준 번호(`segment_file_name(first)` …)로 저장되므로
+/// 같은 임시 폴더의 다운로드와 그대로 섞어 병합할 수 있다. 실패한 세그먼트 번호를 반환한다.
+/// 세그먼트 번호 범위 `first..=last`가 전체 `total`개 안에 있는지 확인
+pub fn check_segment_range(first: usize, last: usize, total: usize) -> Result<(), String> {
+    if first > last || last >= total {
+        return Err(format!(
+            "세그먼트 번호 범위가 올바르지 않습니다 ({}~{}, 전체 {}개)",
+            first, last, total
+        ));
+    }
+    Ok(())
+}
+
+pub async fn download_segment_range(
+    app: &AppHandle,
+    segment_urls: &[String],
+    first: usize,
+    last: usize,
+    temp_dir: &Path,
+    config: &SegmentDownloadConfig,
+) -> Result<Vec<usize>, String> {
+    check_segment_range(first, last, segment_urls.len())?;
+    let track = SegmentTrack {
+        name: "video",
+        urls: &segment_urls[first..=last],
+        dir: temp_dir.to_path_buf(),
+        first_index: first,
+        resolver: None,
+    };
+    let mut failed = download_tracks(app, &[track], config).await?;
+    Ok(failed
+        .pop()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|i| i + first)
+        .collect())
+}
+
+/// 여러 트랙(영상 + 오디오 등)의 세그먼트를 함께 병렬로 다운로드
+///
+/// 진행률은 모든 트랙의 세그먼트 수를 합쳐 하나로 보고하며, 각 트랙은 자신의
+/// `dir`에 저장된다. 트랙 순서대로 실패한 세그먼트 인덱스(트랙 내 인덱스) 목록을 반환한다.
+pub async fn download_tracks(
+    app: &AppHandle,
+    tracks: &[SegmentTrack<'_>],
+    config: &SegmentDownloadConfig,
+) -> Result<Vec<Vec<usize>>, String> {
+    for track in tracks {
+        fs::create_dir_all(&track.dir)
+            .await
+            .map_err(|e| format!("임시 폴더 생성 실패: {}", e))?;
+        // 이전 버전에서 받아 둔 세그먼트 이름을 현재 형식으로 맞춤
+        scan_segment_indices(&track.dir).await?;
+    }
+
+    let client = build_client();
+    let total: usize = tracks.iter().map(|t| t.urls.len()).sum();
+    let progress = std::sync::Arc::new(SegmentProgress::new(total));
+
+    // 트랙별 진행률 인덱스 시작점
+    let offsets: Vec<usize> = tracks
+        .iter()
+        .scan(0, |offset, t| {
+            let start = *offset;
+            *offset += t.urls.len();
+            Some(start)
+        })
+        .collect();
+
+    // 순서대로 받기: 병합 파일을 비우고 앞에서부터 이어 붙여 나감
+    let merge = if config.is_sequential() {
+        for track in tracks {
+            fs::File::create(track.dir.join(COMBINED_FILE_NAME))
+                .await
+                .map_err(|e| format!("병합 파일 생성 실패: {}", e))?;
+        }
+        Some(ProgressiveMerge::default())
+    } else {
+        None
+    };
+
+    let mut jobs: Vec<SegmentJob> = tracks
+        .iter()
+        .zip(&offsets)
+        .flat_map(|(track, offset)| track_jobs(track, *offset, track.urls, 0..track.urls.len()))
+        .collect();
+    config.order.schedule(&mut jobs);
+    // aria2c 백엔드: 설정에서 켜고 설치되어 있을 때만
+    // (순서대로 받기, 복호화가 필요한 암호화 세그먼트와는 함께 쓰지 않음)
+    let encrypted = jobs.iter().any(|job| segment_key(&job.url).is_some());
+    let aria2c = if config.use_aria2c && !config.is_sequential() && !encrypted {
+        let found = find_aria2c().await;
+        if found.is_none() {
+            eprintln!("⚠️ aria2c not found - using built-in downloader");
+        }
+        found
+    } else {
+        None
+    };
+    let mut results = match &aria2c {
+        Some(aria2c) => download_batch_aria2c(app, aria2c, jobs, &progress, config).await?,
+        None => {
+            download_segment_batch(app, &client, jobs, &progress, config, merge.as_ref()).await?
+        }
+    };
+    debug_assert!(
+        {
+            let mut seen: Vec<usize> = results.iter().map(|(idx, _)| *idx).collect();
+            seen.sort_unstable();
+            seen.into_iter().eq(0..total)
+        },
+        "every segment index must be downloaded exactly once"
+    );
+
+    // CDN 호스트 장애 감지 → URL 재해석 후 실패분 재시도 (트랙별)
+    for (track, &offset) in tracks.iter().zip(&offsets) {
+        let Some(resolver) = track.resolver else {
+            continue;
+        };
+        let range = offset..offset + track.urls.len();
+        let failed_local: Vec<usize> = results
+            .iter()
+            .filter(|(idx, r)| r.is_err() && range.contains(idx))
+            .map(|(idx, _)| idx - offset)
+            .collect();
+        let failed_urls: Vec<&String> = failed_local.iter().map(|i| &track.urls[*i]).collect();
+
+        if let Some(host) = failing_host(&failed_urls) {
+            eprintln!(
+                "🔁 Segment host {} is failing for {} track - re-resolving segment URLs",
+                host, track.name
+            );
+            log_download(&format!(
+                "Segment host {} is failing for {} track",
+                host, track.name
+            ));
+            match resolver().await {
+                Ok(new_urls) if new_urls.len() == track.urls.len() => {
+                    let retry: Vec<SegmentJob> =
+                        track_jobs(track, offset, &new_urls, failed_local).collect();
+                    let retried = download_segment_batch(
+                        app,
+                        &client,
+                        retry,
+                        &progress,
+                        config,
+                        merge.as_ref(),
+                    )
+                    .await?;
+                    results.retain(|(idx, r)| r.is_ok() || !range.contains(idx));
+                    results.extend(retried);
+                }
+                Ok(new_urls) => eprintln!(
+                    "⚠️ Re-resolved segment count differs ({} vs {}) - skipping failover",
+                    new_urls.len(),
+                    track.urls.len()
+                ),
+                Err(e) => {
+                    eprintln!("⚠️ Segment URL re-resolve failed: {}", e);
+                    log_download(&format!("Segment URL re-resolve failed: {}", e));
+                }
+            }
+        }
+    }
+
+    let mut failed = vec![Vec::new(); tracks.len()];
+    for (idx, r) in results {
+        if let Err(e) = r {
+            log_download(&format!("Segment {} failed: {}", idx, e));
+            if !config.continue_on_error {
+                return Err(e);
+            }
+            eprintln!("⚠️ {}", e);
+            // idx가 속한 트랙 = 시작점이 idx 이하인 마지막 트랙
+            let track_no = offsets.partition_point(|&o| o <= idx) - 1;
+            failed[track_no].push(idx - offsets[track_no]);
+        }
+    }
+    for track_failed in &mut failed {
+        track_failed.sort_unstable();
+    }
+
+    Ok(failed)
+}
+
+/// 트랙의 세그먼트 `indices`에 대한 다운로드 작업 목록
+fn track_jobs<'a>(
+    track: &'a SegmentTrack<'_>,
+    offset: usize,
+    urls: &'a [String],
+    indices: impl IntoIterator<Item = usize> + 'a,
+) -> impl Iterator<Item = SegmentJob> + 'a {
+    indices.into_iter().map(move |i| SegmentJob {
+        idx: offset + i,
+        target_path: track.dir.join(segment_file_name(track.first_index + i)),
+        url: urls[i].clone(),
+    })
+}
+
+/// 실패한 URL 중 CDN_FAILOVER_MIN_FAILURES 이상 실패가 몰린 호스트
+fn failing_host(failed_urls: &[&String]) -> Option<String> {
+    let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for url in failed_urls {
+        if let Some(host) = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_string()))
+        {
+            *counts.entry(host).or_default() += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count >= CDN_FAILOVER_MIN_FAILURES)
+        .max_by_key(|(_, count)| *count)
+        .map(|(host, _)| host)
+}
+
+/// 세그먼트가 완료될 때마다 그 트랙 폴더에서 아직 붙이지 않은 세그먼트를
+/// 순서대로 병합 파일 끝에 이어 붙임 (다음 차례 세그먼트가 아직 없으면 멈춤)
+///
+/// 병합 파일은 항상 처음부터 빈틈없이 이어진 재생 가능한 앞부분만 담는다.
+async fn append_progressive(merge: &ProgressiveMerge, target_path: &Path) -> Result<(), String> {
+    let Some(dir) = target_path.parent() else {
+        return Ok(());
+    };
+    let Some(mut appended) = merge.lock().ok().map(|m| m.get(dir).copied().unwrap_or(0)) else {
+        return Ok(());
+    };
+
+    let mut outfile = fs::OpenOptions::new()
+        .append(true)
+        .open(dir.join(COMBINED_FILE_NAME))
+        .await
+        .map_err(|e| format!("병합 파일 열기 실패: {}", e))?;
+    loop {
+        let seg_path = dir.join(segment_file_name(appended));
+        if !seg_path.exists() {
+            break;
+        }
+        let mut seg_file = fs::File::open(&seg_path)
+            .await
+            .map_err(|e| format!("세그먼트 읽기 실패: {}", e))?;
+        tokio::io::copy(&mut seg_file, &mut outfile)
+            .await
+            .map_err(|e| format!("병합 쓰기 실패: {}", e))?;
+        appended += 1;
+        if let Ok(mut m) = merge.lock() {
+            m.insert(dir.to_path_buf(), appended);
+        }
+    }
+    outfile
+        .flush()
+        .await
+        .map_err(|e| format!("병합 쓰기 실패: {}", e))
+}
+
+/// 세그먼트 하나 완료 처리 후 진행 상태 기록/이벤트 전송
+fn report_segment_done(
+    app: &AppHandle,
+    progress: &SegmentProgress,
+    status: Option<&LiveStatus>,
+    idx: usize,
+    bytes: u64,
+) {
+    let done = progress.complete(idx);
+    let total = progress.total();
+    if let Some(status) = status {
+        status.record_segment(done, total, bytes);
+    }
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "downloading".into(),
+            current: done,
+            total,
+            message: format!("세그먼트 다운로드 중... ({}/{})", done, total),
+        },
+    );
+}
+
+fn stall_error(config: &SegmentDownloadConfig) -> String {
+    format!(
+        "다운로드가 멈춘 것 같습니다 ({}초 동안 진행 없음). 네트워크 상태를 확인해주세요.",
+        config.stall_timeout.as_secs()
+    )
+}
+
+/// PATH에서 실행 가능한 aria2c 찾기
+async fn find_aria2c() -> Option<PathBuf> {
+    let path = PathBuf::from("aria2c");
+    let probe = path.clone();
+    let runs = tokio::task::spawn_blocking(move || {
+        std::process::Command::new(probe)
+            .arg("--version")
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    })
+    .await
+    .unwrap_or(false);
+
+    runs.then_some(path)
+}
+
+/// aria2c 동시 다운로드 수 상한 (실제 값은 받은 세그먼트 허가 수)
+const ARIA2C_CONCURRENCY: usize = 16;
+
+/// aria2c로 세그먼트 묶음 다운로드 (결과 형식은 `download_segment_batch`와 같음)
+///
+/// URL과 저장 위치를 입력 파일로 넘기고, aria2c가 출력하는 `Download complete: <경로>`
+/// 줄을 읽어 진행률을 보고한다. 끝까지 받지 못한 세그먼트(`.aria2` 제어 파일이 남은
+/// 것)는 지우고 실패로 돌려준다.
+async fn download_batch_aria2c(
+    app: &AppHandle,
+    aria2c: &Path,
+    jobs: Vec<SegmentJob>,
+    progress: &SegmentProgress,
+    config: &SegmentDownloadConfig,
+) -> Result<Vec<(usize, Result<(), String>)>, String> {
+    use tokio::io::AsyncBufReadExt;
+
+    let status = config.status.as_deref();
+    let normalize = |p: &Path| p.to_string_lossy().replace('\\', "/");
+    progress.touch();
+
+    // 이미 받은 세그먼트는 건너뛰고 나머지를 입력 파일로
+    let mut pending: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut input = String::new();
+    for job in &jobs {
+        if job.target_path.exists() {
+            report_segment_done(app, progress, status, job.idx, 0);
+            continue;
+        }
+        let (Some(dir), Some(name)) = (job.target_path.parent(), job.target_path.file_name())
+        else {
+            continue;
+        };
+        input.push_str(&format!(
+            "{}\n  dir={}\n  out={}\n",
+            job.url,
+            dir.display(),
+            name.to_string_lossy()
+        ));
+        pending.insert(normalize(&job.target_path), job.idx);
+    }
+
+    if let Some(first_dir) = jobs
+        .first()
+        .and_then(|job| job.target_path.parent())
+        .filter(|_| !pending.is_empty())
+    {
+        let input_path = first_dir.join("aria2c_input.txt");
+        fs::write(&input_path, input)
+            .await
+            .map_err(|e| format!("aria2c 입력 파일 쓰기 실패: {}", e))?;
+
+        // 다른 다운로드와 같은 동시 세그먼트 한도를 나눠 쓰도록, 허가를 최소 하나 받고
+        // 지금 남아 있는 만큼 더 받아 그 수만큼만 동시에 받는다 (aria2c가 끝날 때까지 유지)
+        let limits = app.state::<DownloadLimits>();
+        let mut permits = limits
+            .segments
+            .acquire()
+            .await
+            .map_err(|e| format!("세그먼트 다운로드 대기 실패: {}", e))?;
+        while permits.num_permits() < ARIA2C_CONCURRENCY.min(pending.len()) {
+            let Ok(permit) = limits.segments.try_acquire() else {
+                break;
+            };
+            permits.merge(permit);
+        }
+        let concurrency = permits.num_permits();
+        let cancel_state = app.state::<CancelState>();
+        let _active = ActiveSegmentGuard::many(&cancel_state.active_segments, concurrency);
+
+        let mut command = tokio::process::Command::new(aria2c);
+        command
+            .arg(format!("--input-file={}", input_path.display()))
+            .arg(format!("--max-concurrent-downloads={}", concurrency))
+            .arg(format!(
+                "--timeout={}",
+                config.segment_timeout.as_secs().max(1)
+            ))
+            .arg(format!("--user-agent={}", USER_AGENT))
+            .args([
+                "--header=Referer: https://chzzk.naver.com/",
+                "--split=1",
+                "--max-tries=3",
+                "--retry-wait=1",
+                "--auto-file-renaming=false",
+                "--allow-overwrite=true",
+                "--console-log-level=notice",
+                "--summary-interval=0",
+                "--download-result=hide",
+            ]);
+        if NETWORK_OPTIONS
+            .read()
+            .is_ok_and(|n| matches!(n.ip_mode, IpMode::Ipv4))
+        {
+            command.arg("--disable-ipv6=true");
+        }
+        let mut child = command
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("aria2c 실행 실패: {}", e))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or("aria2c 출력을 읽을 수 없습니다")?;
+        let mut lines = tokio::io::BufReader::new(stdout).lines();
+        let mut tick = tokio::time::interval(std::time::Duration::from_secs(1));
+
+        // 오류로 빠져나가면 child가 drop되며 aria2c도 종료됨 (kill_on_drop)
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Some(line) = line.map_err(|e| format!("aria2c 출력 읽기 실패: {}", e))?
+                    else {
+                        break;
+                    };
+                    let Some((_, path)) = line.split_once("Download complete: ") else {
+                        continue;
+                    };
+                    let path = normalize(Path::new(path.trim()));
+                    if let Some(&idx) = pending.get(&path) {
+                        let bytes = fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+                        report_segment_done(app, progress, status, idx, bytes);
+                    }
+                }
+                _ = tick.tick() => {
+                    if cancel_state.is_shutting_down() {
+                        return Err(SHUTDOWN_ERROR.to_string());
+                    }
+                    if config.is_cancelled() {
+                        return Err(CANCELLED_ERROR.to_string());
+                    }
+                    if progress.idle_for() > config.stall_timeout {
+                        return Err(stall_error(config));
+                    }
+                }
+            }
+        }
+
+        let exit = child
+            .wait()
+            .await
+            .map_err(|e| format!("aria2c 실행 실패: {}", e))?;
+        let _ = fs::remove_file(&input_path).await;
+        if !exit.success() {
+            eprintln!("⚠️ aria2c exited with {:?}", exit.code());
+        }
+    }
+
+    let mut results = Vec::with_capacity(jobs.len());
+    for job in jobs {
+        let control_path = job.target_path.with_extension("m4s.aria2");
+        let result = if control_path.exists() || !job.target_path.exists() {
+            let _ = fs::remove_file(&control_path).await;
+            let _ = fs::remove_file(&job.target_path).await;
+            Err(format!("세그먼트 {} 다운로드 실패 (aria2c)", job.idx))
+        } else {
+            // 완료 줄을 놓쳤어도 집계되도록 (이미 집계된 세그먼트는 무시됨)
+            report_segment_done(app, progress, status, job.idx, 0);
+            Ok(())
+        };
+        results.push((job.idx, result));
+    }
+
+    Ok(results)
+}
+
+async fn download_segment_batch(
+    app: &AppHandle,
+    client: &Client,
+    jobs: Vec<SegmentJob>,
+    progress: &std::sync::Arc<SegmentProgress>,
+    config: &SegmentDownloadConfig,
+    merge: Option<&ProgressiveMerge>,
+) -> Result<Vec<(usize, Result<(), String>)>, String> {
+    let segment_timeout = config.segment_timeout;
+    progress.touch();
+
+    let downloads = stream::iter(jobs)
+        .map(|job| {
+            let SegmentJob {
+                idx,
+                target_path,
+                url,
+            } = job;
+            let client = client.clone();
+            let progress = progress.clone();
+            let app = app.clone();
+            let config = config.clone();
+
+            let merge_path = target_path.clone();
+            let status = config.status.clone();
+            let emit_done = move |app: &AppHandle, bytes: u64| {
+                report_segment_done(app, &progress, status.as_deref(), idx, bytes)
+            };
+
+            let result = async move {
+                if target_path.exists() {
+                    emit_done(&app, 0);
+                    return Ok(());
+                }
+
+                // 모든 다운로드에 걸친 동시 세그먼트 요청 수 제한
+                let limits = app.state::<DownloadLimits>();
+                let _permit = limits
+                    .segments
+                    .acquire()
+                    .await
+                    .map_err(|e| format!("세그먼트 다운로드 대기 실패: {}", e))?;
+
+                let cancel_state = app.state::<CancelState>();
+                if cancel_state.is_shutting_down() {
+                    return Err(SHUTDOWN_ERROR.to_string());
+                }
+                if config.is_cancelled() {
+                    return Err(CANCELLED_ERROR.to_string());
+                }
+                let _active = ActiveSegmentGuard::new(&cancel_state.active_segments);
+
+                // 압축된 응답을 그대로 저장하는 일이 없도록 무압축으로 요청
+                let _permit = request_permit().await;
+                let resp = client
+                    .get(&url)
+                    .header(reqwest::header::ACCEPT_ENCODING, "identity")
+                    .timeout(segment_timeout)
+                    .send()
+                    .await
+                    .map_err(|e| format!("세그먼트 {} 다운로드 실패: {}", idx, e))?;
+                let encoding = resp
+                    .headers()
+                    .get(reqwest::header::CONTENT_ENCODING)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("")
+                    .to_string();
+
+                let body = resp
+                    .bytes()
+                    .await
+                    .map_err(|e| format!("세그먼트 {} 읽기 실패: {}", idx, e))?;
+                record_throughput(body.len() as u64);
+                let bytes = decode_segment_body(&encoding, &body)
+                    .map_err(|e| format!("세그먼트 {} 압축 해제 실패: {}", idx, e))?;
+                if bytes.starts_with(&GZIP_MAGIC) {
+                    return Err(format!("세그먼트 {}가 압축된 상태로 받아졌습니다", idx));
+                }
+                let bytes = match segment_key(&url) {
+                    Some(key) => std::borrow::Cow::Owned(
+                        key.decrypt(&bytes)
+                            .map_err(|e| format!("세그먼트 {} {}", idx, e))?,
+                    ),
+                    None => bytes,
+                };
+
+                // 임시 파일에 다 쓴 뒤 이름을 바꿔, 중간에 끊겨도 반쯤 쓴 세그먼트가 남지 않게 한다
+                let part_path = target_path.with_extension("m4s.part");
+                let mut file = fs::File::create(&part_path)
+                    .await
+                    .map_err(|e| format!("파일 생성 실패: {}", e))?;
+
+                file.write_all(&bytes)
+                    .await
+                    .map_err(|e| format!("파일 쓰기 실패: {}", e))?;
+                file.flush()
+                    .await
+                    .map_err(|e| format!("파일 쓰기 실패: {}", e))?;
+                drop(file);
+
+                fs::rename(&part_path, &target_path)
+                    .await
+                    .map_err(|e| format!("파일 이름 변경 실패: {}", e))?;
+
+                emit_done(&app, bytes.len() as u64);
+
+                Ok(())
+            };
+            async move { (idx, merge_path, result.await) }
+        })
+        .buffer_unordered(config.order.concurrency())
+        .then(|(idx, merge_path, result)| async move {
+            if let (Some(merge), Ok(())) = (merge, &result) {
+                if let Err(e) = append_progressive(merge, &merge_path).await {
+                    eprintln!("⚠️ Progressive merge failed: {}", e);
+                }
+            }
+            (idx, result)
+        })
+        .collect::<Vec<_>>();
+
+    // 멈춤 감지: stall_timeout 동안 완료된 세그먼트가 없으면 중단
+    let stall_watch = async {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            if progress.idle_for() > config.stall_timeout {
+                break;
+            }
+        }
+    };
+
+    tokio::select! {
+        results = downloads => {
+            if app.state::<CancelState>().is_shutting_down() {
+                return Err(SHUTDOWN_ERROR.to_string());
+            }
+            if config.is_cancelled() {
+                return Err(CANCELLED_ERROR.to_string());
+            }
+            Ok(results)
+        }
+        _ = stall_watch => Err(stall_error(config)),
+    }
+}
+
+/// 폴더에 있는 seg_NNNNNNN.m4s 파일 번호 목록 (오름차순)
+pub async fn scan_segment_indices(temp_dir: &Path) -> Result<Vec<usize>, String> {
+    let mut entries = fs::read_dir(temp_dir)
+        .await
+        .map_err(|e| format!("임시 폴더 읽기 실패: {}", e))?;
+
+    let mut indices = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| format!("임시 폴더 읽기 실패: {}", e))?
+    {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if let Some(idx) = segment_index(name) {
+            // 예전 자릿수로 저장된 세그먼트는 현재 이름으로 바꿔 이어받기/병합에 쓰이게 함
+            let canonical = segment_file_name(idx);
+            if name != canonical {
+                fs::rename(entry.path(), temp_dir.join(&canonical))
+                    .await
+                    .map_err(|e| format!("세그먼트 이름 변경 실패: {}", e))?;
+            }
+            indices.push(idx);
+        }
+    }
+    indices.sort_unstable();
+
+    Ok(indices)
+}
+
+/// `temp_dir`의 세그먼트 0..segment_count를 인덱스 순서대로 이어 붙여 combined.raw 생성
+///
+/// 다운로드 완료 순서와 무관하게 원래 파싱된 순서가 유지된다 (`segment_file_name` 참고).
+/// 없는 세그먼트(건너뛴 실패분)는 빼고 이어 붙인다.
+pub async fn merge_segments(
+    app: &AppHandle,
+    segment_count: usize,
+    temp_dir: &Path,
+) -> Result<PathBuf, String> {
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "merging".into(),
+            current: 0,
+            total: 1,
+            message: "세그먼트 병합 중...".into(),
+        },
+    );
+
+    let combined_path = temp_dir.join(COMBINED_FILE_NAME);
+    // 순서대로 받기로 이미 모두 이어 붙였으면 다시 병합하지 않음
+    if is_merged(segment_count, temp_dir, &combined_path).await {
+        eprintln!("✅ Segments already merged in order");
+        return Ok(combined_path);
+    }
+
+    concat_segment_files(temp_dir, 0..segment_count, &combined_path).await?;
+
+    Ok(combined_path)
+}
+
+/// 세그먼트 병합 쓰기 버퍼 기본 크기 (MB)
+pub const DEFAULT_MERGE_BUFFER_MB: usize = 4;
+
+static MERGE_BUFFER_BYTES: std::sync::atomic::AtomicUsize =
+    std::sync::atomic::AtomicUsize::new(DEFAULT_MERGE_BUFFER_MB * 1024 * 1024);
+
+/// 이후 병합에 쓸 쓰기 버퍼 크기 (MB, 1~256)
+pub fn set_merge_buffer_mb(mb: usize) {
+    let bytes = mb.clamp(1, 256) * 1024 * 1024;
+    MERGE_BUFFER_BYTES.store(bytes, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// `temp_dir`의 세그먼트 `indices`를 주어진 순서대로 이어 붙여 `output_path`에 쓰기
+/// (없는 세그먼트는 빼고 이어 붙인다)
+pub async fn concat_segment_files(
+    temp_dir: &Path,
+    indices: impl IntoIterator<Item = usize>,
+    output_path: &Path,
+) -> Result<(), String> {
+    let outfile = fs::File::create(output_path)
+        .await
+        .map_err(|e| format!("병합 파일 생성 실패: {}", e))?;
+    // 작은 세그먼트가 많으면 세그먼트마다 쓰기 호출이 생기므로 크게 모아서 쓴다
+    let buffer_size = MERGE_BUFFER_BYTES.load(std::sync::atomic::Ordering::Relaxed);
+    let mut outfile = tokio::io::BufWriter::with_capacity(buffer_size, outfile);
+
+    for i in indices {
+        let seg_path = temp_dir.join(segment_file_name(i));
+        if seg_path.exists() {
+            // 세그먼트 전체를 메모리에 올리지 않고 파일에서 바로 복사
+            let mut seg_file = fs::File::open(&seg_path)
+                .await
+                .map_err(|e| format!("세그먼트 읽기 실패: {}", e))?;
+            tokio::io::copy(&mut seg_file, &mut outfile)
+                .await
+                .map_err(|e| format!("병합 쓰기 실패: {}", e))?;
+        }
+    }
+
+    outfile
+        .flush()
+        .await
+        .map_err(|e| format!("병합 쓰기 실패: {}", e))
+}
+
+/// 병합 파일 크기가 존재하는 세그먼트 크기의 합과 같으면 이미 병합된 것으로 봄
+async fn is_merged(segment_count: usize, temp_dir: &Path, combined_path: &Path) -> bool {
+    let Ok(combined) = fs::metadata(combined_path).await else {
+        return false;
+    };
+    let mut total = 0;
+    for i in 0..segment_count {
+        if let Ok(meta) = fs::metadata(temp_dir.join(segment_file_name(i))).await {
+            total += meta.len();
+        }
+    }
+    total > 0 && combined.len() == total
+}
+
+/// 시작/끝이 모두 비어 있으면 (또는 시작이 0이고 끝이 비어 있으면) VOD 전체 다운로드
+pub fn is_full_range(start_time: &str, end_time: &str) -> bool {
+    end_time.trim().is_empty() && time_to_sec(start_time.trim()) == 0.0
+}
+
+/// 출력 파일 경로: `채널_제목_시작_끝.mp4` (VOD 전체면 `채널_제목.mp4`)
+///
+/// `batch`는 일괄 다운로드의 (순번, 전체 개수)로, 템플릿의 `{index}` 자리에 들어간다.
+pub fn build_output_filename(
+    info: &VideoInfo,
+    start_time: &str,
+    end_time: &str,
+    output_dir: &str,
+    batch: Option<(usize, usize)>,
+) -> PathBuf {
+    let safe_channel = sanitize_filename(&info.channel);
+    let safe_title = sanitize_filename(&info.title);
+    let full_range = is_full_range(start_time, end_time);
+    let s_tag = start_time.replace(':', "");
+    let e_tag = if end_time.is_empty() {
+        "END".to_string()
+    } else {
+        end_time.replace(':', "")
+    };
+    let index = batch.map(|(position, total)| batch_index_tag(position, total));
+
+    let template_stem = render_filename_template(&[
+        ("channel", Some(&safe_channel)),
+        ("title", Some(&safe_title)),
+        ("date", date_part(info.publish_date.as_deref())),
+        ("start", (!full_range).then_some(s_tag.as_str())),
+        ("end", (!full_range).then_some(e_tag.as_str())),
+        ("index", index.as_deref()),
+    ]);
+    if let Some(stem) = template_stem {
+        return Path::new(output_dir).join(format!("{}.mp4", stem));
+    }
+
+    if full_range {
+        let filename = format!("{}_{}.mp4", safe_channel, safe_title);
+        return Path::new(output_dir).join(filename);
+    }
+
+    let filename = format!("{}_{}_{}_{}.mp4", safe_channel, safe_title, s_tag, e_tag);
+    Path::new(output_dir).join(filename)
+}
+
+pub fn build_subtitle_filename(info: &VideoInfo, track: &SubtitleTrack, output_dir: &str) -> PathBuf {
+    let safe_channel = sanitize_filename(&info.channel);
+    let safe_title = sanitize_filename(&info.title);
+    let safe_lang = sanitize_filename(&track.language);
+
+    let filename = format!("{}_{}.{}.vtt", safe_channel, safe_title, safe_lang);
+    Path::new(output_dir).join(filename)
+}
+
+/// 분할 저장할 출력 파일 하나
+#[derive(Debug, Clone)]
+pub struct OutputPart {
+    /// 병합 파일 기준 시작 위치 (초)
+    pub start_secs: f64,
+    pub duration_secs: f64,
+    pub path: PathBuf,
+}
+
+/// `total_secs` 길이를 `interval_secs` 단위로 나눈 출력 파일 목록 (`이름_part01.mp4` ...)
+pub fn split_parts(output_path: &Path, total_secs: f64, interval_secs: f64) -> Vec<OutputPart> {
+    let stem = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "video".into());
+    let count = (total_secs / interval_secs).ceil().max(1.0) as usize;
+
+    (0..count)
+        .map(|i| {
+            let start_secs = i as f64 * interval_secs;
+            OutputPart {
+                start_secs,
+                duration_secs: interval_secs.min(total_secs - start_secs).max(0.0),
+                path: output_path.with_file_name(format!("{}_part{:02}.mp4", stem, i + 1)),
+            }
+        })
+        .collect()
+}
+
+/// 병합 파일을 구간별로 잘라 여러 파일로 리먹싱 (세그먼트는 한 번만 받는다)
+pub async fn split_with_ffmpeg(
+    app: &AppHandle,
+    ffmpeg_path: &Path,
+    combined_path: &Path,
+    parts: &[OutputPart],
+) -> Result<(), String> {
+    let cancel_state = app.state::<CancelState>();
+
+    for (i, part) in parts.iter().enumerate() {
+        let _ = app.emit(
+            "download-progress",
+            DownloadProgress {
+                stage: "remuxing".into(),
+                current: i as u32,
+                total: parts.len() as u32,
+                message: format!("ffmpeg로 분할 저장 중... ({}/{})", i + 1, parts.len()),
+            },
+        );
+
+        let run = tokio::process::Command::new(ffmpeg_path)
+            .args(["-y", "-ss", &format!("{:.3}", part.start_secs), "-t"])
+            .arg(format!("{:.3}", part.duration_secs))
+            .arg("-i")
+            .arg(combined_path)
+            .args([
+                "-c",
+                "copy",
+                "-map",
+                "0",
+                "-movflags",
+                "faststart",
+                "-bsf:a",
+                "aac_adtstoasc",
+            ])
+            .arg(&part.path)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .output();
+
+        let output = tokio::select! {
+            output = run => output.map_err(|e| format!("ffmpeg 실행 실패: {}", e))?,
+            _ = cancel_state.ffmpeg_cancelled() => {
+                return Err(CANCELLED_ERROR.to_string());
+            }
+        };
+        log_ffmpeg_stderr(&output.stderr);
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "ffmpeg 오류 (파트 {}, 코드 {:?}): {}",
+                i + 1,
+                output.status.code(),
+                stderr
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// 영상/오디오 분리 저장 경로: (`이름_video.mp4`, `이름_audio.m4a`)
+pub fn separate_track_paths(output_path: &Path) -> (PathBuf, PathBuf) {
+    let stem = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "video".into());
+    (
+        output_path.with_file_name(format!("{}_video.mp4", stem)),
+        output_path.with_file_name(format!("{}_audio.m4a", stem)),
+    )
+}
+
+/// 병합 파일에서 영상만(-an), 오디오만(-vn) 각각 리먹싱 (세그먼트는 한 번만 받는다)
+pub async fn separate_tracks_with_ffmpeg(
+    app: &AppHandle,
+    ffmpeg_path: &Path,
+    combined_path: &Path,
+    video_path: &Path,
+    audio_path: &Path,
+) -> Result<(), String> {
+    let cancel_state = app.state::<CancelState>();
+    let steps = [
+        ("영상", video_path, ["-an", "-movflags", "faststart"]),
+        ("오디오", audio_path, ["-vn", "-bsf:a", "aac_adtstoasc"]),
+    ];
+
+    for (i, (label, path, args)) in steps.iter().enumerate() {
+        let _ = app.emit(
+            "download-progress",
+            DownloadProgress {
+                stage: "remuxing".into(),
+                current: i as u32,
+                total: steps.len() as u32,
+                message: format!("ffmpeg로 {} 트랙 저장 중...", label),
+            },
+        );
+
+        let run = tokio::process::Command::new(ffmpeg_path)
+            .args(["-y", "-i"])
+            .arg(combined_path)
+            .args(["-c", "copy"])
+            .args(args)
+            .arg(path)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .output();
+
+        let output = tokio::select! {
+            output = run => output.map_err(|e| format!("ffmpeg 실행 실패: {}", e))?,
+            _ = cancel_state.ffmpeg_cancelled() => {
+                return Err(CANCELLED_ERROR.to_string());
+            }
+        };
+        log_ffmpeg_stderr(&output.stderr);
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "ffmpeg 오류 ({} 트랙, 코드 {:?}): {}",
+                label,
+                output.status.code(),
+                stderr
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// 오디오만 받은 병합 파일(fMP4 오디오)을 영상 없이 m4a로 리먹싱
+pub async fn remux_audio_with_ffmpeg(
+    app: &AppHandle,
+    ffmpeg_path: &Path,
+    combined_path: &Path,
+    output_path: &Path,
+) -> Result<(), String> {
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "remuxing".into(),
+            current: 0,
+            total: 1,
+            message: "ffmpeg로 오디오 저장 중...".into(),
+        },
+    );
+
+    let cancel_state = app.state::<CancelState>();
+    let run = tokio::process::Command::new(ffmpeg_path)
+        .args(["-y", "-i"])
+        .arg(combined_path)
+        .args(["-vn", "-c:a", "copy", "-movflags", "faststart"])
+        .arg(output_path)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+
+    let output = tokio::select! {
+        output = run => output.map_err(|e| format!("ffmpeg 실행 실패: {}", e))?,
+        _ = cancel_state.ffmpeg_cancelled() => {
+            return Err(CANCELLED_ERROR.to_string());
+        }
+    };
+    log_ffmpeg_stderr(&output.stderr);
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg 오류 (코드 {:?}): {}",
+            output.status.code(),
+            stderr
+        ));
+    }
+    Ok(())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Kodi/Jellyfin용 .nfo 메타데이터를 영상 파일 옆에 저장
+///
+/// `runtime_secs`는 실제로 받은 구간 길이 (부분 다운로드면 전체 길이와 다름).
+pub async fn write_nfo(
+    info: &VideoInfo,
+    video_path: &Path,
+    runtime_secs: f64,
+) -> Result<PathBuf, String> {
+    let mut xml =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<movie>\n");
+    let mut push = |tag: &str, value: &str| {
+        xml.push_str(&format!("  <{0}>{1}</{0}>\n", tag, escape_xml(value)));
+    };
+
+    push("title", &info.title);
+    push("studio", &info.channel);
+    let plot = match &info.category {
+        Some(category) => format!("{} 방송 다시보기 ({})", info.channel, category),
+        None => format!("{} 방송 다시보기", info.channel),
+    };
+    push("plot", &plot);
+    if let Some(category) = &info.category {
+        push("genre", category);
+    }
+    let aired = info.publish_date.as_deref().and_then(|d| d.split(' ').next());
+    if let Some(date) = aired {
+        push("premiered", date);
+        push("aired", date);
+    }
+    let runtime_mins = (runtime_secs / 60.0).round() as u64;
+    push("runtime", &runtime_mins.to_string());
+    if !info.thumbnail.is_empty() {
+        push("thumb", &info.thumbnail);
+    }
+    xml.push_str(&format!(
+        "  <actor>\n    <name>{}</name>\n    <role>스트리머</role>\n  </actor>\n",
+        escape_xml(&info.channel)
+    ));
+    xml.push_str("</movie>\n");
+
+    let nfo_path = video_path.with_extension("nfo");
+    fs::write(&nfo_path, xml)
+        .await
+        .map_err(|e| format!("nfo 파일 저장 실패: {}", e))?;
+
+    Ok(nfo_path)
+}
+
+/// 자막 파일 이름의 언어 부분인지 (`ko`, `en-US` 등, 점 없음)
+fn is_subtitle_language(lang: &str) -> bool {
+    lang.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && lang
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 결과 파일과 함께 만들어지는 부가 파일 목록
+///
+/// 같은 폴더의 `<이름>.jpg`, `<이름>.nfo`, `<이름>.<언어>.vtt`, `<이름>.chat.json`만 해당한다
+/// (`<이름>.1.nfo`처럼 다른 다운로드의 파일은 포함하지 않음).
+/// 로컬 재생 목록(`.m3u8`)이면 재생 목록이 가리키는 세그먼트 파일도 포함한다.
+pub fn sidecar_files(video_path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(stem)) = (video_path.parent(), video_path.file_stem()) else {
+        return Vec::new();
+    };
+    let stem = stem.to_string_lossy().to_string();
+    let exact = [
+        format!("{}.jpg", stem),
+        format!("{}.nfo", stem),
+        format!("{}.chat.json", stem),
+    ];
+    let prefix = format!("{}.", stem);
+
+    let mut sidecars: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path != video_path && path.is_file())
+                .filter(|path| {
+                    let name = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    exact.contains(&name)
+                        || name
+                            .strip_prefix(&prefix)
+                            .and_then(|rest| rest.strip_suffix(".vtt"))
+                            .is_some_and(is_subtitle_language)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if video_path.extension().is_some_and(|e| e == "m3u8") {
+        sidecars.extend(local_playlist_segment_files(video_path));
+    }
+    sidecars.sort();
+    sidecars
+}
+
+/// 로컬 재생 목록이 가리키는 세그먼트 폴더 안의 파일 목록
+///
+/// 폴더 안의 다른 파일은 건드리지 않도록 재생 목록에 적힌 파일만 반환한다.
+pub fn local_playlist_segment_files(playlist_path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Ok(text)) = (
+        playlist_path.parent(),
+        std::fs::read_to_string(playlist_path),
+    ) else {
+        return Vec::new();
+    };
+    let segment_dir = local_playlist_segment_dir(playlist_path);
+    text.lines()
+        .map(str::trim)
+        .filter_map(|line| match line.strip_prefix("#EXT-X-MAP:") {
+            Some(attrs) => attrs
+                .split("URI=\"")
+                .nth(1)
+                .and_then(|rest| rest.split('"').next()),
+            None => (!line.is_empty() && !line.starts_with('#')).then_some(line),
+        })
+        .map(|uri| dir.join(uri))
+        .filter(|path| path.parent() == Some(segment_dir.as_path()) && path.is_file())
+        .collect()
+}
+
+/// 병합 파일을 mp4로 리먹싱
+///
+/// `chapters`가 있으면 (구간 시작 기준 시각) 챕터 메뉴로 함께 기록한다.
+pub async fn remux_with_ffmpeg(
+    app: &AppHandle,
+    ffmpeg_path: &Path,
+    combined_path: &Path,
+    output_path: &Path,
+    chapters: &[Chapter],
+    total_secs: f64,
+) -> Result<(), String> {
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "remuxing".into(),
+            current: 0,
+            total: 1,
+            message: "ffmpeg로 리먹싱 중...".into(),
+        },
+    );
+
+    // HDR 원본은 mp4 faststart 기록 시 색 정보가 빠지지 않도록 명시적으로 전달
+    let mut color_args: Vec<String> = Vec::new();
+    if let Some(color) = probe_color_metadata(ffmpeg_path, combined_path).await {
+        if color.is_hdr() {
+            eprintln!(
+                "🌈 HDR source detected ({}/{}/{})",
+                color.colorspace, color.primaries, color.transfer
+            );
+            color_args = vec![
+                "-color_primaries".into(),
+                color.primaries,
+                "-color_trc".into(),
+                color.transfer,
+                "-colorspace".into(),
+                color.colorspace,
+            ];
+        }
+    }
+
+    // 챕터는 메타데이터 파일을 두 번째 입력으로 넣어 가져온다
+    let mut chapter_args: Vec<String> = Vec::new();
+    if !chapters.is_empty() {
+        let metadata_path = combined_path.with_file_name("chapters.txt");
+        fs::write(&metadata_path, ffmetadata_chapters(chapters, total_secs))
+            .await
+            .map_err(|e| format!("챕터 파일 쓰기 실패: {}", e))?;
+        chapter_args = vec![
+            "-i".into(),
+            metadata_path.to_string_lossy().to_string(),
+            "-map_chapters".into(),
+            "1".into(),
+        ];
+    }
+
+    let run = tokio::process::Command::new(ffmpeg_path)
+        .args(["-y", "-i", combined_path.to_str().unwrap()])
+        .args(&chapter_args)
+        .args([
+            "-c",
+            "copy",
+            "-map",
+            "0",
+            "-movflags",
+            "faststart",
+            "-bsf:a",
+            "aac_adtstoasc",
+        ])
+        .args(&color_args)
+        .arg(output_path)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+
+    // 전체 취소 시 ffmpeg 프로세스 종료 (kill_on_drop)
+    let cancel_state = app.state::<CancelState>();
+    let output = tokio::select! {
+        output = run => output.map_err(|e| format!("ffmpeg 실행 실패: {}", e))?,
+        _ = cancel_state.ffmpeg_cancelled() => {
+            return Err(CANCELLED_ERROR.to_string());
+        }
+    };
+    log_ffmpeg_stderr(&output.stderr);
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg 오류 (코드 {:?}): {}",
+            output.status.code(),
+            stderr
+        ));
+    }
+
+    Ok(())
+}
+
+/// 빠진 세그먼트가 있는 병합 파일을 최대한 재생 가능한 파일로 리먹싱 (손상된 패킷은 버림)
+///
+/// 오류를 무시하고 진행하므로 ffmpeg가 실패 코드로 끝나도 결과 파일이 만들어졌으면 성공으로 본다.
+pub async fn salvage_remux_with_ffmpeg(
+    app: &AppHandle,
+    ffmpeg_path: &Path,
+    combined_path: &Path,
+    output_path: &Path,
+) -> Result<(), String> {
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "remuxing".into(),
+            current: 0,
+            total: 1,
+            message: "받은 부분만 ffmpeg로 리먹싱 중...".into(),
+        },
+    );
+
+    let run = tokio::process::Command::new(ffmpeg_path)
+        .args([
+            "-y",
+            "-err_detect",
+            "ignore_err",
+            "-fflags",
+            "+discardcorrupt+genpts",
+        ])
+        .arg("-i")
+        .arg(combined_path)
+        .args([
+            "-c",
+            "copy",
+            "-map",
+            "0",
+            "-movflags",
+            "faststart",
+            "-bsf:a",
+            "aac_adtstoasc",
+        ])
+        .arg(output_path)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+
+    let cancel_state = app.state::<CancelState>();
+    let output = tokio::select! {
+        output = run => output.map_err(|e| format!("ffmpeg 실행 실패: {}", e))?,
+        _ = cancel_state.ffmpeg_cancelled() => {
+            return Err(CANCELLED_ERROR.to_string());
+        }
+    };
+    log_ffmpeg_stderr(&output.stderr);
+
+    let written = fs::metadata(output_path).await.is_ok_and(|m| m.len() > 0);
+    if !written {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg 오류 (코드 {:?}): {}",
+            output.status.code(),
+            stderr
+        ));
+    }
+    if !output.status.success() {
+        eprintln!("⚠️ ffmpeg reported errors while salvaging - output kept");
+    }
+
+    Ok(())
+}
+
+/// 여러 영상 파일을 재인코딩 없이 순서대로 이어 붙여 하나로 저장 (concat demuxer)
+pub async fn concat_with_ffmpeg(
+    app: &AppHandle,
+    ffmpeg_path: &Path,
+    inputs: &[PathBuf],
+    output_path: &Path,
+) -> Result<(), String> {
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "remuxing".into(),
+            current: 0,
+            total: 1,
+            message: "ffmpeg로 이어 붙이는 중...".into(),
+        },
+    );
+
+    // concat 목록: file '경로' (작은따옴표는 '\'' 로 이스케이프)
+    let list: String = inputs
+        .iter()
+        .map(|p| format!("file '{}'\n", p.to_string_lossy().replace('\'', "'\\''")))
+        .collect();
+    let list_path = output_path.with_extension("concat.txt");
+    fs::write(&list_path, list)
+        .await
+        .map_err(|e| format!("concat 목록 쓰기 실패: {}", e))?;
 
-    let rep_id = best_rep
-        .get("id")
-        .and_then(|v| v.as_str())
-        .ok_or("representation ID를 찾을 수 없습니다")?;
+    let run = tokio::process::Command::new(ffmpeg_path)
+        .args(["-y", "-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .args([
+            "-c",
+            "copy",
+            "-map",
+            "0",
+            "-movflags",
+            "faststart",
+            "-bsf:a",
+            "aac_adtstoasc",
+        ])
+        .arg(output_path)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .output();
 
-    let base_url = best_rep
-        .get("baseURL")
-        .and_then(|b| b.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|u| u.get("value"))
-        .and_then(|v| v.as_str())
-        .ok_or("baseURL을 찾을 수 없습니다")?;
+    let cancel_state = app.state::<CancelState>();
+    let output = tokio::select! {
+        output = run => output.map_err(|e| format!("ffmpeg 실행 실패: {}", e)),
+        _ = cancel_state.ffmpeg_cancelled() => Err(CANCELLED_ERROR.to_string()),
+    };
+    let _ = fs::remove_file(&list_path).await;
+    let output = output?;
+    log_ffmpeg_stderr(&output.stderr);
 
-    let seg_template = best_rep
-        .get("segmentTemplate")
-        .ok_or("segmentTemplate을 찾을 수 없습니다")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg 오류 (코드 {:?}): {}",
+            output.status.code(),
+            stderr
+        ));
+    }
 
-    let media_template = seg_template
-        .get("media")
-        .and_then(|v| v.as_str())
-        .ok_or("media template을 찾을 수 없습니다")?;
+    Ok(())
+}
 
-    let timescale = seg_template
-        .get("timescale")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(1000) as f64;
+/// 병합된 영상에서 `interval_secs`초마다 한 장씩 JPEG 프레임 추출
+pub async fn extract_frames_with_ffmpeg(
+    app: &AppHandle,
+    ffmpeg_path: &Path,
+    combined_path: &Path,
+    frames_dir: &Path,
+    interval_secs: u32,
+) -> Result<(), String> {
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "extracting".into(),
+            current: 0,
+            total: 1,
+            message: "ffmpeg로 프레임 추출 중...".into(),
+        },
+    );
 
-    let timeline = seg_template
-        .get("segmentTimeline")
-        .and_then(|t| t.get("s"))
-        .and_then(|s| s.as_array())
-        .ok_or("segmentTimeline을 찾을 수 없습니다")?;
+    fs::create_dir_all(frames_dir)
+        .await
+        .map_err(|e| format!("프레임 폴더 생성 실패: {}", e))?;
+
+    let output_pattern = frames_dir.join("frame_%05d.jpg");
+    let cancel_state = app.state::<CancelState>();
+    let run = tokio::process::Command::new(ffmpeg_path)
+        .args(["-y", "-i"])
+        .arg(combined_path)
+        .arg("-vf")
+        .arg(format!("fps=1/{}", interval_secs.max(1)))
+        .args(["-q:v", "3"])
+        .arg(&output_pattern)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .output();
 
-    // 시작/종료 시간을 초 단위로 변환
-    let s_limit = time_to_sec(start_time);
-    let e_limit = if end_time.is_empty() {
-        f64::MAX
-    } else {
-        time_to_sec(end_time)
+    let output = tokio::select! {
+        output = run => output.map_err(|e| format!("ffmpeg 실행 실패: {}", e))?,
+        _ = cancel_state.ffmpeg_cancelled() => {
+            return Err(CANCELLED_ERROR.to_string());
+        }
     };
 
-    let mut segment_urls = Vec::new();
-    let mut seg_number = 1u32;
-    let mut curr_time = 0.0;
-
-    for seg in timeline {
-        let duration = seg
-            .get("d")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0) as f64
-            / timescale;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "ffmpeg 오류 (코드 {:?}): {}",
+            output.status.code(),
+            stderr
+        ));
+    }
 
-        let repeat = seg.get("r").and_then(|v| v.as_i64()).unwrap_or(0);
+    Ok(())
+}
 
-        let count = if repeat >= 0 { repeat + 1 } else { 1 } as u32;
+/// 리먹싱(-c copy) 대신 재인코딩할 때의 코덱 설정
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranscodeOptions {
+    /// h264 / hevc / av1
+    pub video_codec: String,
+    /// aac / opus / mp3 / copy (없으면 aac)
+    pub audio_codec: Option<String>,
+    /// 화질 기반 인코딩 (bitrate보다 우선)
+    pub crf: Option<u32>,
+    /// 예: "4M"
+    pub bitrate: Option<String>,
+    /// 오디오 비트레이트, 예: "192k"
+    pub audio_bitrate: Option<String>,
+}
 
-        for _ in 0..count {
-            if curr_time + duration >= s_limit && curr_time <= e_limit {
-                let url = media_template
-                    .replace("$RepresentationID$", rep_id)
-                    .replace("$Number%06d$", &format!("{:06}", seg_number))
-                    .replace("$Number$", &seg_number.to_string());
+impl Default for TranscodeOptions {
+    fn default() -> Self {
+        Self {
+            video_codec: "h264".into(),
+            audio_codec: None,
+            crf: Some(23),
+            bitrate: None,
+            audio_bitrate: None,
+        }
+    }
+}
 
-                segment_urls.push(format!("{}{}", base_url, url));
-            }
+/// 출력 해상도/프레임레이트 축소 설정 (재인코딩 필요)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScaleOptions {
+    /// 출력 세로 해상도 (가로는 비율 유지)
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    /// 원본보다 큰 해상도/프레임레이트도 허용
+    #[serde(default)]
+    pub force: bool,
+}
 
-            curr_time += duration;
-            seg_number += 1;
+impl ScaleOptions {
+    /// 원본 크기와 비교해 값을 검증하고 ffmpeg 필터 인자 생성
+    fn filter_args(&self, source: Option<(u32, f64)>) -> Result<Vec<String>, String> {
+        let mut args = Vec::new();
 
-            if curr_time > e_limit {
-                break;
+        if let Some(height) = self.height {
+            if !(144..=4320).contains(&height) {
+                return Err(format!("출력 해상도가 올바르지 않습니다: {}p", height));
+            }
+            if let Some((src_height, _)) = source {
+                if height > src_height && !self.force {
+                    return Err(format!(
+                        "원본({}p)보다 높은 해상도({}p)로는 변환할 수 없습니다",
+                        src_height, height
+                    ));
+                }
             }
+            // 가로는 비율 유지 + 짝수로 맞춤
+            args.extend(["-vf".to_string(), format!("scale=-2:{}", height)]);
         }
 
-        if curr_time > e_limit {
-            break;
+        if let Some(fps) = self.fps {
+            if !(1.0..=240.0).contains(&fps) {
+                return Err(format!("프레임레이트가 올바르지 않습니다: {}", fps));
+            }
+            if let Some((_, src_fps)) = source {
+                if src_fps > 0.0 && fps > src_fps && !self.force {
+                    return Err(format!(
+                        "원본({}fps)보다 높은 프레임레이트({}fps)로는 변환할 수 없습니다",
+                        src_fps, fps
+                    ));
+                }
+            }
+            args.extend(["-r".to_string(), fps.to_string()]);
         }
-    }
 
-    Ok(segment_urls)
+        Ok(args)
+    }
 }
 
-pub async fn parse_segments(
-    master_url: &str,
-    start_time: &str,
-    end_time: &str,
-    quality_id: Option<&str>,
-) -> Result<Vec<String>, String> {
-    let client = build_client();
+/// 첫 세그먼트(fMP4면 초기화 세그먼트 포함)만 받아 ffmpeg로 재생 가능한 영상인지 확인
+///
+/// 전체 다운로드 전에 토큰/CDN 문제나 잘못된 화질 선택을 빠르게 찾기 위한 용도.
+/// 성공하면 ffmpeg가 읽은 영상 스트림 설명을 반환한다.
+pub async fn probe_first_segment(
+    info: &VideoInfo,
+    segments: &[String],
+    ffmpeg_path: &Path,
+    temp_dir: &Path,
+) -> Result<String, String> {
+    let first = segments.first().ok_or("다운로드할 세그먼트가 없습니다")?;
+    // HLS fMP4는 첫 URL이 EXT-X-MAP 초기화 세그먼트이므로 다음 세그먼트까지 받는다
+    let has_init = !info.is_dash && !is_ts_source(info, std::slice::from_ref(first));
+    let count = if has_init { 2 } else { 1 }.min(segments.len());
 
-    let master_text = client
-        .get(master_url)
-        .send()
-        .await
-        .map_err(|e| format!("Master playlist 요청 실패: {}", e))?
-        .text()
+    fs::create_dir_all(temp_dir)
         .await
-        .map_err(|e| format!("Master playlist 읽기 실패: {}", e))?;
+        .map_err(|e| format!("임시 폴더 생성 실패: {}", e))?;
+    let sample_path = temp_dir.join("sample.raw");
+
+    let result = async {
+        let client = build_client();
+        let mut sample = Vec::new();
+        for (idx, url) in segments[..count].iter().enumerate() {
+            let _permit = request_permit().await;
+            let resp = client
+                .get(url)
+                .timeout(std::time::Duration::from_secs(30))
+                .send()
+                .await
+                .map_err(|e| format!("세그먼트 {} 다운로드 실패: {}", idx, e))?;
+            if !resp.status().is_success() {
+                return Err(format!(
+                    "세그먼트 {} 다운로드 실패: HTTP {}",
+                    idx,
+                    resp.status()
+                ));
+            }
+            let bytes = resp
+                .bytes()
+                .await
+                .map_err(|e| format!("세그먼트 {} 읽기 실패: {}", idx, e))?;
+            match segment_key(url) {
+                Some(key) => sample.extend(key.decrypt(&bytes)?),
+                None => sample.extend_from_slice(&bytes),
+            }
+        }
 
-    // 화질 선택: quality_id가 있으면 해당 variant 사용, 없으면 최고 화질
-    let quality_path = if let Some(qid) = quality_id {
-        // 지정된 variant playlist URL 사용
-        qid
-    } else {
-        // 최고 화질 (마지막 variant) 선택
-        master_text
-            .lines()
-            .filter(|l| !l.is_empty() && !l.starts_with('#'))
-            .last()
-            .ok_or("Quality playlist를 찾을 수 없습니다")?
-    };
+        fs::write(&sample_path, &sample)
+            .await
+            .map_err(|e| format!("파일 쓰기 실패: {}", e))?;
 
-    let quality_url = if quality_path.starts_with("http://") || quality_path.starts_with("https://") {
-        quality_path.to_string()
-    } else {
-        resolve_url(master_url, quality_path)
-    };
+        probe_video_line(ffmpeg_path, &sample_path)
+            .await
+            .map(|line| line.trim().to_string())
+            .ok_or_else(|| "첫 세그먼트에서 영상 스트림을 찾을 수 없습니다".to_string())
+    }
+    .await;
 
-    let playlist_text = client
-        .get(&quality_url)
-        .send()
-        .await
-        .map_err(|e| format!("Quality playlist 요청 실패: {}", e))?
-        .text()
+    let _ = fs::remove_dir_all(temp_dir).await;
+    result
+}
+
+/// `ffmpeg -i` 출력에서 영상 스트림 설명 줄 가져오기
+///
+/// ffprobe는 함께 설치되지 않으므로 ffmpeg의 입력 정보 출력을 사용한다.
+async fn probe_video_line(ffmpeg_path: &Path, input: &Path) -> Option<String> {
+    let output = tokio::process::Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-i"])
+        .arg(input)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .output()
         .await
-        .map_err(|e| format!("Quality playlist 읽기 실패: {}", e))?;
+        .ok()?;
 
-    let mut segment_urls: Vec<String> = Vec::new();
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .find(|l| l.contains("Stream #") && l.contains("Video:"))
+        .map(|l| l.to_string())
+}
 
-    let map_re = Regex::new(r#"#EXT-X-MAP:URI="([^"]+)""#).unwrap();
-    if let Some(cap) = map_re.captures(&playlist_text) {
-        segment_urls.push(resolve_url(&quality_url, &cap[1]));
-    }
+/// 영상 스트림의 세로 해상도
+pub async fn probe_video_height(ffmpeg_path: &Path, input: &Path) -> Option<u32> {
+    probe_video_stream(ffmpeg_path, input)
+        .await
+        .map(|(height, _)| height)
+}
 
-    let lines: Vec<&str> = playlist_text.lines().collect();
-    let extinf_re = Regex::new(r"([\d.]+)").unwrap();
+/// ffmpeg가 보고한 파일 전체 길이 (초, `Duration: HH:MM:SS.xx`)
+pub async fn probe_duration(ffmpeg_path: &Path, input: &Path) -> Option<f64> {
+    let output = tokio::process::Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-i"])
+        .arg(input)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .await
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("Duration:"))
+        .and_then(|rest| rest.split(',').next())
+        .map(|d| d.trim())
+        .filter(|d| d.contains(':'))
+        .map(time_to_sec)
+}
 
-    let total_duration: f64 = lines
-        .iter()
-        .filter(|l| l.starts_with("#EXTINF"))
-        .filter_map(|l| extinf_re.find(l))
-        .filter_map(|m| m.as_str().parse::<f64>().ok())
-        .sum();
+/// verify_media_file 결과
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub path: String,
+    pub passed: bool,
+    pub duration_secs: Option<f64>,
+    pub has_video: bool,
+    pub has_audio: bool,
+    /// 실패 사유 (통과면 빈 목록)
+    pub problems: Vec<String>,
+}
 
-    let s_limit = time_to_sec(start_time);
-    let e_limit = if end_time.is_empty() {
-        total_duration
+/// 끝부분 검사 시작 위치 (`-sseof`, 파일 끝에서 5초 전). 잘린 파일은 보통 끝부분을 디코딩할 수 없다
+const VERIFY_TAIL_SEEK: &str = "-5";
+
+/// 파일 이름으로 판단한 있어야 할 스트림 (영상, 오디오)
+///
+/// 오디오만 받은 `.m4a`(분리 저장의 `_audio.m4a` 포함)는 오디오만,
+/// 분리 저장의 `_video.mp4`는 영상만, 그 밖에는 둘 다 있어야 한다.
+fn expected_streams(path: &Path) -> (bool, bool) {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if ext == "m4a" {
+        (false, true)
+    } else if ext == "mp4" && stem.ends_with("_video") {
+        (true, false)
     } else {
-        time_to_sec(end_time)
+        (true, true)
+    }
+}
+
+/// 이미 받은 결과 파일이 온전한지 다시 확인 (다시 받지 않음)
+///
+/// 파일이 열리는지, 있어야 할 영상/오디오 스트림이 있는지([`expected_streams`]),
+/// 끝부분이 디코딩되는지, `expected_secs`가 있으면 길이가 허용 오차 안에 드는지 확인한다.
+pub async fn verify_media_file(
+    ffmpeg_path: &Path,
+    path: &Path,
+    expected_secs: Option<f64>,
+) -> VerifyReport {
+    let mut report = VerifyReport {
+        path: path.to_string_lossy().to_string(),
+        passed: false,
+        duration_secs: None,
+        has_video: false,
+        has_audio: false,
+        problems: Vec::new(),
     };
 
-    let mut curr_time: f64 = 0.0;
+    match fs::metadata(path).await {
+        Ok(meta) if meta.len() > 0 => {}
+        Ok(_) => {
+            report.problems.push("빈 파일입니다".into());
+            return report;
+        }
+        Err(e) => {
+            report
+                .problems
+                .push(format!("파일을 열 수 없습니다: {}", e));
+            return report;
+        }
+    }
 
-    for (i, line) in lines.iter().enumerate() {
-        if line.starts_with("#EXTINF") {
-            if let Some(m) = extinf_re.find(line) {
-                if let Ok(dur) = m.as_str().parse::<f64>() {
-                    if curr_time + dur >= s_limit && curr_time <= e_limit {
-                        if i + 1 < lines.len() {
-                            let seg_line = lines[i + 1].trim();
-                            if !seg_line.starts_with('#') {
-                                segment_urls.push(resolve_url(&quality_url, seg_line));
-                            }
-                        }
-                    }
-                    curr_time += dur;
-                    if curr_time > e_limit {
-                        break;
-                    }
-                }
+    let probe = tokio::process::Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-i"])
+        .arg(path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .await;
+    let info = match probe {
+        Ok(output) => String::from_utf8_lossy(&output.stderr).to_string(),
+        Err(e) => {
+            report.problems.push(format!("ffmpeg 실행 실패: {}", e));
+            return report;
+        }
+    };
+    let streams: Vec<&str> = info.lines().filter(|l| l.contains("Stream #")).collect();
+    report.has_video = streams.iter().any(|l| l.contains("Video:"));
+    report.has_audio = streams.iter().any(|l| l.contains("Audio:"));
+    report.duration_secs = info
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("Duration:"))
+        .and_then(|rest| rest.split(',').next())
+        .map(|d| d.trim())
+        .filter(|d| d.contains(':'))
+        .map(time_to_sec);
+
+    if streams.is_empty() {
+        report.problems.push("미디어 파일로 열 수 없습니다".into());
+        return report;
+    }
+    let (needs_video, needs_audio) = expected_streams(path);
+    if needs_video && !report.has_video {
+        report.problems.push("영상 스트림이 없습니다".into());
+    }
+    if needs_audio && !report.has_audio {
+        report.problems.push("오디오 스트림이 없습니다".into());
+    }
+    match (report.duration_secs, expected_secs) {
+        (None, _) => report.problems.push("길이를 읽을 수 없습니다".into()),
+        (Some(actual), Some(expected)) => {
+            if let Some(warning) = duration_shortfall(actual, expected) {
+                report.problems.push(warning);
             }
         }
+        _ => {}
     }
 
-    Ok(segment_urls)
-}
+    // 끝부분만 디코딩해 잘린 파일인지 확인
+    let tail = tokio::process::Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-v", "error", "-sseof", VERIFY_TAIL_SEEK])
+        .arg("-i")
+        .arg(path)
+        .args(["-f", "null", "-"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .output()
+        .await;
+    match tail {
+        Ok(output) if output.status.success() && output.stderr.is_empty() => {}
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let first = stderr.lines().next().unwrap_or("").trim();
+            report
+                .problems
+                .push(format!("파일 끝부분을 디코딩할 수 없습니다: {}", first));
+        }
+        Err(e) => report.problems.push(format!("ffmpeg 실행 실패: {}", e)),
+    }
 
-pub async fn download_segments(
-    app: &AppHandle,
-    segment_urls: &[String],
-    temp_dir: &Path,
-) -> Result<(), String> {
-    fs::create_dir_all(temp_dir)
-        .await
-        .map_err(|e| format!("임시 폴더 생성 실패: {}", e))?;
+    report.passed = report.problems.is_empty();
+    report
+}
 
-    let client = build_client();
-    let total = segment_urls.len() as u32;
-    let counter = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+/// 폴더 안에서 검사할 결과 파일 확장자 (오디오만 받은 m4a 포함)
+const VERIFY_EXTENSIONS: [&str; 4] = ["mp4", "ts", "mkv", "m4a"];
 
-    let results: Vec<Result<(), String>> = stream::iter(segment_urls.iter().cloned().enumerate())
-        .map(|(idx, url)| {
-            let client = client.clone();
-            let temp_dir = temp_dir.to_path_buf();
-            let counter = counter.clone();
-            let app = app.clone();
-            let total = total;
+/// 폴더 안의 영상 파일을 모두 검사 (하위 폴더 제외, 이름 순)
+pub async fn verify_media_dir(ffmpeg_path: &Path, dir: &Path) -> Result<Vec<VerifyReport>, String> {
+    let mut entries = fs::read_dir(dir)
+        .await
+        .map_err(|e| format!("폴더 읽기 실패: {}", e))?;
+    let mut paths = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let is_video = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| VERIFY_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+        if is_video && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
 
-            async move {
-                let target_path = temp_dir.join(format!("seg_{:05}.m4s", idx));
+    let mut reports = Vec::with_capacity(paths.len());
+    for path in paths {
+        reports.push(verify_media_file(ffmpeg_path, &path, None).await);
+    }
+    Ok(reports)
+}
 
-                if target_path.exists() {
-                    let done =
-                        counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
-                    let _ = app.emit(
-                        "download-progress",
-                        DownloadProgress {
-                            stage: "downloading".into(),
-                            current: done,
-                            total,
-                            message: format!("세그먼트 다운로드 중... ({}/{})", done, total),
-                        },
-                    );
-                    return Ok(());
-                }
+/// 결과 길이가 이만큼(비율) 이상 짧으면 누락/잘림으로 판단
+const DURATION_SHORTFALL_RATIO: f64 = 0.05;
+/// 짧은 구간에서의 오차를 감안한 최소 허용치 (초)
+const DURATION_SHORTFALL_MIN_SECS: f64 = 5.0;
+
+/// 결과 파일 길이가 기대 길이보다 눈에 띄게 짧으면 경고 메시지
+pub fn duration_shortfall(actual_secs: f64, expected_secs: f64) -> Option<String> {
+    let tolerance = (expected_secs * DURATION_SHORTFALL_RATIO).max(DURATION_SHORTFALL_MIN_SECS);
+    (expected_secs - actual_secs > tolerance).then(|| {
+        format!(
+            "결과 영상이 예상보다 짧습니다 (감지 {:.0}초 / 예상 {:.0}초). \
+             일부 구간이 누락되었을 수 있습니다",
+            actual_secs, expected_secs
+        )
+    })
+}
 
-                let resp = client
-                    .get(&url)
-                    .timeout(std::time::Duration::from_secs(30))
-                    .send()
-                    .await
-                    .map_err(|e| format!("세그먼트 {} 다운로드 실패: {}", idx, e))?;
+/// `ffmpeg -i` 출력에서 영상 스트림의 세로 해상도와 프레임레이트 읽기
+async fn probe_video_stream(ffmpeg_path: &Path, input: &Path) -> Option<(u32, f64)> {
+    let line = probe_video_line(ffmpeg_path, input).await?;
 
-                let bytes = resp
-                    .bytes()
-                    .await
-                    .map_err(|e| format!("세그먼트 {} 읽기 실패: {}", idx, e))?;
+    let size_re = Regex::new(r"\b(\d{2,5})x(\d{2,5})\b").unwrap();
+    let fps_re = Regex::new(r"([\d.]+) fps").unwrap();
 
-                let mut file = fs::File::create(&target_path)
-                    .await
-                    .map_err(|e| format!("파일 생성 실패: {}", e))?;
+    let height = size_re.captures(&line)?[2].parse().ok()?;
+    let fps = fps_re
+        .captures(&line)
+        .and_then(|c| c[1].parse().ok())
+        .unwrap_or(0.0);
+    Some((height, fps))
+}
 
-                file.write_all(&bytes)
-                    .await
-                    .map_err(|e| format!("파일 쓰기 실패: {}", e))?;
+/// 영상 스트림의 색 정보 (colorspace / primaries / transfer)
+#[derive(Debug, Clone)]
+struct ColorMetadata {
+    colorspace: String,
+    primaries: String,
+    transfer: String,
+}
 
-                let done =
-                    counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
-                let _ = app.emit(
-                    "download-progress",
-                    DownloadProgress {
-                        stage: "downloading".into(),
-                        current: done,
-                        total,
-                        message: format!("세그먼트 다운로드 중... ({}/{})", done, total),
-                    },
-                );
+impl ColorMetadata {
+    /// PQ/HLG 전달 특성이나 BT.2020 색역이면 HDR로 판단
+    fn is_hdr(&self) -> bool {
+        matches!(self.transfer.as_str(), "smpte2084" | "arib-std-b67")
+            || self.primaries == "bt2020"
+    }
+}
 
-                Ok(())
-            }
-        })
-        .buffer_unordered(20)
-        .collect()
-        .await;
+/// `yuv420p10le(tv, bt2020nc/bt2020/smpte2084)` 형태의 색 정보 읽기
+async fn probe_color_metadata(ffmpeg_path: &Path, input: &Path) -> Option<ColorMetadata> {
+    let line = probe_video_line(ffmpeg_path, input).await?;
+    let color_re = Regex::new(r"\((?:tv|pc)?,?\s*([\w-]+)/([\w-]+)/([\w-]+)").unwrap();
+    let caps = color_re.captures(&line)?;
 
-    for r in results {
-        r?;
+    Some(ColorMetadata {
+        colorspace: caps[1].to_string(),
+        primaries: caps[2].to_string(),
+        transfer: caps[3].to_string(),
+    })
+}
+
+fn video_encoder(codec: &str) -> Result<&'static str, String> {
+    match codec.to_ascii_lowercase().as_str() {
+        "h264" | "avc" => Ok("libx264"),
+        "hevc" | "h265" => Ok("libx265"),
+        "av1" => Ok("libsvtav1"),
+        other => Err(format!("지원하지 않는 비디오 코덱입니다: {}", other)),
     }
+}
 
-    Ok(())
+fn audio_encoder(codec: &str) -> Result<&'static str, String> {
+    match codec.to_ascii_lowercase().as_str() {
+        "aac" => Ok("aac"),
+        "opus" => Ok("libopus"),
+        "mp3" => Ok("libmp3lame"),
+        "copy" => Ok("copy"),
+        other => Err(format!("지원하지 않는 오디오 코덱입니다: {}", other)),
+    }
 }
 
-pub async fn merge_segments(
-    app: &AppHandle,
-    segment_count: usize,
-    temp_dir: &Path,
-) -> Result<PathBuf, String> {
-    let _ = app.emit(
-        "download-progress",
-        DownloadProgress {
-            stage: "merging".into(),
-            current: 0,
-            total: 1,
-            message: "세그먼트 병합 중...".into(),
-        },
-    );
+impl TranscodeOptions {
+    /// 코덱 이름을 확인하고 ffmpeg 인코딩 인자 생성
+    fn codec_args(&self) -> Result<Vec<String>, String> {
+        let vcodec = video_encoder(&self.video_codec)?;
+        let acodec = audio_encoder(self.audio_codec.as_deref().unwrap_or("aac"))?;
 
-    let combined_path = temp_dir.join("combined.raw");
-    let mut outfile = fs::File::create(&combined_path)
-        .await
-        .map_err(|e| format!("병합 파일 생성 실패: {}", e))?;
+        let mut args = vec!["-c:v".to_string(), vcodec.to_string()];
+        if let Some(crf) = self.crf {
+            if crf > 63 {
+                return Err(format!("CRF 값이 올바르지 않습니다: {}", crf));
+            }
+            args.extend(["-crf".to_string(), crf.to_string()]);
+        } else if let Some(bitrate) = &self.bitrate {
+            args.extend(["-b:v".to_string(), bitrate.clone()]);
+        }
 
-    for i in 0..segment_count {
-        let seg_path = temp_dir.join(format!("seg_{:05}.m4s", i));
-        if seg_path.exists() {
-            let data = fs::read(&seg_path)
-                .await
-                .map_err(|e| format!("세그먼트 읽기 실패: {}", e))?;
-            outfile
-                .write_all(&data)
-                .await
-                .map_err(|e| format!("병합 쓰기 실패: {}", e))?;
+        args.extend(["-c:a".to_string(), acodec.to_string()]);
+        if acodec != "copy" {
+            if let Some(ab) = &self.audio_bitrate {
+                args.extend(["-b:a".to_string(), ab.clone()]);
+            }
         }
+        Ok(args)
     }
 
-    Ok(combined_path)
+    fn encoders(&self) -> Result<Vec<&'static str>, String> {
+        let mut encoders = vec![video_encoder(&self.video_codec)?];
+        let acodec = audio_encoder(self.audio_codec.as_deref().unwrap_or("aac"))?;
+        if acodec != "copy" {
+            encoders.push(acodec);
+        }
+        Ok(encoders)
+    }
+
+    /// 코덱 설정과 ffmpeg 인코더 지원 여부 확인 (세그먼트를 받기 전에 잘못된 설정을 거르기 위해)
+    pub async fn validate(&self, ffmpeg_path: &Path) -> Result<(), String> {
+        self.codec_args()?;
+        check_encoders(ffmpeg_path, &self.encoders()?).await
+    }
 }
 
-pub fn build_output_filename(
-    info: &VideoInfo,
-    start_time: &str,
-    end_time: &str,
-    output_dir: &str,
-) -> PathBuf {
-    let safe_channel = sanitize_filename(&info.channel);
-    let safe_title = sanitize_filename(&info.title);
-    let s_tag = start_time.replace(':', "");
-    let e_tag = if end_time.is_empty() {
-        "END".to_string()
-    } else {
-        end_time.replace(':', "")
-    };
+/// ffmpeg 빌드가 지정한 인코더를 모두 지원하는지 확인
+async fn check_encoders(ffmpeg_path: &Path, encoders: &[&str]) -> Result<(), String> {
+    let output = tokio::process::Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-encoders"])
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("ffmpeg 실행 실패: {}", e))?;
 
-    let filename = format!("{}_{}_{}_{}.mp4", safe_channel, safe_title, s_tag, e_tag);
-    Path::new(output_dir).join(filename)
+    let list = String::from_utf8_lossy(&output.stdout);
+    for encoder in encoders {
+        let supported = list
+            .lines()
+            .any(|l| l.split_whitespace().nth(1) == Some(*encoder));
+        if !supported {
+            return Err(format!("설치된 ffmpeg가 {} 인코더를 지원하지 않습니다", encoder));
+        }
+    }
+    Ok(())
 }
 
-pub async fn remux_with_ffmpeg(
+/// 지정한 코덱(과 선택적인 해상도/프레임레이트 축소)으로 재인코딩
+/// (`-progress`로 진행률 보고)
+///
+/// `expected_secs`는 결과 영상의 예상 길이로, 진행률 계산에 사용한다.
+pub async fn transcode_with_ffmpeg(
     app: &AppHandle,
     ffmpeg_path: &Path,
     combined_path: &Path,
     output_path: &Path,
+    options: &TranscodeOptions,
+    scale: Option<&ScaleOptions>,
+    expected_secs: f64,
 ) -> Result<(), String> {
+    let codec_args = options.codec_args()?;
+    options.validate(ffmpeg_path).await?;
+
+    let filter_args = match scale {
+        Some(scale) => {
+            let source = probe_video_stream(ffmpeg_path, combined_path).await;
+            scale.filter_args(source)?
+        }
+        None => Vec::new(),
+    };
+
     let _ = app.emit(
         "download-progress",
         DownloadProgress {
-            stage: "remuxing".into(),
+            stage: "encoding".into(),
             current: 0,
-            total: 1,
-            message: "ffmpeg로 리먹싱 중...".into(),
+            total: 100,
+            message: "ffmpeg로 재인코딩 중...".into(),
         },
     );
 
-    let output = tokio::process::Command::new(ffmpeg_path)
-        .args([
-            "-y",
-            "-i",
-            combined_path.to_str().unwrap(),
-            "-c",
-            "copy",
-            "-map",
-            "0",
-            "-movflags",
-            "faststart",
-            "-bsf:a",
-            "aac_adtstoasc",
-            output_path.to_str().unwrap(),
-        ])
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-loglevel".into(),
+        "error".into(),
+        "-nostats".into(),
+        "-progress".into(),
+        "pipe:1".into(),
+        "-i".into(),
+        combined_path.to_string_lossy().to_string(),
+    ];
+    args.extend(filter_args);
+    args.extend(codec_args);
+    args.extend([
+        "-movflags".into(),
+        "faststart".into(),
+        output_path.to_string_lossy().to_string(),
+    ]);
+
+    let mut child = tokio::process::Command::new(ffmpeg_path)
+        .args(&args)
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
-        .output()
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("ffmpeg 실행 실패: {}", e))?;
+
+    // 전체 취소 시 child가 drop되면서 ffmpeg 프로세스도 종료된다
+    let cancel_state = app.state::<CancelState>();
+    tokio::select! {
+        result = wait_transcode(app, &mut child, expected_secs) => result,
+        _ = cancel_state.ffmpeg_cancelled() => Err(CANCELLED_ERROR.to_string()),
+    }
+}
+
+/// 재인코딩 중인 ffmpeg의 진행률을 전달하며 종료까지 대기
+async fn wait_transcode(
+    app: &AppHandle,
+    child: &mut tokio::process::Child,
+    expected_secs: f64,
+) -> Result<(), String> {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+
+    let stdout = child.stdout.take().ok_or("ffmpeg 출력을 읽을 수 없습니다")?;
+    let mut lines = BufReader::new(stdout).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        // out_time_us / out_time_ms 모두 마이크로초 단위
+        let Some(us) = line
+            .strip_prefix("out_time_us=")
+            .or_else(|| line.strip_prefix("out_time_ms="))
+            .and_then(|v| v.trim().parse::<f64>().ok())
+        else {
+            continue;
+        };
+
+        if expected_secs > 0.0 {
+            let done_secs = us / 1_000_000.0;
+            let percent = ((done_secs / expected_secs) * 100.0).clamp(0.0, 100.0) as u32;
+            let _ = app.emit(
+                "download-progress",
+                DownloadProgress {
+                    stage: "encoding".into(),
+                    current: percent,
+                    total: 100,
+                    message: format!("ffmpeg로 재인코딩 중... ({}%)", percent),
+                },
+            );
+        }
+    }
+
+    let mut stderr = String::new();
+    if let Some(mut err) = child.stderr.take() {
+        let _ = err.read_to_string(&mut stderr).await;
+    }
+    log_ffmpeg_stderr(stderr.as_bytes());
+
+    let status = child
+        .wait()
         .await
         .map_err(|e| format!("ffmpeg 실행 실패: {}", e))?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!(
-            "ffmpeg 오류 (코드 {:?}): {}",
-            output.status.code(),
-            stderr
-        ));
+    if !status.success() {
+        return Err(format!("ffmpeg 오류 (코드 {:?}): {}", status.code(), stderr));
     }
 
     Ok(())
@@ -995,3 +6326,669 @@ pub async fn cleanup_temp(temp_dir: &Path) -> Result<(), String> {
         .map_err(|e| format!("임시 파일 정리 실패: {}", e))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_progress_counts_each_index_once() {
+        let progress = SegmentProgress::new(3);
+        assert_eq!(progress.complete(0), 1);
+        // 같은 세그먼트가 두 번 완료 보고되어도 한 번만 센다
+        assert_eq!(progress.complete(0), 1);
+        assert_eq!(progress.complete(2), 2);
+        // 재시도 끝에 성공한 세그먼트도 처음 완료될 때만 센다
+        assert_eq!(progress.complete(1), 3);
+        assert_eq!(progress.complete(1), 3);
+        assert_eq!(progress.complete(2), 3);
+        assert_eq!(progress.total(), 3);
+    }
+
+    #[test]
+    fn segment_progress_ignores_out_of_range_index() {
+        let progress = SegmentProgress::new(2);
+        assert_eq!(progress.complete(5), 0);
+        assert_eq!(progress.complete(1), 1);
+        assert_eq!(progress.complete(5), 1);
+    }
+
+    #[test]
+    fn equal_bandwidth_variants_sort_stably() {
+        let a = serde_json::json!({ "id": "b-720", "bandwidth": 4000000, "height": 720 });
+        let b = serde_json::json!({ "id": "a-720", "bandwidth": 4000000, "height": 720 });
+        let c = serde_json::json!({ "id": "c-1080", "bandwidth": 4000000, "height": 1080 });
+
+        for reps in [[&a, &b, &c], [&c, &b, &a], [&b, &c, &a]] {
+            let mut variants: Vec<QualityVariant> =
+                reps.into_iter().map(dash_rep_variant).collect();
+            variants.sort_by(compare_variants);
+            let ids: Vec<&str> = variants.iter().map(|v| v.id.as_str()).collect();
+            // bandwidth가 같으면 해상도 높은 순, 그다음 ID 사전순
+            assert_eq!(ids, ["c-1080", "a-720", "b-720"]);
+        }
+    }
+
+    /// 테스트마다 따로 쓰는 빈 임시 폴더
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "chzzk-downloader-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn merge_concatenates_segments_in_index_order() {
+        let dir = test_dir("merge-order");
+        // 세그먼트가 완료되는 순서는 인덱스 순서와 다를 수 있다 (2번은 실패해서 없음)
+        for idx in [3, 0, 4, 1] {
+            std::fs::write(dir.join(segment_file_name(idx)), format!("[{}]", idx)).unwrap();
+        }
+
+        let output = dir.join(COMBINED_FILE_NAME);
+        concat_segment_files(&dir, 0..5, &output).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "[0][1][3][4]");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn whole_vod_range_uses_plain_filename() {
+        let info = VideoInfo {
+            title: "다시보기".into(),
+            channel: "채널".into(),
+            ..Default::default()
+        };
+        let whole = Path::new("out").join("채널_다시보기.mp4");
+        assert_eq!(build_output_filename(&info, "", "", "out", None), whole);
+        assert_eq!(
+            build_output_filename(&info, "00:00:00", "", "out", None),
+            whole
+        );
+        assert_eq!(
+            build_output_filename(&info, "00:10:00", "", "out", None),
+            Path::new("out").join("채널_다시보기_001000_END.mp4")
+        );
+    }
+
+    #[test]
+    fn resolve_url_follows_url_join_rules() {
+        let base = "https://cdn.example.com/vod/abc/720p/index.m3u8?sig=xyz";
+        assert_eq!(
+            resolve_url(base, "https://other.example.com/seg_1.ts"),
+            "https://other.example.com/seg_1.ts"
+        );
+        assert_eq!(
+            resolve_url(base, "/hls/seg_1.ts"),
+            "https://cdn.example.com/hls/seg_1.ts"
+        );
+        assert_eq!(
+            resolve_url(base, "../audio/seg_1.ts"),
+            "https://cdn.example.com/vod/abc/audio/seg_1.ts"
+        );
+        // base의 쿼리는 버리고 relative의 쿼리는 유지
+        assert_eq!(
+            resolve_url(base, "seg_1.ts"),
+            "https://cdn.example.com/vod/abc/720p/seg_1.ts"
+        );
+        assert_eq!(
+            resolve_url(base, "init.mp4?token=abc"),
+            "https://cdn.example.com/vod/abc/720p/init.mp4?token=abc"
+        );
+    }
+
+    #[test]
+    fn segment_file_names_sort_past_100000_segments() {
+        let indices = [0, 9, 99_999, 100_000, 100_001, 123_456, 999_999, 1_000_000];
+        assert_eq!(segment_file_name(123_456), "seg_0123456.m4s");
+
+        let mut names: Vec<String> = indices
+            .iter()
+            .rev()
+            .map(|&i| segment_file_name(i))
+            .collect();
+        names.sort();
+        let parsed: Vec<usize> = names.iter().filter_map(|n| segment_index(n)).collect();
+        // 이름 순서와 인덱스 순서가 같아야 한다
+        assert_eq!(parsed, indices);
+
+        // 예전 5자리 이름과 6자리를 넘는 이름도 읽을 수 있다
+        assert_eq!(segment_index("seg_00042.m4s"), Some(42));
+        assert_eq!(segment_index("seg_100000.m4s"), Some(100_000));
+        assert_eq!(segment_index("seg_.m4s"), None);
+        assert_eq!(segment_index("combined.raw"), None);
+    }
+
+    #[test]
+    fn vod_temp_dir_is_stable_per_request() {
+        let dir = vod_temp_dir("out", "12345", "00:10:00", "00:20:00", "1080p");
+        // 다시 실행해도 같은 폴더여야 이어받을 수 있다 (해시 값 고정)
+        assert_eq!(dir, Path::new("out").join("temp_12345_1e9593a5"));
+        assert_eq!(
+            vod_temp_dir("out", "12345", " 00:10:00 ", "00:20:00 ", "1080p"),
+            dir
+        );
+
+        // 화질이나 구간이 다르면 다른 폴더
+        let others = [
+            vod_temp_dir("out", "12345", "00:10:00", "00:20:00", "720p"),
+            vod_temp_dir("out", "12345", "00:10:00", "00:20:00", ""),
+            vod_temp_dir("out", "12345", "00:10:00", "00:30:00", "1080p"),
+            vod_temp_dir("out", "12345", "00:00:00", "00:20:00", "1080p"),
+            // 항목 경계가 달라도 이어 붙인 문자열이 같으면 안 된다
+            vod_temp_dir("out", "12345", "00:10:0", "000:20:00", "1080p"),
+        ];
+        for (i, other) in others.iter().enumerate() {
+            assert_ne!(other, &dir);
+            assert!(others[i + 1..].iter().all(|o| o != other));
+        }
+    }
+
+    /// `tests/fixtures`에 저장해 둔 API/플레이리스트 응답
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/",
+                $name
+            ))
+        };
+    }
+
+    fn json_fixture(text: &str) -> serde_json::Value {
+        serde_json::from_str(text).unwrap()
+    }
+
+    const MASTER_URL: &str = "https://cdn-a.example.com/vod/abc/hls/playlist.m3u8?_HLS_token=abc";
+
+    #[test]
+    fn fixture_hls_media_playlist() {
+        let playlist = fixture!("hls_media.m3u8");
+        let playlist_url = "https://cdn.example.com/vod/abc/1080p/hls/index.m3u8?_HLS_token=abc";
+
+        let segments = parse_media_playlist(playlist, playlist_url, "", "").unwrap();
+        let urls: Vec<&str> = segments.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://cdn.example.com/vod/abc/1080p/hls/seg_100.ts",
+                "https://cdn.example.com/vod/abc/1080p/hls/seg_101.ts",
+                "https://cdn.example.com/vod/abc/1080p/hls/seg_102.ts",
+                "https://cdn.example.com/vod/abc/1080p/shared/seg_103.ts?_HLS_token=def",
+                "https://cdn.example.com/vod/abc/1080p/hls/seg_104.ts",
+                "https://cdn2.example.com/vod/abc/1080p/hls/seg_105.ts",
+            ]
+        );
+        assert!(segments.iter().all(|s| s.key.is_none()));
+
+        let owned: Vec<String> = urls.iter().map(|u| u.to_string()).collect();
+        assert!(is_ts_source(&VideoInfo::default(), &owned));
+
+        // 구간에 걸치는 세그먼트만 (4~8초, 8~12초)
+        let ranged = parse_media_playlist(playlist, playlist_url, "00:00:05", "00:00:10").unwrap();
+        let urls: Vec<&str> = ranged.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://cdn.example.com/vod/abc/1080p/hls/seg_101.ts",
+                "https://cdn.example.com/vod/abc/1080p/hls/seg_102.ts",
+            ]
+        );
+    }
+
+    #[test]
+    fn fixture_hls_fmp4_playlist_with_keys() {
+        let playlist = fixture!("hls_media_fmp4.m3u8");
+        let playlist_url = "https://cdn.example.com/vod/abc/hls/index.m3u8";
+
+        let segments = parse_media_playlist(playlist, playlist_url, "", "").unwrap();
+        let urls: Vec<&str> = segments.iter().map(|s| s.url.as_str()).collect();
+        // 초기화 세그먼트가 맨 앞
+        assert_eq!(
+            urls,
+            [
+                "https://cdn.example.com/vod/abc/hls/init.mp4?_HLS_token=abc",
+                "https://cdn.example.com/vod/abc/hls/seg_0.m4s",
+                "https://cdn.example.com/vod/abc/hls/seg_1.m4s",
+                "https://cdn.example.com/vod/abc/hls/seg_2.m4s",
+                "https://cdn.example.com/vod/abc/hls/seg_3.m4s",
+            ]
+        );
+        assert!(segments[0].key.is_none());
+
+        let explicit_iv: [u8; 16] = std::array::from_fn(|i| i as u8);
+        let key1 = "https://cdn.example.com/vod/abc/hls/key.bin".to_string();
+        let key2 = "https://cdn.example.com/vod/abc/hls/key2.bin".to_string();
+        assert_eq!(segments[1].key, Some((key1.clone(), explicit_iv)));
+        assert_eq!(segments[2].key, Some((key1, explicit_iv)));
+        // IV가 없으면 미디어 시퀀스 번호
+        assert_eq!(segments[3].key, Some((key2, 2u128.to_be_bytes())));
+        assert!(segments[4].key.is_none());
+
+        let owned: Vec<String> = urls.iter().map(|u| u.to_string()).collect();
+        assert!(!is_ts_source(&VideoInfo::default(), &owned));
+    }
+
+    #[test]
+    fn fixture_dash_playback() {
+        let playback = json_fixture(fixture!("dash_playback.json"));
+
+        // 화질을 지정하지 않으면 최고 bandwidth
+        let timeline = dash_timeline_from_playback(&playback, None).unwrap();
+        assert_eq!(timeline.representation_id, "1080p");
+        assert_eq!(timeline.timescale, 90000.0);
+        assert_eq!(timeline.entries, [(360000, 5), (225000, 1)]);
+        assert_eq!(timeline.base_url, "https://cdn-a.example.com/vod/abc/");
+        assert_eq!(
+            timeline.media_template,
+            "$RepresentationID$/seg_$Number%06d$.ts"
+        );
+        assert!(timeline.initialization_url.is_none());
+        assert_eq!(timeline_bounds(&timeline), (Some(0.0), Some(20.0), 22.5));
+
+        let timeline = dash_timeline_from_playback(&playback, Some("720p")).unwrap();
+        assert_eq!(timeline.representation_id, "720p");
+        assert_eq!(timeline.entries, [(4000, 5), (2500, 1)]);
+        assert!(dash_timeline_from_playback(&playback, Some("4k")).is_err());
+    }
+
+    #[test]
+    fn fixture_videos_api_content() {
+        let resp = json_fixture(fixture!("videos_api_dash.json"));
+        let content = &resp["content"];
+        assert!(geo_block_error(&resp).is_none());
+
+        let info = parse_video_content(content, PreferFormat::Auto).unwrap();
+        assert_eq!(info.title, "테스트 다시보기");
+        assert_eq!(info.channel, "테스트 채널");
+        assert_eq!(info.duration, 22);
+        assert_eq!(info.publish_date.as_deref(), Some("2026-10-01 21:00:00"));
+        assert_eq!(info.category.as_deref(), Some("Just Chatting"));
+        assert!(info.is_dash);
+        assert_eq!(info.dash_video_id.as_deref(), Some("ABCDEF0123456789"));
+        assert_eq!(info.dash_in_key.as_deref(), Some("in-key-0123"));
+        // 챕터는 시작 시각 순
+        let chapters: Vec<(&str, f64)> = info
+            .chapters
+            .iter()
+            .map(|c| (c.title.as_str(), c.start_secs))
+            .collect();
+        assert_eq!(chapters, [("시작", 0.0), ("게임", 10.0)]);
+    }
+
+    #[test]
+    fn fixture_videos_api_hls_content() {
+        let resp = json_fixture(fixture!("videos_api_hls.json"));
+        let content = &resp["content"];
+
+        let info = parse_video_content(content, PreferFormat::Auto).unwrap();
+        assert!(!info.is_dash);
+        assert_eq!(info.master_url, MASTER_URL);
+        assert_eq!(info.duration, 0);
+
+        // DASH를 선호하면 videoId/inKey로 DASH
+        let info = parse_video_content(content, PreferFormat::Dash).unwrap();
+        assert!(info.is_dash);
+        assert_eq!(info.dash_in_key.as_deref(), Some("in-key-0123"));
+    }
+
+    #[test]
+    fn fixture_hls_master_variants() {
+        let master = fixture!("hls_master.m3u8");
+        let mut variants = parse_master_playlist_variants(master);
+        variants.sort_by(compare_variants);
+        let summary: Vec<(&str, u64, u32)> = variants
+            .iter()
+            .map(|v| (v.id.as_str(), v.bandwidth, v.height))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("1080p/hls/index.m3u8?_HLS_token=abc", 8192000, 1080),
+                ("720p/hls/index.m3u8?_HLS_token=abc", 2692000, 720),
+                ("480p/hls/index.m3u8?_HLS_token=abc", 1192000, 480),
+            ]
+        );
+        assert_eq!(variants[0].width, 1920);
+        assert_eq!(variants[0].label(), "1080p (8.2Mbps)");
+
+        assert_eq!(
+            select_variant_url(MASTER_URL, master, None).unwrap(),
+            "https://cdn-a.example.com/vod/abc/hls/1080p/hls/index.m3u8?_HLS_token=abc"
+        );
+        assert_eq!(
+            select_variant_url(MASTER_URL, master, Some(&variants[2].id)).unwrap(),
+            "https://cdn-a.example.com/vod/abc/hls/480p/hls/index.m3u8?_HLS_token=abc"
+        );
+        assert!(select_variant_url(MASTER_URL, "#EXTM3U\n", None).is_err());
+    }
+
+    #[test]
+    fn fixture_dash_segments() {
+        let playback = json_fixture(fixture!("dash_playback.json"));
+
+        let mut variants = parse_dash_quality_variants(&playback);
+        variants.sort_by(compare_variants);
+        let ids: Vec<&str> = variants.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["1080p", "720p", "480p"]);
+
+        let (segments, report) = compute_dash_segments(&playback, "", "", None).unwrap();
+        assert_eq!(segments.len(), 6);
+        assert_eq!(
+            segments[0],
+            "https://cdn-a.example.com/vod/abc/1080p/seg_000001.ts"
+        );
+        assert_eq!(
+            segments[5],
+            "https://cdn-a.example.com/vod/abc/1080p/seg_000006.ts"
+        );
+        assert_eq!(report.timeline_secs, 22.5);
+        assert_eq!(report.timeline_segments, 6);
+        let dash_info = VideoInfo {
+            is_dash: true,
+            ..Default::default()
+        };
+        assert!(is_ts_source(&dash_info, &segments));
+
+        let (segments, report) =
+            compute_dash_segments(&playback, "00:00:05", "00:00:10", Some("720p")).unwrap();
+        assert_eq!(
+            segments,
+            [
+                "https://cdn-a.example.com/vod/abc/720p/seg_2.ts",
+                "https://cdn-a.example.com/vod/abc/720p/seg_3.ts",
+            ]
+        );
+        assert_eq!(report.matched_segments, 2);
+        assert_eq!(report.first_segment_secs, Some(4.0));
+        assert_eq!(report.last_segment_secs, Some(8.0));
+
+        // 오디오만 받기는 fMP4라 초기화 세그먼트가 맨 앞
+        let audio = compute_dash_audio_segments(&playback, "", "").unwrap();
+        assert_eq!(audio.len(), 7);
+        assert_eq!(
+            audio[0],
+            "https://cdn-a.example.com/vod/abc/audio-128k/init.mp4"
+        );
+        assert!(!is_ts_source(&dash_info, &audio));
+    }
+
+    #[test]
+    fn fixture_clip_play_info_and_playback() {
+        let play_info =
+            parse_clip_play_info(&json_fixture(fixture!("clip_play_info.json"))).unwrap();
+        assert_eq!(play_info.title, "테스트 클립");
+        assert_eq!(play_info.channel, "테스트 채널");
+        assert_eq!(
+            play_info.created_date.as_deref(),
+            Some("2026-10-03 12:00:00")
+        );
+        assert_eq!(play_info.video_id, "CLIP0123456789");
+        assert_eq!(play_info.in_key, "clip-in-key");
+
+        let (variants, thumbnail) =
+            parse_clip_playback(&json_fixture(fixture!("clip_playback.json")));
+        assert_eq!(thumbnail, "https://clip-thumb.example.com/large.jpg");
+        // baseURL이 없는 representation은 빼고, ID가 없으면 순번
+        let summary: Vec<(&str, &str, u32, u64)> = variants
+            .iter()
+            .map(|v| (v.id.as_str(), v.url.as_str(), v.height, v.bandwidth))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("720p", "https://clip.example.com/720p.mp4", 720, 2500000),
+                ("1", "https://clip.example.com/1080p.mp4", 1080, 6000000),
+            ]
+        );
+    }
+
+    fn ts_playback(representations: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "period": [{
+                "adaptationSet": [{
+                    "mimeType": "video/mp2t",
+                    "representation": representations,
+                }]
+            }]
+        })
+    }
+
+    #[test]
+    fn zero_duration_vod_uses_timeline_length() {
+        let content = serde_json::json!({
+            "videoTitle": "방금 끝난 방송",
+            "duration": 0,
+            "videoId": "dash-id",
+            "inKey": "in-key",
+        });
+        let info = parse_video_content(&content, PreferFormat::Auto).unwrap();
+        assert!(info.is_dash);
+        assert_eq!(info.duration, 0);
+
+        let playback = ts_playback(serde_json::json!([{
+            "id": "1080p",
+            "bandwidth": 8000000,
+            "height": 1080,
+            "baseURL": [{ "value": "https://cdn.example.com/" }],
+            "segmentTemplate": {
+                "media": "$RepresentationID$/$Number%06d$.ts",
+                "timescale": 1000,
+                "segmentTimeline": { "s": [{ "d": 2000, "r": 2 }, { "d": 1500 }] },
+            },
+        }]));
+        let timeline = dash_timeline_from_playback(&playback, None).unwrap();
+        // fill_missing_duration은 API의 duration 대신 이 끝 시각을 쓴다
+        assert_eq!(timeline_bounds(&timeline), (Some(0.0), Some(6.0), 7.5));
+
+        // 구간을 비워 두면 duration과 상관없이 VOD 전체를 받는다
+        let (segments, report) = compute_dash_segments(&playback, "", "", None).unwrap();
+        assert_eq!(
+            segments,
+            [
+                "https://cdn.example.com/1080p/000001.ts",
+                "https://cdn.example.com/1080p/000002.ts",
+                "https://cdn.example.com/1080p/000003.ts",
+                "https://cdn.example.com/1080p/000004.ts",
+            ]
+        );
+        assert_eq!(report.timeline_secs, 7.5);
+    }
+
+    fn scheduled(order: DownloadOrder) -> Vec<usize> {
+        let mut jobs: Vec<usize> = (0..8).collect();
+        order.schedule(&mut jobs);
+        jobs
+    }
+
+    #[test]
+    fn download_order_schedules_jobs() {
+        assert_eq!(
+            scheduled(DownloadOrder::Unordered),
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        assert_eq!(
+            scheduled(DownloadOrder::Sequential),
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        assert_eq!(scheduled(DownloadOrder::Reverse), [7, 6, 5, 4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn sidecar_files_match_exact_names_only() {
+        let dir = test_dir("sidecars");
+        for name in [
+            "X.mp4",
+            "X.jpg",
+            "X.nfo",
+            "X.ko.vtt",
+            "X.chat.json",
+            "X.1.mp4",
+            "X.1.nfo",
+            "X.1.ko.vtt",
+            "X.txt",
+        ] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let names: Vec<String> = sidecar_files(&dir.join("X.mp4"))
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["X.chat.json", "X.jpg", "X.ko.vtt", "X.nfo"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn sidecar_files_list_only_playlist_segments() {
+        let dir = test_dir("sidecars-playlist");
+        let segment_dir = dir.join("X_segments");
+        std::fs::create_dir_all(&segment_dir).unwrap();
+        for name in ["init.mp4", "seg_0.m4s", "notes.txt"] {
+            std::fs::write(segment_dir.join(name), b"").unwrap();
+        }
+        std::fs::write(
+            dir.join("X.m3u8"),
+            "#EXTM3U\n#EXT-X-MAP:URI=\"X_segments/init.mp4\"\n#EXTINF:2.000,\nX_segments/seg_0.m4s\n#EXT-X-ENDLIST\n",
+        )
+        .unwrap();
+        assert_eq!(
+            sidecar_files(&dir.join("X.m3u8")),
+            [segment_dir.join("init.mp4"), segment_dir.join("seg_0.m4s")]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// 요청 하나에 `body`를 돌려주는 로컬 HTTP 서버 (주소 반환)
+    async fn serve_once(body: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 4096];
+            let _ = socket.read(&mut request).await;
+            let resp = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(resp.as_bytes()).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn fixture_video_info_quality_list() {
+        // fetch_video_info와 같은 순서: videos API content → master playlist → 정렬된 화질 목록
+        let resp = json_fixture(fixture!("videos_api_hls.json"));
+        let mut info = parse_video_content(&resp["content"], PreferFormat::Auto).unwrap();
+        info.master_url = format!(
+            "{}/hls/master.m3u8",
+            serve_once(fixture!("hls_master.m3u8")).await
+        );
+
+        let variants = fetch_quality_variants(&build_client(), &info)
+            .await
+            .unwrap();
+        let labels: Vec<String> = variants.iter().map(QualityVariant::label).collect();
+        assert_eq!(
+            labels,
+            ["1080p (8.2Mbps)", "720p (2.7Mbps)", "480p (1.2Mbps)"]
+        );
+        assert_eq!(variants[1].width, 1280);
+    }
+
+    #[test]
+    fn vod_temp_dir_treats_full_range_alike() {
+        let whole = vod_temp_dir("out", "12345", "", "", "best");
+        assert_eq!(vod_temp_dir("out", "12345", "00:00:00", "", "best"), whole);
+        assert_eq!(vod_temp_dir("out", "12345", " 0 ", " ", "best"), whole);
+        assert_ne!(vod_temp_dir("out", "12345", "00:00:01", "", "best"), whole);
+        assert!(check_segment_range(2, 1, 10).is_err());
+        assert!(check_segment_range(0, 10, 10).is_err());
+        assert!(check_segment_range(3, 9, 10).is_ok());
+    }
+
+    #[test]
+    fn expected_streams_follow_output_suffix() {
+        assert_eq!(expected_streams(Path::new("a/채널_제목.mp4")), (true, true));
+        assert_eq!(
+            expected_streams(Path::new("a/채널_제목_video.mp4")),
+            (true, false)
+        );
+        assert_eq!(
+            expected_streams(Path::new("a/채널_제목_audio.m4a")),
+            (false, true)
+        );
+        assert_eq!(
+            expected_streams(Path::new("a/채널_제목.m4a")),
+            (false, true)
+        );
+        assert_eq!(expected_streams(Path::new("a/채널_제목.ts")), (true, true));
+    }
+
+    /// 세그먼트가 `completion` 순서로 완료될 때, 완료마다 병합 파일에 붙은 세그먼트 수
+    async fn progressive_lengths(name: &str, completion: &[usize]) -> Vec<usize> {
+        let dir = test_dir(name);
+        let combined = dir.join(COMBINED_FILE_NAME);
+        std::fs::write(&combined, b"").unwrap();
+        let merge = ProgressiveMerge::default();
+
+        let mut lengths = Vec::new();
+        for &idx in completion {
+            let path = dir.join(segment_file_name(idx));
+            std::fs::write(&path, [idx as u8]).unwrap();
+            append_progressive(&merge, &path).await.unwrap();
+            lengths.push(std::fs::read(&combined).unwrap().len());
+        }
+        // 모두 받은 뒤에는 순서대로 한 번씩 붙어 있어야 한다
+        let expected: Vec<u8> = (0..completion.len() as u8).collect();
+        assert_eq!(std::fs::read(&combined).unwrap(), expected);
+        let _ = std::fs::remove_dir_all(&dir);
+        lengths
+    }
+
+    #[tokio::test]
+    async fn progressive_merge_follows_download_order() {
+        for (order, lengths) in [
+            (DownloadOrder::Sequential, [1, 2, 3, 4, 5]),
+            (DownloadOrder::Unordered, [1, 2, 3, 4, 5]),
+            // 뒤에서부터 받으면 첫 세그먼트가 올 때까지 재생 가능한 앞부분이 없다
+            (DownloadOrder::Reverse, [0, 0, 0, 0, 5]),
+        ] {
+            // 시작한 순서대로 완료된다고 보고 확인
+            let mut completion: Vec<usize> = (0..5).collect();
+            order.schedule(&mut completion);
+            let name = format!("progressive-{:?}", order);
+            assert_eq!(progressive_lengths(&name, &completion).await, lengths);
+        }
+
+        // 순서대로 받기의 창 안에서 완료 순서가 바뀌면 빈틈이 메워질 때까지 기다린다
+        assert_eq!(
+            progressive_lengths("progressive-window", &[1, 0, 3, 4, 2]).await,
+            [0, 2, 2, 2, 5]
+        );
+    }
+
+    #[test]
+    fn download_order_reads_legacy_ordered_flag() {
+        #[derive(Deserialize)]
+        struct Options {
+            #[serde(
+                default,
+                alias = "ordered",
+                deserialize_with = "DownloadOrder::deserialize_with_flag"
+            )]
+            order: DownloadOrder,
+        }
+        let order = |json: &str| serde_json::from_str::<Options>(json).unwrap().order;
+        assert_eq!(order(r#"{"ordered": true}"#), DownloadOrder::Sequential);
+        assert_eq!(order(r#"{"ordered": false}"#), DownloadOrder::Unordered);
+        assert_eq!(order(r#"{"order": "reverse"}"#), DownloadOrder::Reverse);
+        assert_eq!(order("{}"), DownloadOrder::Unordered);
+    }
+}
@@ -1,13 +1,18 @@
 mod downloader;
+mod remote;
 
 use downloader::{
-    build_output_filename, cleanup_temp, download_segments, find_ffmpeg, get_clip_info,
-    get_video_info, get_video_info_with_cookies, merge_segments,
-    parse_segments, remux_with_ffmpeg, DownloadProgress,
+    build_output_filename, check_login, cleanup_temp, download_segments, find_ffmpeg,
+    get_clip_info, get_video_info, get_video_info_with_cookies, merge_segments, remux_with_ffmpeg,
+    resolve_segments, segment_resolver, time_to_sec, CancelState, Chapter, ClipPage,
+    DownloadLimits, DownloadProgress, DownloadStatus, IpMode, NetworkOptions, PreferFormat,
+    ResumeManifest, ScaleOptions, SegmentDownloadConfig, SubtitleTrack, ThumbnailSprite,
+    TranscodeOptions, VideoInfo,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{Emitter, Manager};
 
 #[cfg(target_os = "windows")]
@@ -32,6 +37,13 @@ struct VodInfo {
     duration: u64,
     thumbnail: String,
     qualities: Vec<VideoQuality>,
+    subtitles: Vec<SubtitleTrack>,
+    /// 챕터 마커 (없으면 빈 목록)
+    chapters: Vec<Chapter>,
+    /// 저장된 로그인 정보(쿠키)로 조회했는지
+    authenticated: bool,
+    /// API가 로그인/성인 인증이 필요하다고 알려줌 (받을 수 있는 화질이 제한될 수 있음)
+    requires_login: bool,
 }
 
 #[derive(Serialize)]
@@ -39,6 +51,200 @@ struct ClipInfoResp {
     title: String,
     channel: String,
     thumbnail: String,
+    /// VodInfo와 같은 형태의 화질 목록 (id는 클립 representation ID)
+    qualities: Vec<VideoQuality>,
+}
+
+/// `diagnose` 명령의 환경 진단 결과
+#[derive(Serialize)]
+struct DiagnosticReport {
+    ffmpeg_path: Option<String>,
+    /// "system" (PATH의 ffmpeg) 또는 "bundled" (앱이 설치한 ffmpeg)
+    ffmpeg_source: Option<String>,
+    ffmpeg_version: Option<String>,
+    credentials_saved: bool,
+    /// 저장된 쿠키로 로그인 확인 결과 (쿠키가 없거나 확인 실패 시 None)
+    credentials_valid: Option<bool>,
+    api_reachable: bool,
+    api_error: Option<String>,
+    app_data_dir: Option<String>,
+    app_data_writable: bool,
+    output_dir: Option<String>,
+    output_free_bytes: Option<u64>,
+}
+
+/// `get_app_paths` 결과: 앱이 파일을 두는 위치 (문제 해결/수동 정리용)
+#[derive(Serialize)]
+struct AppPaths {
+    app_data_dir: String,
+    app_local_data_dir: String,
+    /// Tauri 로그 폴더 (플랫폼 기본 위치)
+    app_log_dir: Option<String>,
+    /// 앱이 설치한 ffmpeg 위치 (설치하지 않았으면 아직 없을 수 있음)
+    ffmpeg_path: String,
+    credentials_path: String,
+    settings_path: String,
+    history_path: String,
+}
+
+/// 안전 상한 초과 오류의 접두사 (UI가 확인 창으로 바꿔 보여줄 수 있도록)
+const CAP_EXCEEDED_ERROR: &str = "CAP_EXCEEDED";
+
+/// download_vod의 선택 옵션 (모두 생략 가능)
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct VodDownloadOptions {
+    /// 실패한 세그먼트를 건너뛰고 받을 수 있는 만큼 받기
+    continue_on_error: bool,
+    transcode: Option<TranscodeOptions>,
+    scale: Option<ScaleOptions>,
+    /// 구간 길이 상한 (초)
+    max_duration_secs: Option<u64>,
+    /// 예상 크기 상한 (바이트)
+    max_size_bytes: Option<u64>,
+    /// 같은 영상/구간/화질을 이미 받았으면 파일명과 상관없이 기존 파일 경로 반환
+    dedupe: bool,
+    /// ffmpeg 리먹싱 없이 병합한 TS 스트림을 그대로 .ts로 저장
+    no_remux: bool,
+    /// 지정하면 이 길이(분)마다 나눠 여러 파일로 저장
+    split_minutes: Option<u32>,
+    /// 화질을 지정하지 않았을 때 이 높이(예: 1080) 이하 중 최고 화질 선택
+    max_height: Option<u32>,
+    /// 세그먼트를 받는 순서 (unordered/sequential/reverse).
+    /// 예전 옵션 `ordered: true`는 sequential로 읽는다
+    #[serde(
+        alias = "ordered",
+        deserialize_with = "downloader::DownloadOrder::deserialize_with_flag"
+    )]
+    order: downloader::DownloadOrder,
+    /// 하나로 합친 파일 대신 영상만(`_video.mp4`)/오디오만(`_audio.m4a`) 두 파일로 저장
+    separate_tracks: bool,
+    /// DASH의 별도 오디오 트랙만 받아 m4a로 저장 (영상 세그먼트는 받지 않음)
+    audio_only_download: bool,
+    /// 선택한 화질의 첫 세그먼트에 접근할 수 없으면(403/404 등) 한 단계씩 낮은 화질로 받기
+    allow_fallback: bool,
+    /// 병합/리먹싱 없이 받은 세그먼트를 그대로 두고 이를 가리키는 로컬 `.m3u8` 작성
+    local_playlist: bool,
+    /// 대기열에서의 순번 (1부터). batch_total과 함께 주면 파일 이름 템플릿의 `{index}`에 사용
+    batch_index: Option<usize>,
+    /// 대기열 전체 개수 (순번을 몇 자리로 채울지 결정)
+    batch_total: Option<usize>,
+}
+
+/// 다운로드 완료 요약 (`download-complete` 이벤트, "complete" 진행 이벤트 직후 전송)
+#[derive(Serialize, Clone)]
+struct DownloadSummary {
+    /// 결과 파일 경로 (분할 저장이면 첫 파일)
+    output_path: String,
+    /// 결과 파일 크기 합계 (바이트)
+    size_bytes: u64,
+    elapsed_secs: f64,
+    message: String,
+}
+
+/// "complete" 진행 이벤트와 완료 요약 이벤트 전송
+///
+/// 진행 이벤트 메시지에도 파일 이름, 크기, 걸린 시간을 덧붙인다.
+fn emit_complete(
+    app: &tauri::AppHandle,
+    title: &str,
+    message: String,
+    outputs: &[PathBuf],
+    started: std::time::Instant,
+) {
+    let size_bytes: u64 = outputs
+        .iter()
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum();
+    let elapsed_secs = started.elapsed().as_secs_f64();
+    let output_path = outputs.first().cloned().unwrap_or_default();
+    let file_name = output_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "complete".into(),
+            current: 1,
+            total: 1,
+            message: format!(
+                "{} - {} ({:.1}MB, {:.0}초)",
+                message,
+                file_name,
+                size_bytes as f64 / (1024.0 * 1024.0),
+                elapsed_secs
+            ),
+        },
+    );
+    let _ = app.emit(
+        "download-complete",
+        DownloadSummary {
+            output_path: output_path.to_string_lossy().to_string(),
+            size_bytes,
+            elapsed_secs,
+            message,
+        },
+    );
+    tauri::async_runtime::spawn(notify(
+        app.clone(),
+        "다운로드 완료".into(),
+        format!("{}\n{}", title, output_path.display()),
+    ));
+}
+
+/// 설정에서 켠 경우 OS 알림 표시 (처음 사용할 때 권한 요청)
+async fn notify(app: tauri::AppHandle, title: String, body: String) {
+    use tauri_plugin_notification::{NotificationExt, PermissionState};
+
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    if !settings.desktop_notifications {
+        return;
+    }
+    let notification = app.notification();
+    let granted = match notification.permission_state() {
+        Ok(PermissionState::Granted) => true,
+        _ => matches!(
+            notification.request_permission(),
+            Ok(PermissionState::Granted)
+        ),
+    };
+    if !granted {
+        eprintln!("⚠️ Notification permission not granted");
+        return;
+    }
+    if let Err(e) = notification.builder().title(title).body(body).show() {
+        eprintln!("⚠️ Notification failed: {}", e);
+    }
+}
+
+/// 일괄 클립 다운로드의 항목별 진행 이벤트 (`clip-batch-progress`)
+#[derive(Serialize, Clone)]
+struct ClipBatchProgress {
+    index: usize,
+    total: usize,
+    clip_uid: String,
+    /// "started" / "done" / "failed"
+    status: String,
+    message: String,
+}
+
+/// 일괄 클립 다운로드의 항목별 결과
+#[derive(Serialize)]
+struct ClipBatchResult {
+    clip_uid: String,
+    output_path: Option<String>,
+    error: Option<String>,
+}
+
+/// remux_existing의 선택 옵션
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RemuxOptions {
+    transcode: Option<TranscodeOptions>,
+    scale: Option<ScaleOptions>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -47,7 +253,7 @@ struct Credentials {
     nid_ses: String,
 }
 
-fn get_credentials_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+fn get_app_data_file(app: &tauri::AppHandle, file_name: &str) -> Result<PathBuf, String> {
     let app_dir = app
         .path()
         .app_data_dir()
@@ -56,7 +262,312 @@ fn get_credentials_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     fs::create_dir_all(&app_dir)
         .map_err(|e| format!("디렉토리 생성 실패: {}", e))?;
 
-    Ok(app_dir.join("credentials.json"))
+    Ok(app_dir.join(file_name))
+}
+
+fn get_credentials_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    get_app_data_file(app, "credentials.json")
+}
+
+/// 앱 설정 (settings.json)
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct AppSettings {
+    /// 세그먼트 하나의 요청 제한 시간 (초)
+    segment_timeout_secs: u64,
+    /// 이 시간 동안 진행이 없으면 다운로드 중단 (초)
+    stall_timeout_secs: u64,
+    /// HLS와 DASH가 모두 가능할 때 사용할 형식 (auto / hls / dash)
+    prefer_format: PreferFormat,
+    /// 접속 IP 버전 (auto / ipv4 / ipv6)
+    ip_mode: IpMode,
+    /// DNS 대신 사용할 호스트 → IP (예: "api.chzzk.naver.com": "1.2.3.4")
+    dns_overrides: HashMap<String, String>,
+    /// 영상 옆에 Kodi/Jellyfin용 .nfo 메타데이터 파일도 저장
+    write_nfo: bool,
+    /// 결과 파일에 적용할 권한 (8진수 문자열, 예: "644"). Unix에서만 적용, 없으면 umask 그대로
+    file_mode: Option<String>,
+    /// 동시에 받을 VOD 수 (다시 시작하면 적용)
+    max_concurrent_vods: usize,
+    /// 모든 다운로드를 합친 동시 세그먼트 요청 수 (다시 시작하면 적용)
+    max_segment_requests: usize,
+    /// 설치된 aria2c로 세그먼트 받기 (없으면 내장 다운로더 사용)
+    use_aria2c: bool,
+    /// 앱 전체 동시 네트워크 요청 수 (다시 시작하면 적용)
+    max_requests: usize,
+    /// 원격 제어용 HTTP API 실행 (다시 시작하면 적용)
+    remote_server: bool,
+    /// 원격 제어 서버가 받을 주소 (기본 127.0.0.1, LAN에서 쓰려면 0.0.0.0)
+    remote_bind: String,
+    remote_port: u16,
+    /// 원격 제어 인증 토큰 (localhost가 아닌 주소로 열 때는 필수)
+    remote_token: Option<String>,
+    /// 원격으로 추가한 다운로드의 저장 폴더 (요청의 output_dir는 이 폴더 안으로 제한)
+    remote_output_dir: Option<String>,
+    /// 결과 파일 이름 템플릿 (예: "{date}_{channel}_{title}"). 없으면 기본 형식
+    filename_template: Option<String>,
+    /// VOD 다운로드 시작/완료/실패 시 OS 알림 표시
+    desktop_notifications: bool,
+    /// 세그먼트 병합 시 쓰기 버퍼 크기 (MB)
+    merge_buffer_mb: usize,
+    /// 호스트별 유휴 연결 수 (없으면 제한 없음. 지연이 큰 회선은 동시 세그먼트 요청 수만큼 권장)
+    pool_max_idle_per_host: Option<usize>,
+    /// 유휴 연결 유지 시간 (초, 없으면 90초)
+    pool_idle_timeout_secs: Option<u64>,
+    /// TCP keepalive 간격 (초, 없으면 사용 안 함. 중간 장비가 연결을 끊는 환경이면 30~60초)
+    tcp_keepalive_secs: Option<u64>,
+    /// 모든 요청을 거칠 HTTP(S) 프록시 URL (지역 제한 콘텐츠를 받을 때)
+    proxy_url: Option<String>,
+    /// 진단용으로 API 응답 전체를 로그에 출력 (민감한 값은 가림)
+    verbose_logging: bool,
+    /// DASH baseURL이 여러 개면 가장 빨리 응답하는 CDN 복제본에서 받기
+    prefer_fastest_cdn: bool,
+    /// VOD 다운로드마다 저장 폴더에 `download_<video_id>.log` 진단 로그 작성
+    download_log: bool,
+    /// 성공한 다운로드의 로그도 남기기 (끄면 실패한 경우만 남음)
+    keep_download_log: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            segment_timeout_secs: 30,
+            stall_timeout_secs: 60,
+            prefer_format: PreferFormat::Auto,
+            ip_mode: IpMode::Auto,
+            dns_overrides: HashMap::new(),
+            write_nfo: false,
+            file_mode: None,
+            max_concurrent_vods: 2,
+            max_segment_requests: 20,
+            use_aria2c: false,
+            max_requests: downloader::DEFAULT_MAX_REQUESTS,
+            remote_server: false,
+            remote_bind: "127.0.0.1".into(),
+            remote_port: 8790,
+            remote_token: None,
+            remote_output_dir: None,
+            filename_template: None,
+            desktop_notifications: false,
+            merge_buffer_mb: downloader::DEFAULT_MERGE_BUFFER_MB,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_secs: None,
+            tcp_keepalive_secs: None,
+            proxy_url: None,
+            verbose_logging: false,
+            prefer_fastest_cdn: false,
+            download_log: false,
+            keep_download_log: false,
+        }
+    }
+}
+
+/// 파일/폴더 권한 변경 (Unix 전용, 실패는 로그만 남김)
+fn set_mode(path: &Path, mode: u32) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Err(e) = fs::set_permissions(path, fs::Permissions::from_mode(mode)) {
+            eprintln!("⚠️ Failed to set mode {:o} on {:?}: {}", mode, path, e);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+}
+
+impl AppSettings {
+    fn segment_config(&self, continue_on_error: bool) -> SegmentDownloadConfig {
+        SegmentDownloadConfig {
+            continue_on_error,
+            segment_timeout: std::time::Duration::from_secs(self.segment_timeout_secs.max(1)),
+            stall_timeout: std::time::Duration::from_secs(self.stall_timeout_secs.max(1)),
+            cancel: None,
+            status: None,
+            order: downloader::DownloadOrder::Unordered,
+            use_aria2c: self.use_aria2c,
+        }
+    }
+
+    fn remote_token(&self) -> Option<String> {
+        self.remote_token
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+    }
+
+    /// 원격 제어 서버 주소 (꺼져 있으면 None)
+    fn remote_addr(&self) -> Result<Option<std::net::SocketAddr>, String> {
+        if !self.remote_server {
+            return Ok(None);
+        }
+        let ip: std::net::IpAddr = self
+            .remote_bind
+            .trim()
+            .parse()
+            .map_err(|_| format!("잘못된 원격 제어 주소입니다: {}", self.remote_bind))?;
+        if !ip.is_loopback() && self.remote_token().is_none() {
+            return Err("localhost가 아닌 주소로 원격 제어를 열려면 토큰을 설정해주세요".into());
+        }
+        Ok(Some(std::net::SocketAddr::new(ip, self.remote_port)))
+    }
+
+    fn file_mode(&self) -> Result<Option<u32>, String> {
+        self.file_mode
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(|m| {
+                u32::from_str_radix(m, 8)
+                    .ok()
+                    .filter(|mode| *mode <= 0o7777)
+                    .ok_or_else(|| format!("잘못된 파일 권한입니다: {}", m))
+            })
+            .transpose()
+    }
+
+    /// 설정된 파일 권한을 결과 파일에 적용 (Unix 전용)
+    fn apply_file_mode(&self, path: &Path) {
+        if let Ok(Some(mode)) = self.file_mode() {
+            set_mode(path, mode);
+        }
+    }
+
+    /// 결과 폴더와 그 안의 파일에 설정된 권한 적용 (프레임 폴더, 로컬 재생 목록의 세그먼트 폴더)
+    ///
+    /// 폴더는 읽기 권한이 있는 곳에 실행(탐색) 권한을 더한다 (예: 644 → 755).
+    fn apply_dir_mode(&self, dir: &Path) {
+        let Ok(Some(mode)) = self.file_mode() else {
+            return;
+        };
+        set_mode(dir, mode | ((mode & 0o444) >> 2));
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_file() {
+                set_mode(&path, mode);
+            }
+        }
+    }
+
+    fn network_options(&self) -> Result<NetworkOptions, String> {
+        let dns_overrides = self
+            .dns_overrides
+            .iter()
+            .map(|(host, ip)| {
+                ip.trim()
+                    .parse()
+                    .map(|ip| (host.trim().to_string(), ip))
+                    .map_err(|_| format!("잘못된 IP 주소입니다 ({}): {}", host, ip))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        if self.tcp_keepalive_secs == Some(0) {
+            return Err("TCP keepalive 간격은 1초 이상이어야 합니다".into());
+        }
+        let proxy = match self.proxy_url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => Some(
+                reqwest::Proxy::all(url)
+                    .map_err(|e| format!("잘못된 프록시 주소입니다 ({}): {}", url, e))?,
+            ),
+            _ => None,
+        };
+
+        Ok(NetworkOptions {
+            ip_mode: self.ip_mode,
+            dns_overrides,
+            pool_max_idle_per_host: self.pool_max_idle_per_host,
+            pool_idle_timeout: self
+                .pool_idle_timeout_secs
+                .map(std::time::Duration::from_secs),
+            tcp_keepalive: self.tcp_keepalive_secs.map(std::time::Duration::from_secs),
+            proxy,
+        })
+    }
+}
+
+#[tauri::command]
+async fn load_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
+    let path = get_app_data_file(&app, "settings.json")?;
+
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("파일 읽기 실패: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("JSON 파싱 실패: {}", e))
+}
+
+#[tauri::command]
+async fn save_settings(app: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
+    let path = get_app_data_file(&app, "settings.json")?;
+    let network = settings.network_options()?;
+    settings.file_mode()?;
+    settings.remote_addr()?;
+
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("JSON 직렬화 실패: {}", e))?;
+
+    fs::write(&path, json)
+        .map_err(|e| format!("파일 쓰기 실패: {}", e))?;
+
+    downloader::set_network_options(network);
+    downloader::set_merge_buffer_mb(settings.merge_buffer_mb);
+    downloader::set_filename_template(settings.filename_template);
+    downloader::set_verbose_logging(settings.verbose_logging);
+    downloader::set_prefer_fastest_cdn(settings.prefer_fastest_cdn);
+
+    Ok(())
+}
+
+/// 다운로드 기록 한 건 (history.json)
+#[derive(Serialize, Deserialize, Clone)]
+struct HistoryEntry {
+    /// 같은 영상/구간/화질이면 같은 값 (파일명과 무관한 중복 판별 키)
+    key: String,
+    video_id: String,
+    output_path: String,
+    /// UNIX 시간 (초)
+    completed_at: u64,
+}
+
+fn download_key(video_id: &str, start_time: &str, end_time: &str, quality_id: Option<&str>) -> String {
+    format!(
+        "vod:{}:{}:{}:{}",
+        video_id,
+        start_time,
+        end_time,
+        quality_id.unwrap_or("")
+    )
+}
+
+fn load_history(app: &tauri::AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    let path = get_app_data_file(app, "history.json")?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("파일 읽기 실패: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("JSON 파싱 실패: {}", e))
+}
+
+fn append_history(app: &tauri::AppHandle, entry: HistoryEntry) -> Result<(), String> {
+    let mut history = load_history(app).unwrap_or_default();
+    history.retain(|h| h.key != entry.key);
+    history.push(entry);
+    save_history(app, &history)
+}
+
+fn save_history(app: &tauri::AppHandle, history: &[HistoryEntry]) -> Result<(), String> {
+    let json =
+        serde_json::to_string_pretty(history).map_err(|e| format!("JSON 직렬화 실패: {}", e))?;
+
+    fs::write(get_app_data_file(app, "history.json")?, json)
+        .map_err(|e| format!("파일 쓰기 실패: {}", e))
 }
 
 #[tauri::command]
@@ -94,6 +605,330 @@ async fn load_credentials(app: tauri::AppHandle) -> Result<Option<Credentials>,
     Ok(Some(creds))
 }
 
+/// 설정 내보내기 파일(zip)의 형식 버전
+const CONFIG_BUNDLE_VERSION: u32 = 1;
+const CONFIG_BUNDLE_MANIFEST: &str = "manifest.json";
+/// 앱 데이터 폴더에서 그대로 옮기는 파일 (쿠키는 따로 암호화)
+const CONFIG_BUNDLE_FILES: [&str; 2] = ["settings.json", "history.json"];
+const CONFIG_BUNDLE_CREDENTIALS: &str = "credentials.json";
+
+#[derive(Serialize, Deserialize)]
+struct ConfigBundleManifest {
+    version: u32,
+    app_version: String,
+    has_credentials: bool,
+}
+
+/// `import_config` 결과
+#[derive(Serialize)]
+struct ConfigImportResult {
+    settings: AppSettings,
+    history_entries: usize,
+    credentials_restored: bool,
+}
+
+/// 설정/기록(과 선택적으로 로그인 쿠키)을 하나의 zip 파일로 내보내기
+///
+/// 쿠키는 `include_credentials`일 때만 포함하며, `password`로 AES-256 암호화한다.
+#[tauri::command]
+async fn export_config(
+    app: tauri::AppHandle,
+    path: String,
+    include_credentials: bool,
+    password: Option<String>,
+) -> Result<(), String> {
+    use std::io::Write;
+
+    let password = password.filter(|p| !p.is_empty());
+    if include_credentials && password.is_none() {
+        return Err("로그인 정보를 포함하려면 암호를 입력해주세요".into());
+    }
+    let creds = if include_credentials {
+        Some(
+            load_credentials(app.clone())
+                .await?
+                .ok_or("저장된 로그인 정보가 없습니다")?,
+        )
+    } else {
+        None
+    };
+
+    let file = fs::File::create(&path).map_err(|e| format!("파일 생성 실패: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+
+    let manifest = ConfigBundleManifest {
+        version: CONFIG_BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        has_credentials: creds.is_some(),
+    };
+    let manifest =
+        serde_json::to_vec_pretty(&manifest).map_err(|e| format!("JSON 직렬화 실패: {}", e))?;
+    zip.start_file(CONFIG_BUNDLE_MANIFEST, options)
+        .and_then(|_| Ok(zip.write_all(&manifest)?))
+        .map_err(|e| format!("ZIP 쓰기 실패: {}", e))?;
+
+    for name in CONFIG_BUNDLE_FILES {
+        let source = get_app_data_file(&app, name)?;
+        if !source.exists() {
+            continue;
+        }
+        let content = fs::read(&source).map_err(|e| format!("파일 읽기 실패: {}", e))?;
+        zip.start_file(name, options)
+            .and_then(|_| Ok(zip.write_all(&content)?))
+            .map_err(|e| format!("ZIP 쓰기 실패: {}", e))?;
+    }
+
+    if let (Some(creds), Some(password)) = (creds, password.as_deref()) {
+        let content =
+            serde_json::to_vec_pretty(&creds).map_err(|e| format!("JSON 직렬화 실패: {}", e))?;
+        let encrypted = options.with_aes_encryption(zip::AesMode::Aes256, password);
+        zip.start_file(CONFIG_BUNDLE_CREDENTIALS, encrypted)
+            .and_then(|_| Ok(zip.write_all(&content)?))
+            .map_err(|e| format!("ZIP 쓰기 실패: {}", e))?;
+    }
+
+    zip.finish().map_err(|e| format!("ZIP 쓰기 실패: {}", e))?;
+    eprintln!("📦 Config exported: {}", path);
+    Ok(())
+}
+
+fn write_app_data_json<T: Serialize>(
+    app: &tauri::AppHandle,
+    file_name: &str,
+    value: &T,
+) -> Result<(), String> {
+    let json =
+        serde_json::to_string_pretty(value).map_err(|e| format!("JSON 직렬화 실패: {}", e))?;
+
+    fs::write(get_app_data_file(app, file_name)?, json)
+        .map_err(|e| format!("파일 쓰기 실패: {}", e))
+}
+
+/// zip 항목을 문자열로 읽기 (없으면 None)
+fn read_bundle_entry(
+    archive: &mut zip::ZipArchive<fs::File>,
+    name: &str,
+    password: Option<&str>,
+) -> Result<Option<String>, String> {
+    use std::io::Read;
+
+    let entry = match password {
+        Some(password) => archive.by_name_decrypt(name, password.as_bytes()),
+        None => archive.by_name(name),
+    };
+    let mut entry = match entry {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(zip::result::ZipError::InvalidPassword) => {
+            return Err("암호가 올바르지 않습니다".into())
+        }
+        Err(e) => return Err(format!("ZIP 엔트리 읽기 실패: {}", e)),
+    };
+
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .map_err(|e| format!("ZIP 엔트리 읽기 실패: {}", e))?;
+    Ok(Some(content))
+}
+
+/// `export_config`로 만든 파일에서 설정/기록(과 로그인 쿠키)을 복원
+///
+/// 모든 항목을 먼저 검증한 뒤에 기존 파일을 덮어쓴다. 쿠키는 파일에
+/// 들어 있고 `password`를 준 경우에만 복원한다.
+#[tauri::command]
+async fn import_config(
+    app: tauri::AppHandle,
+    path: String,
+    password: Option<String>,
+) -> Result<ConfigImportResult, String> {
+    let file = fs::File::open(&path).map_err(|e| format!("파일 열기 실패: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("ZIP 파싱 실패: {}", e))?;
+
+    let manifest = read_bundle_entry(&mut archive, CONFIG_BUNDLE_MANIFEST, None)?
+        .ok_or("설정 내보내기 파일이 아닙니다")?;
+    let manifest: ConfigBundleManifest =
+        serde_json::from_str(&manifest).map_err(|e| format!("JSON 파싱 실패: {}", e))?;
+    if manifest.version != CONFIG_BUNDLE_VERSION {
+        return Err(format!(
+            "지원하지 않는 설정 파일 버전입니다: {} (지원: {})",
+            manifest.version, CONFIG_BUNDLE_VERSION
+        ));
+    }
+
+    let settings: Option<AppSettings> = read_bundle_entry(&mut archive, "settings.json", None)?
+        .map(|c| serde_json::from_str(&c))
+        .transpose()
+        .map_err(|e| format!("settings.json 파싱 실패: {}", e))?;
+    let network = settings
+        .as_ref()
+        .map(|s| {
+            s.file_mode()?;
+            s.network_options()
+        })
+        .transpose()?;
+    let history: Option<Vec<HistoryEntry>> = read_bundle_entry(&mut archive, "history.json", None)?
+        .map(|c| serde_json::from_str(&c))
+        .transpose()
+        .map_err(|e| format!("history.json 파싱 실패: {}", e))?;
+    let password = password.filter(|p| !p.is_empty());
+    let creds: Option<Credentials> = match password.as_deref() {
+        Some(password) if manifest.has_credentials => {
+            read_bundle_entry(&mut archive, CONFIG_BUNDLE_CREDENTIALS, Some(password))?
+                .map(|c| serde_json::from_str(&c))
+                .transpose()
+                .map_err(|e| format!("credentials.json 파싱 실패: {}", e))?
+        }
+        _ => None,
+    };
+
+    if let Some(settings) = &settings {
+        write_app_data_json(&app, "settings.json", settings)?;
+    }
+    if let Some(history) = &history {
+        write_app_data_json(&app, "history.json", history)?;
+    }
+    if let Some(creds) = &creds {
+        write_app_data_json(&app, CONFIG_BUNDLE_CREDENTIALS, creds)?;
+    }
+    if let Some(network) = network {
+        downloader::set_network_options(network);
+    }
+    if let Some(settings) = settings {
+        downloader::set_merge_buffer_mb(settings.merge_buffer_mb);
+        downloader::set_filename_template(settings.filename_template);
+        downloader::set_verbose_logging(settings.verbose_logging);
+        downloader::set_prefer_fastest_cdn(settings.prefer_fastest_cdn);
+    }
+
+    eprintln!("📦 Config imported: {}", path);
+    Ok(ConfigImportResult {
+        settings: load_settings(app).await?,
+        history_entries: history.map(|h| h.len()).unwrap_or(0),
+        credentials_restored: creds.is_some(),
+    })
+}
+
+/// Netscape 형식 cookies.txt 내용에서 NID_AUT/NID_SES 추출
+fn parse_netscape_cookies(content: &str) -> Result<Credentials, String> {
+    let mut nid_aut = None;
+    let mut nid_ses = None;
+    let mut valid_lines = 0;
+
+    for line in content.lines() {
+        // "#HttpOnly_" 접두사는 주석이 아니라 HttpOnly 쿠키 표시
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line).trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // domain, flag, path, secure, expiration, name, value
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 7 {
+            return Err(format!("잘못된 cookies.txt 형식입니다: {}", line));
+        }
+        valid_lines += 1;
+
+        if !fields[0].ends_with("naver.com") {
+            continue;
+        }
+        match fields[5] {
+            "NID_AUT" => nid_aut = Some(fields[6].to_string()),
+            "NID_SES" => nid_ses = Some(fields[6].to_string()),
+            _ => {}
+        }
+    }
+
+    if valid_lines == 0 {
+        return Err("cookies.txt에 쿠키가 없습니다".into());
+    }
+
+    match (nid_aut, nid_ses) {
+        (Some(nid_aut), Some(nid_ses)) => Ok(Credentials { nid_aut, nid_ses }),
+        (None, _) => Err("cookies.txt에서 NID_AUT 쿠키를 찾을 수 없습니다".into()),
+        (_, None) => Err("cookies.txt에서 NID_SES 쿠키를 찾을 수 없습니다".into()),
+    }
+}
+
+#[tauri::command]
+async fn import_cookies_file(app: tauri::AppHandle, path: String) -> Result<Credentials, String> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("파일 읽기 실패: {}", e))?;
+
+    let creds = parse_netscape_cookies(&content)?;
+    save_credentials(app, creds.nid_aut.clone(), creds.nid_ses.clone()).await?;
+
+    eprintln!("🍪 Imported NID_AUT/NID_SES from {}", path);
+    Ok(creds)
+}
+
+/// 사용자가 붙여넣은 쿠키 값에서 "NID_AUT=" 같은 이름 접두사와 따옴표, 구분자 제거
+fn clean_cookie_value(name: &str, value: &str) -> String {
+    let value = value.trim().trim_end_matches(';').trim();
+    let value = value
+        .strip_prefix(name)
+        .and_then(|v| v.trim_start().strip_prefix('='))
+        .unwrap_or(value);
+    value.trim().trim_matches('"').to_string()
+}
+
+/// "Cookie:" 헤더 문자열에서 NID_AUT/NID_SES 추출
+fn parse_cookie_header(header: &str) -> (Option<String>, Option<String>) {
+    let header = header.trim();
+    let header = header
+        .strip_prefix("Cookie:")
+        .or_else(|| header.strip_prefix("cookie:"))
+        .unwrap_or(header);
+
+    let mut nid_aut = None;
+    let mut nid_ses = None;
+    for pair in header.split(';') {
+        if let Some((name, value)) = pair.split_once('=') {
+            match name.trim() {
+                "NID_AUT" => nid_aut = Some(clean_cookie_value("NID_AUT", value)),
+                "NID_SES" => nid_ses = Some(clean_cookie_value("NID_SES", value)),
+                _ => {}
+            }
+        }
+    }
+    (nid_aut, nid_ses)
+}
+
+/// 수동 입력한 쿠키를 파싱하고 실제 로그인 상태인지 확인한 뒤에만 저장
+#[tauri::command]
+async fn verify_and_save_cookies(
+    app: tauri::AppHandle,
+    cookie_header: Option<String>,
+    nid_aut: Option<String>,
+    nid_ses: Option<String>,
+) -> Result<bool, String> {
+    let (header_aut, header_ses) = cookie_header
+        .as_deref()
+        .map(parse_cookie_header)
+        .unwrap_or((None, None));
+
+    let nid_aut = nid_aut
+        .map(|v| clean_cookie_value("NID_AUT", &v))
+        .filter(|v| !v.is_empty())
+        .or(header_aut)
+        .ok_or("NID_AUT 값이 없습니다")?;
+    let nid_ses = nid_ses
+        .map(|v| clean_cookie_value("NID_SES", &v))
+        .filter(|v| !v.is_empty())
+        .or(header_ses)
+        .ok_or("NID_SES 값이 없습니다")?;
+
+    if !check_login(&nid_aut, &nid_ses).await? {
+        eprintln!("⚠️ Pasted cookies are not logged in - not saving");
+        return Ok(false);
+    }
+
+    save_credentials(app, nid_aut, nid_ses).await?;
+    eprintln!("✅ Pasted cookies verified and saved");
+    Ok(true)
+}
+
 #[tauri::command]
 async fn open_login_webview(app: tauri::AppHandle) -> Result<String, String> {
     use tauri::{WebviewWindowBuilder, WebviewUrl};
@@ -134,7 +969,7 @@ async fn open_login_webview(app: tauri::AppHandle) -> Result<String, String> {
 
                 std::thread::spawn(move || {
                     // 쿠키가 완전히 저장될 때까지 잠시 대기
-                    std::thread::sleep(std::time::Duration::from_millis(500));
+                    std::thread::sleep(COOKIE_SETTLE_DELAY);
                     // AppHandle에서 웹뷰 참조 획득
                     if let Some(wv) = app.get_webview_window("naver-login") {
                         extract_and_save_cookies(wv, app.clone());
@@ -153,83 +988,152 @@ async fn open_login_webview(app: tauri::AppHandle) -> Result<String, String> {
     Ok("login_webview_opened".to_string())
 }
 
-/// WebView2 CookieManager를 통해 NID_AUT/NID_SES 쿠키를 추출하고 저장
+/// 로그인 리다이렉트 후 쿠키가 저장될 때까지 기다리는 시간
 #[cfg(target_os = "windows")]
-fn extract_and_save_cookies(webview: tauri::WebviewWindow, app: tauri::AppHandle) {
-    use std::sync::mpsc;
+const COOKIE_SETTLE_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
 
-    let (tx, rx) = mpsc::sync_channel::<Vec<(String, String)>>(1);
-
-    let result = webview.with_webview(move |platform_webview| {
-        unsafe {
-            use webview2_com::Microsoft::Web::WebView2::Win32::*;
-            use windows_core::Interface;
+/// GetCookies 콜백을 기다리는 최대 시간
+#[cfg(target_os = "windows")]
+const COOKIE_EXTRACT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
-            let controller = platform_webview.controller();
-            let core: ICoreWebView2 = controller.CoreWebView2().unwrap();
-            let core2: ICoreWebView2_2 = core.cast().unwrap();
-            let cookie_manager = core2.CookieManager().unwrap();
+/// GetCookies 호출 시도 횟수 (실패 시 한 번 재시도)
+#[cfg(target_os = "windows")]
+const COOKIE_EXTRACT_ATTEMPTS: u32 = 2;
 
-            let handler: ICoreWebView2GetCookiesCompletedHandler =
-                CookieCompletedHandler { sender: tx }.into();
+/// 백그라운드 쿠키 갱신 창을 열어 두는 최대 시간 (페이지가 끝내 안 열려도 창을 닫기 위해)
+#[cfg(target_os = "windows")]
+const COOKIE_REFRESH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
 
-            cookie_manager
-                .GetCookies(
-                    windows_core::w!("https://chzzk.naver.com"),
-                    &handler,
-                )
-                .unwrap();
-        }
-    });
+/// 쿠키 갱신 창을 연 횟수 (시간 초과 타이머가 다음 갱신 창을 닫지 않도록 구분)
+#[cfg(target_os = "windows")]
+static COOKIE_REFRESH_GENERATION: std::sync::atomic::AtomicU64 =
+    std::sync::atomic::AtomicU64::new(0);
 
-    if let Err(e) = result {
-        eprintln!("❌ with_webview failed: {:?}", e);
-        return;
+/// 쿠키 추출 실패를 `login-failed`로 알리고, 숨겨진 쿠키 갱신 창이면 닫기
+///
+/// 로그인 창은 사용자가 다시 시도할 수 있게 그대로 둔다.
+#[cfg(target_os = "windows")]
+fn report_cookie_failure(webview: &tauri::WebviewWindow, app: &tauri::AppHandle, message: String) {
+    let _ = app.emit("login-failed", message);
+    if webview.label() == "cookie-refresh" {
+        let _ = webview.close();
     }
+}
+
+/// WebView2 CookieManager에서 chzzk 쿠키 목록을 한 번 가져오기
+#[cfg(target_os = "windows")]
+fn request_cookies(webview: &tauri::WebviewWindow) -> Result<Vec<(String, String)>, String> {
+    use std::sync::mpsc;
 
-    match rx.recv_timeout(std::time::Duration::from_secs(10)) {
-        Ok(cookies) => {
-            let mut nid_aut = String::new();
-            let mut nid_ses = String::new();
+    let (tx, rx) = mpsc::sync_channel::<Result<Vec<(String, String)>, String>>(2);
 
-            for (name, value) in &cookies {
-                let preview = if value.len() > 20 {
-                    format!("{}...", &value[..20])
-                } else {
-                    value.clone()
-                };
-                eprintln!("🍪 Cookie: {}={}", name, preview);
+    webview
+        .with_webview(move |platform_webview| {
+            unsafe {
+                use webview2_com::Microsoft::Web::WebView2::Win32::*;
+                use windows_core::Interface;
 
-                if name == "NID_AUT" {
-                    nid_aut = value.clone();
-                }
-                if name == "NID_SES" {
-                    nid_ses = value.clone();
+                let controller = platform_webview.controller();
+                let setup = (|| -> windows_core::Result<()> {
+                    let core: ICoreWebView2 = controller.CoreWebView2()?;
+                    let core2: ICoreWebView2_2 = core.cast()?;
+                    let cookie_manager = core2.CookieManager()?;
+
+                    let handler: ICoreWebView2GetCookiesCompletedHandler =
+                        CookieCompletedHandler { sender: tx.clone() }.into();
+
+                    cookie_manager.GetCookies(
+                        windows_core::w!("https://chzzk.naver.com"),
+                        &handler,
+                    )
+                })();
+
+                if let Err(e) = setup {
+                    let _ = tx.try_send(Err(format!("WebView2 COM 오류: {}", e)));
                 }
             }
+        })
+        .map_err(|e| format!("with_webview 실패: {:?}", e))?;
 
-            if !nid_aut.is_empty() && !nid_ses.is_empty() {
-                eprintln!("✅ Successfully extracted NID_AUT and NID_SES");
-                tauri::async_runtime::spawn(async move {
-                    let _ =
-                        save_credentials(app.clone(), nid_aut.clone(), nid_ses.clone()).await;
-                    let _ = app.emit("login-success", Credentials { nid_aut, nid_ses });
-                    // 로그인 웹뷰 자동 닫기
-                    if let Some(wv) = app.get_webview_window("naver-login") {
-                        let _ = wv.close();
-                    }
-                });
-            } else {
+    rx.recv_timeout(COOKIE_EXTRACT_TIMEOUT)
+        .map_err(|_| "쿠키 추출 시간이 초과되었습니다".to_string())?
+}
+
+/// WebView2 CookieManager를 통해 NID_AUT/NID_SES 쿠키를 추출하고 저장
+///
+/// 실패하면 `login-failed` 이벤트로 사유를 알린다.
+#[cfg(target_os = "windows")]
+fn extract_and_save_cookies(webview: tauri::WebviewWindow, app: tauri::AppHandle) {
+    let mut last_error = String::new();
+    let mut cookies = None;
+
+    for attempt in 1..=COOKIE_EXTRACT_ATTEMPTS {
+        // 창이 이미 닫혔으면 (close_login_webview 등) 취소된 것으로 처리
+        if app.get_webview_window(webview.label()).is_none() {
+            eprintln!("🚫 Cookie extraction cancelled - webview closed");
+            let _ = app.emit("login-failed", "로그인이 취소되었습니다".to_string());
+            return;
+        }
+
+        match request_cookies(&webview) {
+            Ok(list) => {
+                cookies = Some(list);
+                break;
+            }
+            Err(e) => {
                 eprintln!(
-                    "⚠️ NID_AUT or NID_SES not found ({} cookies total)",
-                    cookies.len()
+                    "❌ Cookie extraction failed (attempt {}/{}): {}",
+                    attempt, COOKIE_EXTRACT_ATTEMPTS, e
                 );
+                last_error = e;
+                std::thread::sleep(COOKIE_SETTLE_DELAY);
             }
         }
-        Err(e) => {
-            eprintln!("❌ Cookie extraction timeout: {}", e);
+    }
+
+    let Some(cookies) = cookies else {
+        report_cookie_failure(&webview, &app, last_error);
+        return;
+    };
+
+    let mut nid_aut = String::new();
+    let mut nid_ses = String::new();
+
+    for (name, value) in &cookies {
+        let preview = if value.len() > 20 {
+            format!("{}...", &value[..20])
+        } else {
+            value.clone()
+        };
+        eprintln!("🍪 Cookie: {}={}", name, preview);
+
+        if name == "NID_AUT" {
+            nid_aut = value.clone();
+        }
+        if name == "NID_SES" {
+            nid_ses = value.clone();
         }
     }
+
+    if !nid_aut.is_empty() && !nid_ses.is_empty() {
+        eprintln!("✅ Successfully extracted NID_AUT and NID_SES");
+        tauri::async_runtime::spawn(async move {
+            let _ = save_credentials(app.clone(), nid_aut.clone(), nid_ses.clone()).await;
+            let _ = app.emit("login-success", Credentials { nid_aut, nid_ses });
+            // 쿠키를 추출한 웹뷰(로그인 창 또는 백그라운드 창) 자동 닫기
+            let _ = webview.close();
+        });
+    } else {
+        eprintln!(
+            "⚠️ NID_AUT or NID_SES not found ({} cookies total)",
+            cookies.len()
+        );
+        report_cookie_failure(
+            &webview,
+            &app,
+            "로그인 쿠키(NID_AUT/NID_SES)를 찾을 수 없습니다. 다시 로그인해주세요.".to_string(),
+        );
+    }
 }
 
 /// WebView2 GetCookies COM 콜백 핸들러
@@ -238,7 +1142,7 @@ fn extract_and_save_cookies(webview: tauri::WebviewWindow, app: tauri::AppHandle
     webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2GetCookiesCompletedHandler
 )]
 struct CookieCompletedHandler {
-    sender: std::sync::mpsc::SyncSender<Vec<(String, String)>>,
+    sender: std::sync::mpsc::SyncSender<Result<Vec<(String, String)>, String>>,
 }
 
 #[cfg(target_os = "windows")]
@@ -254,35 +1158,106 @@ impl
             webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2CookieList,
         >,
     ) -> windows_core::Result<()> {
+        if errorcode.is_err() {
+            let _ = self
+                .sender
+                .try_send(Err(format!("GetCookies 실패: {}", errorcode.message())));
+            return Ok(());
+        }
+
         let mut cookies = Vec::new();
-        if errorcode.is_ok() {
-            if let Ok(list) = result.ok() {
-                unsafe {
-                    let mut count: u32 = 0;
-                    list.Count(&mut count)?;
-                    for i in 0..count {
-                        let cookie = list.GetValueAtIndex(i)?;
-                        let mut name_ptr = windows_core::PWSTR::null();
-                        cookie.Name(&mut name_ptr)?;
-                        let name = name_ptr.to_string().unwrap_or_default();
-
-                        let mut value_ptr = windows_core::PWSTR::null();
-                        cookie.Value(&mut value_ptr)?;
-                        let value = value_ptr.to_string().unwrap_or_default();
-
-                        cookies.push((name, value));
-                    }
+        if let Ok(list) = result.ok() {
+            unsafe {
+                let mut count: u32 = 0;
+                list.Count(&mut count)?;
+                for i in 0..count {
+                    let cookie = list.GetValueAtIndex(i)?;
+                    let mut name_ptr = windows_core::PWSTR::null();
+                    cookie.Name(&mut name_ptr)?;
+                    let name = name_ptr.to_string().unwrap_or_default();
+
+                    let mut value_ptr = windows_core::PWSTR::null();
+                    cookie.Value(&mut value_ptr)?;
+                    let value = value_ptr.to_string().unwrap_or_default();
+
+                    cookies.push((name, value));
                 }
             }
         }
-        let _ = self.sender.send(cookies);
+        let _ = self.sender.try_send(Ok(cookies));
         Ok(())
     }
 }
 
+/// 로그인 창 없이 숨겨진 웹뷰로 chzzk에 접속해 저장된 세션 쿠키를 다시 추출
 #[tauri::command]
-async fn close_login_webview(app: tauri::AppHandle) -> Result<(), String> {
-    if let Some(webview) = app.get_webview_window("naver-login") {
+async fn refresh_credentials(app: tauri::AppHandle) -> Result<String, String> {
+    #[cfg(target_os = "windows")]
+    {
+        use tauri::webview::PageLoadEvent;
+        use tauri::{WebviewUrl, WebviewWindowBuilder};
+
+        if app.get_webview_window("cookie-refresh").is_some() {
+            return Ok("cookie_refresh_in_progress".to_string());
+        }
+
+        let extracted = Arc::new(AtomicBool::new(false));
+
+        WebviewWindowBuilder::new(
+            &app,
+            "cookie-refresh",
+            WebviewUrl::External("https://chzzk.naver.com/".parse().unwrap()),
+        )
+        .title("쿠키 갱신")
+        .visible(false)
+        .on_page_load(move |wv, payload| {
+            // 페이지 로드가 끝나면 한 번만 쿠키 추출
+            if payload.event() == PageLoadEvent::Finished
+                && !extracted.swap(true, Ordering::SeqCst)
+            {
+                eprintln!("🔄 Background webview loaded - extracting cookies...");
+                let app = wv.app_handle().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(COOKIE_SETTLE_DELAY);
+                    extract_and_save_cookies(wv, app);
+                });
+            }
+        })
+        .build()
+        .map_err(|e| format!("백그라운드 웹뷰 생성 실패: {}", e))?;
+
+        // 페이지 로드나 쿠키 추출이 끝나지 않아도 창이 남아 갱신이 막히지 않게 닫기
+        let generation = COOKIE_REFRESH_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        let timeout_app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(COOKIE_REFRESH_TIMEOUT).await;
+            if COOKIE_REFRESH_GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            if let Some(webview) = timeout_app.get_webview_window("cookie-refresh") {
+                eprintln!("⏱️ Cookie refresh timed out - closing background webview");
+                report_cookie_failure(
+                    &webview,
+                    &timeout_app,
+                    "쿠키 갱신 시간이 초과되었습니다".to_string(),
+                );
+            }
+        });
+
+        eprintln!("🔄 Cookie refresh webview opened");
+        Ok("cookie_refresh_started".to_string())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = app;
+        Err("쿠키 자동 갱신은 Windows에서만 지원됩니다".into())
+    }
+}
+
+#[tauri::command]
+async fn close_login_webview(app: tauri::AppHandle) -> Result<(), String> {
+    if let Some(webview) = app.get_webview_window("naver-login") {
         webview
             .close()
             .map_err(|e| format!("창 닫기 실패: {}", e))?;
@@ -293,7 +1268,15 @@ async fn close_login_webview(app: tauri::AppHandle) -> Result<(), String> {
 
 #[tauri::command]
 async fn check_ffmpeg(app: tauri::AppHandle) -> Result<bool, String> {
-    Ok(find_ffmpeg(&app).await.is_some())
+    let ffmpeg_path = find_ffmpeg(&app).await;
+    if let Some(path) = &ffmpeg_path {
+        eprintln!(
+            "🎬 Using {} ffmpeg: {}",
+            downloader::ffmpeg_source(path),
+            path.display()
+        );
+    }
+    Ok(ffmpeg_path.is_some())
 }
 
 #[tauri::command]
@@ -302,12 +1285,255 @@ async fn install_ffmpeg(app: tauri::AppHandle) -> Result<String, String> {
     Ok(path.to_string_lossy().to_string())
 }
 
+/// get_throughput 응답
+#[derive(Serialize)]
+struct Throughput {
+    /// 진행 중인 모든 다운로드를 합친 현재 속도 (바이트/초, 최근 5초 평균)
+    bytes_per_sec: f64,
+    /// 앱을 켠 뒤 받은 전체 바이트 수
+    total_bytes: u64,
+}
+
+/// 앱 전체 다운로드 속도 (다운로드별 진행률과 별개로 속도계 표시용)
+#[tauri::command]
+async fn get_throughput() -> Result<Throughput, String> {
+    Ok(Throughput {
+        bytes_per_sec: downloader::current_throughput(),
+        total_bytes: downloader::total_downloaded_bytes(),
+    })
+}
+
+/// 진행 중인 VOD 다운로드의 현재 상태 (UI/CLI 폴링용, 진행 중이 아니면 None)
+#[tauri::command]
+async fn get_download_status(
+    state: tauri::State<'_, CancelState>,
+    video_id: String,
+) -> Result<Option<DownloadStatus>, String> {
+    Ok(state.download_status(&video_id))
+}
+
+/// 진행 중/대기 중인 모든 다운로드 취소 (실행 중인 ffmpeg도 종료)
+#[tauri::command]
+async fn cancel_all_downloads(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, CancelState>,
+) -> Result<usize, String> {
+    let cancelled = state.cancel_all_downloads();
+    eprintln!("🚫 Cancelling {} download(s)", cancelled.len());
+    for id in &cancelled {
+        let _ = app.emit("download-cancelled", id);
+    }
+    Ok(cancelled.len())
+}
+
+/// cancel_and_delete 응답
+#[derive(Debug, Serialize)]
+struct CancelAndDeleteResult {
+    /// 진행 중이던 다운로드를 취소했는지
+    cancelled: bool,
+    /// 삭제한 파일/폴더 경로
+    deleted: Vec<String>,
+}
+
+/// 다운로드 하나를 취소하고 임시 폴더와 만들다 만 출력 파일까지 삭제
+#[tauri::command]
+async fn cancel_and_delete(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, CancelState>,
+    video_id: String,
+    output_dir: String,
+) -> Result<CancelAndDeleteResult, String> {
+    let outputs = state.cancel_download(&video_id);
+    let cancelled = outputs.is_some();
+    if cancelled {
+        eprintln!("🚫 Cancelling download {}", video_id);
+        let _ = app.emit("download-cancelled", &video_id);
+        // 세그먼트 쓰기/ffmpeg가 멈춘 뒤에 지워야 파일이 다시 생기지 않는다
+        if !state
+            .wait_download_finished(&video_id, std::time::Duration::from_secs(10))
+            .await
+        {
+            return Err("다운로드가 아직 종료되지 않아 파일을 삭제하지 못했습니다".into());
+        }
+    }
+
+    let mut deleted = Vec::new();
+    for temp_dir in downloader::find_vod_temp_dirs(&output_dir, &video_id).await {
+        tokio::fs::remove_dir_all(&temp_dir)
+            .await
+            .map_err(|e| format!("임시 폴더 삭제 실패: {}", e))?;
+        deleted.push(temp_dir.to_string_lossy().to_string());
+    }
+    for path in outputs.unwrap_or_default() {
+        if tokio::fs::remove_file(&path).await.is_ok() {
+            deleted.push(path.to_string_lossy().to_string());
+        }
+    }
+    eprintln!("🗑️ Deleted {} path(s) for {}", deleted.len(), video_id);
+    Ok(CancelAndDeleteResult { cancelled, deleted })
+}
+
+#[tauri::command]
+async fn cancel_ffmpeg_install(state: tauri::State<'_, CancelState>) -> Result<(), String> {
+    state.ffmpeg_install.store(true, std::sync::atomic::Ordering::SeqCst);
+    eprintln!("🚫 ffmpeg install cancel requested");
+    Ok(())
+}
+
+/// 받은 파일과 부가 파일(.jpg/.nfo/.vtt/채팅 .json)을 지우고 다운로드 기록에서도 제거
+///
+/// 안전을 위해 다운로드 기록에 있는 결과 파일이나 원격 제어 저장 폴더 안의 파일만 지운다.
+/// 반환값은 지운 경로 목록 (결과 파일이 먼저).
+#[tauri::command]
+async fn delete_download(app: tauri::AppHandle, path: String) -> Result<Vec<String>, String> {
+    let target = PathBuf::from(&path);
+    if !target.is_file() {
+        return Err(format!("파일이 없습니다: {}", path));
+    }
+    let canonical = target
+        .canonicalize()
+        .map_err(|e| format!("경로 확인 실패: {}", e))?;
+
+    let mut history = load_history(&app).unwrap_or_default();
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    let recorded = history.iter().any(|h| {
+        PathBuf::from(&h.output_path)
+            .canonicalize()
+            .is_ok_and(|p| p == canonical)
+    });
+    let in_output_dir = settings
+        .remote_output_dir
+        .and_then(|dir| PathBuf::from(dir).canonicalize().ok())
+        .is_some_and(|dir| canonical.starts_with(dir));
+    if !recorded && !in_output_dir {
+        return Err("다운로드 기록에 없는 파일은 지울 수 없습니다".into());
+    }
+
+    // 로컬 재생 목록은 지우기 전에 읽어야 세그먼트 파일을 알 수 있다
+    let sidecars = downloader::sidecar_files(&target);
+    let mut deleted = Vec::new();
+    fs::remove_file(&target).map_err(|e| format!("파일 삭제 실패: {}", e))?;
+    deleted.push(path.clone());
+    for sidecar in sidecars {
+        match fs::remove_file(&sidecar) {
+            Ok(()) => deleted.push(sidecar.to_string_lossy().to_string()),
+            Err(e) => eprintln!("⚠️ Failed to delete {}: {}", sidecar.display(), e),
+        }
+    }
+    if target.extension().is_some_and(|e| e == "m3u8") {
+        // 재생 목록 밖의 파일이 남아 있으면 폴더는 그대로 둔다
+        let segment_dir = downloader::local_playlist_segment_dir(&target);
+        if fs::remove_dir(&segment_dir).is_ok() {
+            deleted.push(segment_dir.to_string_lossy().to_string());
+        }
+    }
+
+    let before = history.len();
+    history.retain(|h| {
+        PathBuf::from(&h.output_path)
+            .canonicalize()
+            .map_or(h.output_path != path, |p| p != canonical)
+    });
+    if history.len() != before {
+        save_history(&app, &history)?;
+    }
+    eprintln!("🗑️ Deleted {} ({} files)", path, deleted.len());
+    Ok(deleted)
+}
+
+/// 지원 요청 시 확인할 환경 정보를 한 번에 수집
+#[tauri::command]
+async fn diagnose(
+    app: tauri::AppHandle,
+    output_dir: Option<String>,
+) -> Result<DiagnosticReport, String> {
+    let ffmpeg_path = find_ffmpeg(&app).await;
+    let ffmpeg_version = match &ffmpeg_path {
+        Some(path) => downloader::ffmpeg_version(path).await,
+        None => None,
+    };
+
+    let creds = load_credentials(app.clone()).await.ok().flatten();
+    let credentials_valid = match &creds {
+        Some(c) => check_login(&c.nid_aut, &c.nid_ses).await.ok(),
+        None => None,
+    };
+
+    let api_error = downloader::check_api_reachable().await.err();
+
+    let app_data_dir = app.path().app_data_dir().ok();
+    let app_data_writable = match &app_data_dir {
+        Some(dir) => fs::create_dir_all(dir).is_ok() && downloader::is_dir_writable(dir).await,
+        None => false,
+    };
+
+    let output_dir = output_dir
+        .map(PathBuf::from)
+        .or_else(|| app.path().download_dir().ok());
+    let output_free_bytes = match &output_dir {
+        Some(dir) => downloader::available_disk_space(dir).await,
+        None => None,
+    };
+
+    Ok(DiagnosticReport {
+        ffmpeg_source: ffmpeg_path
+            .as_deref()
+            .map(|p| downloader::ffmpeg_source(p).to_string()),
+        ffmpeg_path: ffmpeg_path.map(|p| p.to_string_lossy().to_string()),
+        ffmpeg_version,
+        credentials_saved: creds.is_some(),
+        credentials_valid,
+        api_reachable: api_error.is_none(),
+        api_error,
+        app_data_dir: app_data_dir.map(|p| p.to_string_lossy().to_string()),
+        app_data_writable,
+        output_dir: output_dir.map(|p| p.to_string_lossy().to_string()),
+        output_free_bytes,
+    })
+}
+
+/// 앱 데이터 폴더와 그 안의 파일 경로 조회
+#[tauri::command]
+async fn get_app_paths(app: tauri::AppHandle) -> Result<AppPaths, String> {
+    let path_string = |p: PathBuf| p.to_string_lossy().to_string();
+    let app_local_data_dir = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| format!("앱 데이터 경로를 가져올 수 없습니다: {}", e))?;
+    let settings_path = get_app_data_file(&app, "settings.json")?;
+
+    Ok(AppPaths {
+        app_data_dir: settings_path
+            .parent()
+            .map(|p| path_string(p.to_path_buf()))
+            .unwrap_or_default(),
+        app_local_data_dir: path_string(app_local_data_dir),
+        app_log_dir: app.path().app_log_dir().ok().map(path_string),
+        ffmpeg_path: path_string(downloader::app_ffmpeg_path(&app)?),
+        credentials_path: path_string(get_credentials_path(&app)?),
+        settings_path: path_string(settings_path),
+        history_path: path_string(get_app_data_file(&app, "history.json")?),
+    })
+}
+
+/// API 응답 원문 조회 (이슈 첨부용, 쿠키 값은 가려짐)
+#[tauri::command]
+async fn debug_fetch_api(app: tauri::AppHandle, url: String) -> Result<String, String> {
+    let creds = load_credentials(app).await.ok().flatten();
+    let (nid_aut, nid_ses) = match creds {
+        Some(c) => (Some(c.nid_aut), Some(c.nid_ses)),
+        None => (None, None),
+    };
+    downloader::debug_fetch(&url, nid_aut, nid_ses).await
+}
+
 #[tauri::command]
 async fn download_clip_cmd(
     app: tauri::AppHandle,
     clip_uid: String,
     output_dir: String,
 ) -> Result<String, String> {
+    let started = std::time::Instant::now();
     let _ = app.emit(
         "download-progress",
         DownloadProgress {
@@ -330,68 +1556,218 @@ async fn download_clip_cmd(
         },
     );
 
-    let output_path = downloader::download_clip(&app, &clip_info, &output_dir).await?;
+    let output_path = downloader::download_clip(&app, &clip_info, &output_dir, None).await?;
+
+    emit_complete(
+        &app,
+        &clip_info.title,
+        "다운로드 완료!".into(),
+        &[PathBuf::from(&output_path)],
+        started,
+    );
+
+    Ok(output_path)
+}
+
+/// 여러 클립을 순서대로 다운로드하고, 실패한 클립은 건너뛰며 결과 요약 반환
+#[tauri::command]
+async fn download_clip_batch(
+    app: tauri::AppHandle,
+    clip_uids: Vec<String>,
+    output_dir: String,
+) -> Result<Vec<ClipBatchResult>, String> {
+    let total = clip_uids.len();
+    let mut results = Vec::with_capacity(total);
+
+    // 대기 중인 클립도 전체 취소 대상이 되도록 미리 등록
+    let cancel_state = app.state::<CancelState>();
+    let mut tokens: Vec<_> = clip_uids
+        .iter()
+        .map(|uid| cancel_state.register_download(uid))
+        .collect();
+
+    for (index, clip_uid) in clip_uids.into_iter().enumerate() {
+        let emit = |status: &str, message: String| {
+            let _ = app.emit(
+                "clip-batch-progress",
+                ClipBatchProgress {
+                    index,
+                    total,
+                    clip_uid: clip_uid.clone(),
+                    status: status.into(),
+                    message,
+                },
+            );
+        };
+
+        let token = tokens.remove(0);
+        if token.is_cancelled() {
+            emit("cancelled", downloader::CANCELLED_ERROR.into());
+            results.push(ClipBatchResult {
+                clip_uid,
+                output_path: None,
+                error: Some(downloader::CANCELLED_ERROR.into()),
+            });
+            continue;
+        }
+
+        emit("started", format!("클립 다운로드 중... ({}/{})", index + 1, total));
 
+        let result = match get_clip_info(&clip_uid).await {
+            Ok(info) => {
+                downloader::download_clip(&app, &info, &output_dir, Some((index + 1, total))).await
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(path) => {
+                emit("done", path.clone());
+                results.push(ClipBatchResult {
+                    clip_uid,
+                    output_path: Some(path),
+                    error: None,
+                });
+            }
+            Err(e) => {
+                eprintln!("⚠️ Clip {} failed: {}", clip_uid, e);
+                emit("failed", e.clone());
+                results.push(ClipBatchResult {
+                    clip_uid,
+                    output_path: None,
+                    error: Some(e),
+                });
+            }
+        }
+    }
+
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
     let _ = app.emit(
         "download-progress",
         DownloadProgress {
             stage: "complete".into(),
-            current: 1,
-            total: 1,
-            message: "다운로드 완료!".into(),
+            current: (total - failed) as u32,
+            total: total as u32,
+            message: format!("클립 {}개 중 {}개 다운로드 완료", total, total - failed),
         },
     );
 
-    Ok(output_path)
+    Ok(results)
 }
 
+/// 클립 정보 조회. `request_id`를 주면 cancel_info_fetch로 취소할 수 있다
 #[tauri::command]
-async fn fetch_clip_info(clip_uid: String) -> Result<ClipInfoResp, String> {
-    let info = get_clip_info(&clip_uid).await?;
+async fn fetch_clip_info(
+    state: tauri::State<'_, CancelState>,
+    clip_uid: String,
+    request_id: Option<String>,
+) -> Result<ClipInfoResp, String> {
+    let info = state
+        .with_info_fetch(request_id.as_deref(), get_clip_info(&clip_uid))
+        .await?;
+    let qualities = info
+        .variants
+        .into_iter()
+        .map(|v| {
+            let mbps = v.bandwidth as f64 / 1_000_000.0;
+            let label = if v.height > 0 {
+                format!("{}p ({:.1}Mbps)", v.height, mbps)
+            } else {
+                format!("{:.1}Mbps", mbps)
+            };
+            VideoQuality {
+                id: v.id,
+                width: v.width,
+                height: v.height,
+                bandwidth: v.bandwidth,
+                label,
+            }
+        })
+        .collect();
+
     Ok(ClipInfoResp {
         title: info.title,
         channel: info.channel,
         thumbnail: info.thumbnail,
+        qualities,
     })
 }
 
 #[tauri::command]
-async fn fetch_video_info(app: tauri::AppHandle, video_id: String) -> Result<VodInfo, String> {
-    // 저장된 쿠키 불러오기
+async fn list_channel_clips(
+    app: tauri::AppHandle,
+    channel_id: String,
+    page: Option<u32>,
+    size: Option<u32>,
+) -> Result<ClipPage, String> {
     let creds = load_credentials(app).await?;
+    let (nid_aut, nid_ses) = match creds {
+        Some(c) => (Some(c.nid_aut), Some(c.nid_ses)),
+        None => (None, None),
+    };
 
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert("Referer", "https://chzzk.naver.com/".parse().unwrap());
+    downloader::list_channel_clips(
+        &channel_id,
+        page.unwrap_or(0),
+        size.unwrap_or(30).clamp(1, 100),
+        nid_aut,
+        nid_ses,
+    )
+    .await
+}
 
-    // 쿠키가 있으면 추가
-    if let Some(c) = creds {
-        let cookie_value = format!("NID_AUT={}; NID_SES={}", c.nid_aut, c.nid_ses);
-        if let Ok(cookie) = cookie_value.parse() {
-            headers.insert("Cookie", cookie);
-            eprintln!("🔐 Using saved credentials for video info request");
-        }
-    }
+/// VOD 정보 조회. `request_id`를 주면 cancel_info_fetch로 취소할 수 있고,
+/// 같은 ID로 새로 조회하면 이전 조회는 취소된다.
+#[tauri::command]
+async fn fetch_video_info(
+    app: tauri::AppHandle,
+    video_id: String,
+    request_id: Option<String>,
+) -> Result<VodInfo, String> {
+    let cancel_state = app.state::<CancelState>();
+    cancel_state
+        .with_info_fetch(request_id.as_deref(), load_vod_info(app.clone(), video_id))
+        .await
+}
 
-    let client = reqwest::Client::builder()
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
-        .default_headers(headers)
-        .build()
-        .map_err(|e| format!("HTTP 클라이언트 생성 실패: {}", e))?;
+/// 진행 중인 fetch_video_info/fetch_clip_info 조회 취소
+#[tauri::command]
+async fn cancel_info_fetch(
+    state: tauri::State<'_, CancelState>,
+    request_id: String,
+) -> Result<bool, String> {
+    Ok(state.cancel_info_fetch(&request_id))
+}
 
-    let api_url = format!("https://api.chzzk.naver.com/service/v3/videos/{}", video_id);
-    let resp: serde_json::Value = client
-        .get(&api_url)
-        .send()
-        .await
-        .map_err(|e| format!("API 요청 실패: {}", e))?
-        .json()
+async fn load_vod_info(app: tauri::AppHandle, video_id: String) -> Result<VodInfo, String> {
+    let prefer = load_settings(app.clone())
         .await
-        .map_err(|e| format!("JSON 파싱 실패: {}", e))?;
+        .unwrap_or_default()
+        .prefer_format;
+
+    // 저장된 쿠키 불러오기
+    let creds = load_credentials(app).await?;
+    let has_credentials = creds.is_some();
+
+    // 쿠키가 있으면 추가 (연결 설정의 프록시 등도 다운로드와 같게 적용)
+    let (nid_aut, nid_ses) = match creds {
+        Some(c) => {
+            eprintln!("🔐 Using saved credentials for video info request");
+            (Some(c.nid_aut), Some(c.nid_ses))
+        }
+        None => (None, None),
+    };
+    let client = downloader::build_client_with_cookies(nid_aut, nid_ses);
 
-    let content = resp.get("content").ok_or("API 응답에 content가 없습니다")?;
+    // 다운로드와 같은 순서(v3 → v2 → 시청 페이지)로 content 조회
+    let content = downloader::fetch_video_content(&client, &video_id).await?;
+    let content = &content;
 
-    // 디버깅: VOD API 응답 출력
-    eprintln!("📹 VOD API response content: {}", serde_json::to_string_pretty(content).unwrap_or_default());
+    // 디버깅: VOD API 응답 출력 (진단 로그를 켠 경우만)
+    downloader::dump_api_response("📹 VOD API response content", content);
+
+    // 다운로드 시와 같은 기준(prefer_format)으로 HLS/DASH 결정
+    let parsed_info = downloader::parse_video_content(content, prefer).ok();
 
     let title = content
         .get("videoTitle")
@@ -404,161 +1780,57 @@ async fn fetch_video_info(app: tauri::AppHandle, video_id: String) -> Result<Vod
         .and_then(|v| v.as_str())
         .unwrap_or("channel")
         .to_string();
-    let duration = content.get("duration").and_then(|v| v.as_u64()).unwrap_or(0);
+    let mut duration = content
+        .get("duration")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    // 방금 끝난 VOD는 duration이 0으로 올 수 있어 재생 정보에서 계산한 길이를 쓴다
+    if duration == 0 {
+        if let Some(info) = &parsed_info {
+            let mut info = info.clone();
+            downloader::fill_missing_duration(&mut info).await;
+            duration = info.duration;
+        }
+    }
     let thumbnail = content
         .get("thumbnailImageUrl")
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
 
-    // 화질 정보 가져오기
-    let mut qualities = Vec::new();
-
-    // HLS 또는 DASH 화질 목록 가져오기
-    if let Some(media_json_str) = content.get("liveRewindPlaybackJson").and_then(|v| v.as_str()) {
-        // HLS 방식 - master playlist에서 화질 목록 추출
-        if let Ok(media_data) = serde_json::from_str::<serde_json::Value>(media_json_str) {
-            if let Some(master_url) = media_data
-                .get("media")
-                .and_then(|m| m.as_array())
-                .and_then(|arr| arr.first())
-                .and_then(|item| item.get("path"))
-                .and_then(|v| v.as_str())
-            {
-                // Master playlist 가져오기
-                if let Ok(master_resp) = client.get(master_url).send().await {
-                    if let Ok(master_text) = master_resp.text().await {
-                        // #EXT-X-STREAM-INF 라인 파싱
-                        let lines: Vec<&str> = master_text.lines().collect();
-                        let mut i = 0;
-                        while i < lines.len() {
-                            let line = lines[i];
-                            if line.starts_with("#EXT-X-STREAM-INF:") {
-                                // bandwidth와 resolution 추출
-                                let mut bandwidth = 0u64;
-                                let mut width = 0u32;
-                                let mut height = 0u32;
-
-                                // #EXT-X-STREAM-INF: 제거하고 파라미터만 추출
-                                let params_str = line.strip_prefix("#EXT-X-STREAM-INF:").unwrap_or("");
-
-                                // BANDWIDTH 추출 (정규식 대신 문자열 검색)
-                                if let Some(bw_start) = params_str.find("BANDWIDTH=") {
-                                    let bw_str = &params_str[bw_start + 10..];
-                                    if let Some(comma_pos) = bw_str.find(',') {
-                                        bandwidth = bw_str[..comma_pos].parse().unwrap_or(0);
-                                    } else {
-                                        bandwidth = bw_str.parse().unwrap_or(0);
-                                    }
-                                }
-
-                                // RESOLUTION 추출
-                                if let Some(res_start) = params_str.find("RESOLUTION=") {
-                                    let res_str = &params_str[res_start + 11..];
-                                    let res_end = res_str.find(',').unwrap_or(res_str.len());
-                                    let resolution = &res_str[..res_end];
-                                    let parts: Vec<&str> = resolution.split('x').collect();
-                                    if parts.len() == 2 {
-                                        width = parts[0].parse().unwrap_or(0);
-                                        height = parts[1].parse().unwrap_or(0);
-                                    }
-                                }
-
-                                // 다음 줄이 variant playlist URL
-                                if i + 1 < lines.len() {
-                                    let variant_url = lines[i + 1].trim();
-                                    if !variant_url.starts_with('#') && !variant_url.is_empty() {
-                                        let label = if height > 0 {
-                                            format!("{}p ({:.1}Mbps)", height, bandwidth as f64 / 1_000_000.0)
-                                        } else {
-                                            format!("{:.1}Mbps", bandwidth as f64 / 1_000_000.0)
-                                        };
-
-                                        qualities.push(VideoQuality {
-                                            id: variant_url.to_string(),
-                                            width,
-                                            height,
-                                            bandwidth,
-                                            label,
-                                        });
-                                    }
-                                }
-                            }
-                            i += 1;
-                        }
-                    }
-                }
-            }
-        }
-
-        // HLS 화질 목록 정렬 (bandwidth 기준 내림차순)
-        qualities.sort_by(|a, b| b.bandwidth.cmp(&a.bandwidth));
-    } else if content.get("liveRewindPlaybackJson").and_then(|v| v.as_str()).is_none() {
-        // DASH 방식 - 기존 로직
-        if let (Some(video_id_key), Some(in_key)) = (
-            content.get("videoId").and_then(|v| v.as_str()),
-            content.get("inKey").and_then(|v| v.as_str()),
-        ) {
-            let playback_url = format!(
-                "https://apis.naver.com/neonplayer/vodplay/v2/playback/{}?key={}",
-                video_id_key, in_key
-            );
+    // 화질 목록 (다운로드와 같은 파서/정렬 기준, 조회 실패 시 빈 목록)
+    let qualities: Vec<VideoQuality> = match &parsed_info {
+        Some(info) => downloader::fetch_quality_variants(&client, info)
+            .await
+            .unwrap_or_default(),
+        None => Vec::new(),
+    }
+    .into_iter()
+    .map(|v| VideoQuality {
+        label: v.label(),
+        id: v.id,
+        width: v.width,
+        height: v.height,
+        bandwidth: v.bandwidth,
+    })
+    .collect();
 
-            if let Ok(playback_resp) = client.get(&playback_url).send().await {
-                if let Ok(playback_json) = playback_resp.json::<serde_json::Value>().await {
-                    if let Some(first_period) = playback_json
-                        .get("period")
-                        .and_then(|p| p.as_array())
-                        .and_then(|arr| arr.first())
-                    {
-                        if let Some(hls_set) = first_period
-                            .get("adaptationSet")
-                            .and_then(|a| a.as_array())
-                            .and_then(|sets| {
-                                sets.iter().find(|s| {
-                                    s.get("mimeType").and_then(|m| m.as_str())
-                                        == Some("video/mp2t")
-                                })
-                            })
-                        {
-                            if let Some(representations) =
-                                hls_set.get("representation").and_then(|r| r.as_array())
-                            {
-                                for rep in representations {
-                                    let id = rep.get("id").and_then(|v| v.as_str()).unwrap_or("");
-                                    let width =
-                                        rep.get("width").and_then(|v| v.as_u64()).unwrap_or(0)
-                                            as u32;
-                                    let height =
-                                        rep.get("height").and_then(|v| v.as_u64()).unwrap_or(0)
-                                            as u32;
-                                    let bandwidth =
-                                        rep.get("bandwidth").and_then(|v| v.as_u64()).unwrap_or(0);
-
-                                    let label = if width > 0 && height > 0 {
-                                        format!("{}p ({:.1}Mbps)", height, bandwidth as f64 / 1_000_000.0)
-                                    } else {
-                                        format!("{:.1}Mbps", bandwidth as f64 / 1_000_000.0)
-                                    };
-
-                                    qualities.push(VideoQuality {
-                                        id: id.to_string(),
-                                        width,
-                                        height,
-                                        bandwidth,
-                                        label,
-                                    });
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    // 화질을 하나도 못 찾았으면 빈 목록 대신 원인을 알려줌
+    if qualities.is_empty() {
+        return Err(downloader::no_playback_reason(content, has_credentials));
     }
 
-    // bandwidth 기준 내림차순 정렬 (최고 화질이 먼저)
-    qualities.sort_by(|a, b| b.bandwidth.cmp(&a.bandwidth));
+    // 자막 트랙 목록 (없거나 조회 실패 시 빈 목록)
+    let subtitles = match &parsed_info {
+        Some(info) => downloader::list_subtitle_tracks(info)
+            .await
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let chapters = parsed_info
+        .as_ref()
+        .map(|info| info.chapters.clone())
+        .unwrap_or_default();
 
     Ok(VodInfo {
         title,
@@ -566,9 +1838,36 @@ async fn fetch_video_info(app: tauri::AppHandle, video_id: String) -> Result<Vod
         duration,
         thumbnail,
         qualities,
+        subtitles,
+        chapters,
+        authenticated: has_credentials,
+        requires_login: downloader::requires_login(content),
     })
 }
 
+/// 저장된 쿠키가 있으면 함께 사용해 비디오 정보 가져오기
+async fn load_video_info(app: tauri::AppHandle, video_id: &str) -> Result<VideoInfo, String> {
+    let prefer = load_settings(app.clone())
+        .await
+        .unwrap_or_default()
+        .prefer_format;
+    let creds = load_credentials(app).await?;
+    if let Some(c) = creds {
+        get_video_info_with_cookies(video_id, Some(c.nid_aut), Some(c.nid_ses), prefer).await
+    } else {
+        get_video_info(video_id, prefer).await
+    }
+}
+
+#[tauri::command]
+async fn fetch_thumbnail_sprites(
+    app: tauri::AppHandle,
+    video_id: String,
+) -> Result<Vec<ThumbnailSprite>, String> {
+    let info = load_video_info(app, &video_id).await?;
+    downloader::get_thumbnail_sprites(&info).await
+}
+
 #[tauri::command]
 async fn download_vod(
     app: tauri::AppHandle,
@@ -577,11 +1876,164 @@ async fn download_vod(
     end_time: String,
     output_dir: String,
     quality_id: Option<String>,
+    options: Option<VodDownloadOptions>,
 ) -> Result<String, String> {
-    // 0. ffmpeg 확인
-    let ffmpeg_path = find_ffmpeg(&app)
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    let log = if settings.download_log {
+        match downloader::DownloadLog::create(Path::new(&output_dir), &video_id) {
+            Ok(log) => Some(std::sync::Arc::new(log)),
+            Err(e) => {
+                eprintln!("⚠️ {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(log) = &log {
+        log.write(&format!(
+            "Download {} ({} ~ {}, quality {:?})",
+            video_id, start_time, end_time, quality_id
+        ));
+    }
+
+    let mut result = downloader::with_download_log(
+        log.clone(),
+        run_download_vod(
+            app.clone(),
+            video_id.clone(),
+            start_time,
+            end_time,
+            output_dir,
+            quality_id,
+            options,
+        ),
+    )
+    .await;
+
+    // 실패하면 로그를 남기고 오류에 경로를 덧붙임 (취소는 경로 없이 그대로)
+    if let Some(log) = &log {
+        match &mut result {
+            Ok(path) => {
+                log.write(&format!("Completed: {}", path));
+                if !settings.keep_download_log {
+                    log.remove();
+                }
+            }
+            Err(e) => {
+                log.write(&format!("Failed: {}", e));
+                if e != downloader::CANCELLED_ERROR {
+                    e.push_str(&format!(" (로그: {})", log.path().display()));
+                }
+            }
+        }
+    }
+    if let Err(e) = &result {
+        if e != downloader::CANCELLED_ERROR {
+            let body = format!("{}\n{}", video_id, e);
+            tauri::async_runtime::spawn(notify(app, "다운로드 실패".into(), body));
+        }
+    }
+    result
+}
+
+async fn run_download_vod(
+    app: tauri::AppHandle,
+    video_id: String,
+    start_time: String,
+    end_time: String,
+    output_dir: String,
+    quality_id: Option<String>,
+    options: Option<VodDownloadOptions>,
+) -> Result<String, String> {
+    let started = std::time::Instant::now();
+    let options = options.unwrap_or_default();
+
+    if options.no_remux && (options.transcode.is_some() || options.scale.is_some()) {
+        return Err("리먹싱 없이 받기와 재인코딩 옵션은 함께 사용할 수 없습니다".into());
+    }
+    if let Some(minutes) = options.split_minutes {
+        if minutes == 0 {
+            return Err("분할 길이는 1분 이상이어야 합니다".into());
+        }
+        if options.no_remux || options.transcode.is_some() || options.scale.is_some() {
+            return Err(
+                "분할 저장은 리먹싱 없이 받기/재인코딩 옵션과 함께 사용할 수 없습니다".into(),
+            );
+        }
+    }
+    if options.max_height == Some(0) {
+        return Err("최대 해상도는 1 이상이어야 합니다".into());
+    }
+    if options.separate_tracks
+        && (options.no_remux
+            || options.split_minutes.is_some()
+            || options.transcode.is_some()
+            || options.scale.is_some())
+    {
+        return Err(
+            "영상/오디오 분리 저장은 리먹싱 없이 받기/분할/재인코딩 옵션과 함께 사용할 수 없습니다"
+                .into(),
+        );
+    }
+
+    if options.audio_only_download
+        && (options.no_remux
+            || options.split_minutes.is_some()
+            || options.transcode.is_some()
+            || options.scale.is_some()
+            || options.separate_tracks)
+    {
+        return Err(
+            "오디오만 받기는 리먹싱 없이 받기/분할/재인코딩/분리 저장 옵션과 함께 사용할 수 없습니다"
+                .into(),
+        );
+    }
+    if options.local_playlist
+        && (options.no_remux
+            || options.split_minutes.is_some()
+            || options.transcode.is_some()
+            || options.scale.is_some()
+            || options.separate_tracks
+            || options.audio_only_download)
+    {
+        return Err(
+            "로컬 재생 목록 저장은 리먹싱 없이 받기/분할/재인코딩/분리 저장/오디오만 받기 옵션과 함께 사용할 수 없습니다"
+                .into(),
+        );
+    }
+
+    // 전체 취소와 상태 조회(get_download_status)를 위해 등록
+    let cancel_state = app.state::<CancelState>();
+    let token = cancel_state.register_download(&video_id);
+
+    // 동시에 받을 수 있는 VOD 수를 넘으면 차례가 올 때까지 대기
+    token.status.set_stage("queued");
+    let limits = app.state::<DownloadLimits>();
+    let _vod_permit = limits
+        .vods
+        .acquire()
         .await
-        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+        .map_err(|e| format!("다운로드 대기 실패: {}", e))?;
+    if token.is_cancelled() {
+        return Err(downloader::CANCELLED_ERROR.into());
+    }
+    token.status.set_stage("info");
+
+    // 0. ffmpeg 확인 (리먹싱 없이 받거나 로컬 재생 목록으로 저장할 때는 필요 없음)
+    let ffmpeg_path = if options.no_remux || options.local_playlist {
+        None
+    } else {
+        Some(
+            find_ffmpeg(&app)
+                .await
+                .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?,
+        )
+    };
+    // 재인코딩 설정이 잘못됐으면 세그먼트를 다 받은 뒤가 아니라 지금 실패
+    if let (Some(transcode), Some(ffmpeg_path)) = (&options.transcode, &ffmpeg_path) {
+        transcode.validate(ffmpeg_path).await?;
+    }
 
     // 1. 비디오 정보 가져오기
     let _ = app.emit(
@@ -594,13 +2046,7 @@ async fn download_vod(
         },
     );
 
-    // 저장된 쿠키 불러오기
-    let creds = load_credentials(app.clone()).await?;
-    let info = if let Some(c) = creds {
-        get_video_info_with_cookies(&video_id, Some(c.nid_aut), Some(c.nid_ses)).await?
-    } else {
-        get_video_info(&video_id).await?
-    };
+    let info = load_video_info(app.clone(), &video_id).await?;
     let _ = app.emit(
         "download-progress",
         DownloadProgress {
@@ -610,33 +2056,1504 @@ async fn download_vod(
             message: format!("{} - {}", info.channel, info.title),
         },
     );
-
-    // 2. 세그먼트 URL 파싱 (DASH 또는 HLS)
+    tauri::async_runtime::spawn(notify(
+        app.clone(),
+        "다운로드 시작".into(),
+        format!("{} - {}", info.channel, info.title),
+    ));
+
+    // 2. 화질 결정 (지정값 → 이어받기 매니페스트 → 최대 해상도 이하 최고 화질) 후 세그먼트 URL 파싱
+    // 임시 폴더는 구간/요청 화질별로 따로 (이전 형식 폴더는 같은 구간이면 그대로 이어받음)
+    // 오디오만 받을 때는 화질과 상관없이 따로 받는다
+    let quality_key = match (&quality_id, options.max_height) {
+        _ if options.audio_only_download => "audio".into(),
+        (Some(q), _) => q.clone(),
+        (None, Some(max_height)) => format!("max{}", max_height),
+        (None, None) => "best".into(),
+    };
+    let legacy_dir = PathBuf::from(&output_dir).join(format!("temp_{}", video_id));
+    let temp_dir = match ResumeManifest::load(&legacy_dir).await {
+        Some(m) if m.start_time == start_time && m.end_time == end_time => legacy_dir,
+        _ => downloader::vod_temp_dir(&output_dir, &video_id, &start_time, &end_time, &quality_key),
+    };
+    let manifest = ResumeManifest::load(&temp_dir).await;
+    let quality_id = match quality_id {
+        _ if options.audio_only_download => None,
+        Some(q) => Some(q),
+        None => match manifest.as_ref().and_then(|m| m.quality_id.clone()) {
+            Some(q) => Some(q),
+            None => match options.max_height {
+                Some(max_height) => downloader::capped_quality_id(&info, max_height).await?,
+                None => downloader::highest_quality_id(&info).await?,
+            },
+        },
+    };
     let quality_ref = quality_id.as_deref();
-    let segments = if info.is_dash {
-        let dash_video_id = info.dash_video_id.as_ref().ok_or("DASH videoId가 없습니다")?;
-        let dash_in_key = info.dash_in_key.as_ref().ok_or("DASH inKey가 없습니다")?;
-        downloader::parse_dash_segments(dash_video_id, dash_in_key, &start_time, &end_time, quality_ref).await?
+
+    // 이전 실행과 화질이 다르면 다른 화질의 세그먼트가 섞이지 않도록 처음부터 다시 받음
+    if let Some(previous) = &manifest {
+        if !previous.matches_quality(quality_ref, options.audio_only_download) {
+            eprintln!(
+                "⚠️ Resume quality mismatch ({:?} → {:?}), discarding {}",
+                previous.quality_id,
+                quality_ref,
+                temp_dir.display()
+            );
+            let _ = app.emit(
+                "download-progress",
+                DownloadProgress {
+                    stage: "info".into(),
+                    current: 0,
+                    total: 1,
+                    message: "이전 다운로드와 화질이 달라 처음부터 다시 받습니다".into(),
+                },
+            );
+            cleanup_temp(&temp_dir).await?;
+        }
+    }
+
+    let segments = if options.audio_only_download {
+        downloader::resolve_audio_segments(&info, &start_time, &end_time).await?
     } else {
-        parse_segments(&info.master_url, &start_time, &end_time, quality_ref).await?
+        resolve_segments(&info, &start_time, &end_time, quality_ref).await?
     };
 
     if segments.is_empty() {
         return Err("다운로드할 세그먼트가 없습니다".into());
     }
 
-    // 3. 세그먼트 다운로드
-    let temp_dir = PathBuf::from(&output_dir).join(format!("temp_{}", video_id));
-    download_segments(&app, &segments, &temp_dir).await?;
-
-    // 4. 세그먼트 병합
-    let combined_path = merge_segments(&app, segments.len(), &temp_dir).await?;
+    // 첫 세그먼트를 받을 수 없으면 (옵션을 켠 경우) 낮은 화질로 대체
+    let preflight = downloader::preflight_segment(&segments[0]).await;
+    let (quality_id, segments, temp_dir) = match preflight {
+        Ok(()) => (quality_id, segments, temp_dir),
+        Err(e) if options.allow_fallback && !options.audio_only_download => {
+            let Some((variant, segments)) =
+                downloader::fallback_quality(&info, &start_time, &end_time, quality_ref).await?
+            else {
+                return Err(e);
+            };
+            eprintln!(
+                "⚠️ Quality {:?} unavailable ({}), falling back to {} ({}p)",
+                quality_ref, e, variant.id, variant.height
+            );
+            let _ = app.emit(
+                "download-progress",
+                DownloadProgress {
+                    stage: "info".into(),
+                    current: 0,
+                    total: 1,
+                    message: format!(
+                        "선택한 화질을 받을 수 없어 {}p 화질로 받습니다",
+                        variant.height
+                    ),
+                },
+            );
+            // 대체 화질은 임시 폴더도 따로 써서 원래 화질의 세그먼트와 섞이지 않게 한다
+            let temp_dir = downloader::vod_temp_dir(
+                &output_dir,
+                &video_id,
+                &start_time,
+                &end_time,
+                &variant.id,
+            );
+            (Some(variant.id), segments, temp_dir)
+        }
+        Err(e) => return Err(e),
+    };
+    let quality_ref = quality_id.as_deref();
 
-    // 5. ffmpeg로 리먹싱
-    let output_path = build_output_filename(&info, &start_time, &end_time, &output_dir);
-    remux_with_ffmpeg(&app, &ffmpeg_path, &combined_path, &output_path).await?;
+    if options.no_remux && !downloader::is_ts_source(&info, &segments) {
+        return Err("이 VOD는 TS 스트림이 아니므로 리먹싱 없이 받을 수 없습니다".into());
+    }
+    // 로컬 재생 목록의 #EXTINF 길이는 파싱한 플레이리스트/타임라인에서 다시 계산
+    let segment_durations = if options.local_playlist {
+        let durations =
+            downloader::resolve_segment_durations(&info, &start_time, &end_time, quality_ref)
+                .await?;
+        if durations.len() != segments.len() {
+            return Err("세그먼트 길이 정보가 세그먼트 목록과 맞지 않습니다".into());
+        }
+        durations
+    } else {
+        Vec::new()
+    };
+
+    if downloader::is_full_range(&start_time, &end_time) {
+        eprintln!("🎬 Whole VOD requested ({}s)", info.duration);
+    }
+
+    // 안전 상한 확인 (초과 시 UI가 확인을 받을 수 있도록 구분되는 오류 반환)
+    let start_secs = time_to_sec(&start_time);
+    let end_secs = if end_time.is_empty() {
+        info.duration as f64
+    } else {
+        time_to_sec(&end_time)
+    };
+    let range_secs = (end_secs - start_secs).max(0.0);
+
+    if let Some(max_secs) = options.max_duration_secs {
+        if range_secs > max_secs as f64 {
+            return Err(format!(
+                "{}: 다운로드 구간({}초)이 설정한 최대 길이({}초)를 초과합니다",
+                CAP_EXCEEDED_ERROR, range_secs as u64, max_secs
+            ));
+        }
+    }
+    if let Some(max_bytes) = options.max_size_bytes {
+        // 실제로 받을 화질(오디오만이면 오디오)의 bitrate로 추정하고,
+        // 그 화질의 bitrate를 모르면 가장 높은 화질 기준으로 잡는다
+        let bandwidth = if options.audio_only_download {
+            downloader::audio_bandwidth(&info).await?
+        } else {
+            let variants = downloader::quality_variants(&info).await?;
+            variants
+                .iter()
+                .find(|v| Some(v.id.as_str()) == quality_ref)
+                .map(|v| v.bandwidth)
+                .filter(|&b| b > 0)
+                .or_else(|| variants.iter().map(|v| v.bandwidth).max())
+                .unwrap_or(0)
+        };
+        if bandwidth == 0 {
+            return Err(format!(
+                "{}: bitrate 정보가 없어 예상 크기를 계산할 수 없습니다",
+                CAP_EXCEEDED_ERROR
+            ));
+        }
+        let estimated_bytes = (bandwidth as f64 / 8.0 * range_secs) as u64;
+        if estimated_bytes > max_bytes {
+            return Err(format!(
+                "{}: 예상 크기({}MB)가 설정한 최대 크기({}MB)를 초과합니다",
+                CAP_EXCEEDED_ERROR,
+                estimated_bytes / (1024 * 1024),
+                max_bytes / (1024 * 1024)
+            ));
+        }
+    }
+
+    // 이미 받은 영상/구간/화질이면 기존 파일 반환 (opt-in)
+    let key = if options.audio_only_download {
+        download_key(&video_id, &start_time, &end_time, Some("audio"))
+    } else {
+        download_key(&video_id, &start_time, &end_time, quality_ref)
+    };
+    if options.dedupe {
+        if let Some(existing) = load_history(&app)?
+            .into_iter()
+            .find(|h| h.key == key && PathBuf::from(&h.output_path).exists())
+        {
+            eprintln!("♻️ Already downloaded as {}", existing.output_path);
+            let _ = app.emit(
+                "download-progress",
+                DownloadProgress {
+                    stage: "complete".into(),
+                    current: 1,
+                    total: 1,
+                    message: "이미 다운로드한 영상입니다".into(),
+                },
+            );
+            return Ok(existing.output_path);
+        }
+    }
+
+    // 3. 세그먼트 다운로드
+    token.status.set_stage("downloading");
+    let mut manifest = ResumeManifest {
+        quality_id: quality_id.clone(),
+        segment_count: segments.len(),
+        start_time: start_time.clone(),
+        end_time: end_time.clone(),
+        output_path: None,
+        audio_only: options.audio_only_download,
+    };
+    manifest.save(&temp_dir).await?;
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    let resolver = if options.audio_only_download {
+        downloader::audio_segment_resolver(&info, &start_time, &end_time)
+    } else {
+        segment_resolver(&info, &start_time, &end_time, quality_ref)
+    };
+    let segment_config = SegmentDownloadConfig {
+        cancel: Some(token.cancel.clone()),
+        status: Some(token.status.clone()),
+        order: options.order,
+        ..settings.segment_config(options.continue_on_error)
+    };
+    let failed_segments =
+        download_segments(&app, &segments, &temp_dir, &segment_config, Some(&resolver)).await?;
+
+    if failed_segments.len() == segments.len() {
+        return Err("모든 세그먼트 다운로드에 실패했습니다".into());
+    }
+
+    // 병합 없이 로컬 재생 목록으로 저장
+    if options.local_playlist {
+        let batch = options.batch_index.zip(options.batch_total);
+        let mut playlist_path =
+            build_output_filename(&info, &start_time, &end_time, &output_dir, batch);
+        playlist_path.set_extension("m3u8");
+        token.status.set_outputs(vec![playlist_path.clone()]);
+        downloader::write_local_playlist(&temp_dir, &segment_durations, &playlist_path).await?;
+        settings.apply_file_mode(&playlist_path);
+        settings.apply_dir_mode(&downloader::local_playlist_segment_dir(&playlist_path));
+
+        let _ = append_history(
+            &app,
+            HistoryEntry {
+                key,
+                video_id: video_id.clone(),
+                output_path: playlist_path.to_string_lossy().to_string(),
+                completed_at: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            },
+        );
+        let message = if failed_segments.is_empty() {
+            "다운로드 완료! (로컬 재생 목록)".to_string()
+        } else {
+            format!(
+                "다운로드 완료 (로컬 재생 목록, 세그먼트 {}개 누락)",
+                failed_segments.len()
+            )
+        };
+        emit_complete(
+            &app,
+            &info.title,
+            message,
+            &[playlist_path.clone()],
+            started,
+        );
+        return Ok(playlist_path.to_string_lossy().to_string());
+    }
+
+    // 4. 세그먼트 병합
+    token.status.set_stage("merging");
+    let combined_path = merge_segments(&app, segments.len(), &temp_dir).await?;
+    if token.is_cancelled() {
+        return Err(downloader::CANCELLED_ERROR.into());
+    }
+    token.status.set_stage("remuxing");
+
+    // 5. ffmpeg로 리먹싱 (재인코딩/축소 옵션이 있으면 재인코딩, no_remux면 원본 TS 그대로)
+    let batch = options.batch_index.zip(options.batch_total);
+    let mut output_path = build_output_filename(&info, &start_time, &end_time, &output_dir, batch);
+    let transcode = match (options.transcode, &options.scale) {
+        (None, Some(_)) => Some(TranscodeOptions::default()),
+        (t, _) => t,
+    };
+    // (파일 경로, 길이) 목록. 분할 저장이면 여러 개
+    let mut outputs = Vec::new();
+    if let Some(ffmpeg_path) = &ffmpeg_path {
+        if let Some(minutes) = options.split_minutes {
+            let parts = downloader::split_parts(&output_path, range_secs, minutes as f64 * 60.0);
+            token
+                .status
+                .set_outputs(parts.iter().map(|p| p.path.clone()).collect());
+            let split = downloader::split_with_ffmpeg(&app, ffmpeg_path, &combined_path, &parts);
+            token.until_cancelled(split).await?;
+            outputs.extend(parts.into_iter().map(|p| (p.path, p.duration_secs)));
+            output_path = outputs[0].0.clone();
+        } else if options.audio_only_download {
+            output_path.set_extension("m4a");
+            token.status.set_outputs(vec![output_path.clone()]);
+            token
+                .until_cancelled(downloader::remux_audio_with_ffmpeg(
+                    &app,
+                    ffmpeg_path,
+                    &combined_path,
+                    &output_path,
+                ))
+                .await?;
+        } else if options.separate_tracks {
+            let (video_path, audio_path) = downloader::separate_track_paths(&output_path);
+            token
+                .status
+                .set_outputs(vec![video_path.clone(), audio_path.clone()]);
+            token
+                .until_cancelled(downloader::separate_tracks_with_ffmpeg(
+                    &app,
+                    ffmpeg_path,
+                    &combined_path,
+                    &video_path,
+                    &audio_path,
+                ))
+                .await?;
+            outputs.push((video_path, range_secs));
+            outputs.push((audio_path, range_secs));
+            output_path = outputs[0].0.clone();
+        } else if let Some(transcode) = &transcode {
+            token.status.set_outputs(vec![output_path.clone()]);
+            token
+                .until_cancelled(downloader::transcode_with_ffmpeg(
+                    &app,
+                    ffmpeg_path,
+                    &combined_path,
+                    &output_path,
+                    transcode,
+                    options.scale.as_ref(),
+                    range_secs,
+                ))
+                .await?;
+        } else {
+            let chapters = downloader::chapters_in_range(&info.chapters, start_secs, end_secs);
+            token.status.set_outputs(vec![output_path.clone()]);
+            token
+                .until_cancelled(remux_with_ffmpeg(
+                    &app,
+                    ffmpeg_path,
+                    &combined_path,
+                    &output_path,
+                    &chapters,
+                    range_secs,
+                ))
+                .await?;
+        }
+    } else {
+        output_path.set_extension("ts");
+        tokio::fs::rename(&combined_path, &output_path)
+            .await
+            .map_err(|e| format!("결과 파일 이동 실패: {}", e))?;
+    }
+
+    if outputs.is_empty() {
+        outputs.push((output_path.clone(), range_secs));
+    }
+
+    // 결과 길이 확인: 기대보다 눈에 띄게 짧으면 세그먼트 누락/잘림을 경고
+    let mut duration_warning = None;
+    if let Some(ffmpeg_path) = &ffmpeg_path {
+        for (path, secs) in &outputs {
+            let Some(actual) = downloader::probe_duration(ffmpeg_path, path).await else {
+                continue;
+            };
+            if let Some(warning) = downloader::duration_shortfall(actual, *secs) {
+                eprintln!("⚠️ {}: {}", path.display(), warning);
+                duration_warning.get_or_insert(warning);
+            }
+        }
+    }
+    // 큰 썸네일 확인(네트워크 요청)은 .nfo를 쓸 때만, 분할 저장이어도 한 번만
+    let nfo_info = if settings.write_nfo {
+        Some(downloader::with_large_thumbnail(&info).await)
+    } else {
+        None
+    };
+    for (path, secs) in &outputs {
+        settings.apply_file_mode(path);
+        if let Some(nfo_info) = &nfo_info {
+            match downloader::write_nfo(nfo_info, path, *secs).await {
+                Ok(nfo_path) => settings.apply_file_mode(&nfo_path),
+                Err(e) => eprintln!("⚠️ {}", e),
+            }
+        }
+    }
 
     // 6. 임시 파일 정리
+    token.status.set_stage("cleanup");
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "cleanup".into(),
+            current: 0,
+            total: 1,
+            message: "임시 파일 정리 중...".into(),
+        },
+    );
+    if failed_segments.is_empty() {
+        let _ = cleanup_temp(&temp_dir).await;
+    } else {
+        // 빠진 세그먼트만 다시 받을 수 있도록 받은 세그먼트와 매니페스트는 남긴다
+        let _ = tokio::fs::remove_file(&combined_path).await;
+        manifest.output_path = Some(output_path.to_string_lossy().to_string());
+        let _ = manifest.save(&temp_dir).await;
+    }
+
+    let _ = append_history(
+        &app,
+        HistoryEntry {
+            key,
+            video_id: video_id.clone(),
+            output_path: output_path.to_string_lossy().to_string(),
+            completed_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        },
+    );
+
+    let message = if let Some(warning) = duration_warning {
+        format!("다운로드 완료 ({})", warning)
+    } else if !failed_segments.is_empty() {
+        format!(
+            "다운로드 완료 (세그먼트 {}개 누락: {:?})",
+            failed_segments.len(),
+            failed_segments
+        )
+    } else if options.separate_tracks {
+        "다운로드 완료! (영상/오디오 분리 저장)".into()
+    } else if options.audio_only_download {
+        "다운로드 완료! (오디오만)".into()
+    } else if outputs.len() > 1 {
+        format!("다운로드 완료! ({}개 파일로 분할)", outputs.len())
+    } else {
+        "다운로드 완료!".into()
+    };
+    let output_files: Vec<PathBuf> = outputs.into_iter().map(|(path, _)| path).collect();
+    emit_complete(&app, &info.title, message, &output_files, started);
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// 한 VOD의 여러 구간을 각각의 파일로 저장
+///
+/// 구간이 겹치는 세그먼트는 한 번만 받는다. 모든 구간의 세그먼트를 한 임시 폴더에
+/// 받은 뒤 구간별로 이어 붙여 리먹싱하며, 반환값은 구간 순서대로의 결과 파일 경로.
+#[tauri::command]
+async fn download_vod_ranges(
+    app: tauri::AppHandle,
+    video_id: String,
+    ranges: Vec<(String, String)>,
+    output_dir: String,
+    quality_id: Option<String>,
+    continue_on_error: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let started = std::time::Instant::now();
+    if ranges.is_empty() {
+        return Err("다운로드할 구간이 없습니다".into());
+    }
+    for (start_time, end_time) in &ranges {
+        if !end_time.is_empty() && time_to_sec(end_time) <= time_to_sec(start_time) {
+            return Err(format!("잘못된 구간입니다: {} ~ {}", start_time, end_time));
+        }
+    }
+    let ffmpeg_path = find_ffmpeg(&app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+
+    let cancel_state = app.state::<CancelState>();
+    let token = cancel_state.register_download(&video_id);
+    token.status.set_stage("queued");
+    let limits = app.state::<DownloadLimits>();
+    let _vod_permit = limits
+        .vods
+        .acquire()
+        .await
+        .map_err(|e| format!("다운로드 대기 실패: {}", e))?;
+    if token.is_cancelled() {
+        return Err(downloader::CANCELLED_ERROR.into());
+    }
+
+    token.status.set_stage("info");
+    let info = load_video_info(app.clone(), &video_id).await?;
+    let quality_id = match quality_id {
+        Some(q) => Some(q),
+        None => downloader::highest_quality_id(&info).await?,
+    };
+    let quality_ref = quality_id.as_deref();
+
+    // 구간별 세그먼트 URL을 모아 합집합 목록을 만들고, 각 구간은 그 목록의 인덱스로 기억
+    let mut union: Vec<String> = Vec::new();
+    let mut union_index: HashMap<String, usize> = HashMap::new();
+    let mut range_indices = Vec::new();
+    for (start_time, end_time) in &ranges {
+        let segments = resolve_segments(&info, start_time, end_time, quality_ref).await?;
+        if segments.is_empty() {
+            return Err(format!(
+                "다운로드할 세그먼트가 없습니다 ({} ~ {})",
+                start_time, end_time
+            ));
+        }
+        let indices: Vec<usize> = segments
+            .into_iter()
+            .map(|url| {
+                *union_index.entry(url.clone()).or_insert_with(|| {
+                    union.push(url);
+                    union.len() - 1
+                })
+            })
+            .collect();
+        range_indices.push(indices);
+    }
+    let requested: usize = range_indices.iter().map(Vec::len).sum();
+    eprintln!(
+        "🎯 {} range(s): {} unique segment(s) of {} requested",
+        ranges.len(),
+        union.len(),
+        requested
+    );
+    downloader::preflight_segment(&union[0]).await?;
+
+    token.status.set_stage("downloading");
+    let range_key: Vec<String> = ranges.iter().map(|(s, e)| format!("{}-{}", s, e)).collect();
+    let temp_dir = downloader::vod_temp_dir(
+        &output_dir,
+        &format!("ranges_{}", video_id),
+        &range_key.join(","),
+        "",
+        quality_ref.unwrap_or(""),
+    );
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    let segment_config = SegmentDownloadConfig {
+        cancel: Some(token.cancel.clone()),
+        status: Some(token.status.clone()),
+        ..settings.segment_config(continue_on_error.unwrap_or(false))
+    };
+    let failed_segments = download_segments(&app, &union, &temp_dir, &segment_config, None).await?;
+    if failed_segments.len() == union.len() {
+        return Err("모든 세그먼트 다운로드에 실패했습니다".into());
+    }
+
+    // 구간마다 해당 세그먼트만 이어 붙여 리먹싱
+    token.status.set_stage("remuxing");
+    let mut outputs = Vec::new();
+    for (i, ((start_time, end_time), indices)) in ranges.iter().zip(&range_indices).enumerate() {
+        if token.is_cancelled() {
+            return Err(downloader::CANCELLED_ERROR.into());
+        }
+        let range_path = temp_dir.join(format!("range_{}.raw", i));
+        downloader::concat_segment_files(&temp_dir, indices.iter().copied(), &range_path).await?;
+
+        let start_secs = time_to_sec(start_time);
+        let end_secs = if end_time.is_empty() {
+            info.duration as f64
+        } else {
+            time_to_sec(end_time)
+        };
+        let output_path = build_output_filename(
+            &info,
+            start_time,
+            end_time,
+            &output_dir,
+            Some((i + 1, ranges.len())),
+        );
+        let chapters = downloader::chapters_in_range(&info.chapters, start_secs, end_secs);
+        token.status.set_outputs(vec![output_path.clone()]);
+        token
+            .until_cancelled(remux_with_ffmpeg(
+                &app,
+                &ffmpeg_path,
+                &range_path,
+                &output_path,
+                &chapters,
+                (end_secs - start_secs).max(0.0),
+            ))
+            .await?;
+        settings.apply_file_mode(&output_path);
+
+        let _ = append_history(
+            &app,
+            HistoryEntry {
+                key: download_key(&video_id, start_time, end_time, quality_ref),
+                video_id: video_id.clone(),
+                output_path: output_path.to_string_lossy().to_string(),
+                completed_at: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            },
+        );
+        outputs.push(output_path);
+    }
+
+    token.status.set_stage("cleanup");
+    let _ = cleanup_temp(&temp_dir).await;
+
+    let message = if failed_segments.is_empty() {
+        format!("다운로드 완료! ({}개 구간)", outputs.len())
+    } else {
+        format!(
+            "다운로드 완료 ({}개 구간, 세그먼트 {}개 누락)",
+            outputs.len(),
+            failed_segments.len()
+        )
+    };
+    emit_complete(&app, &info.title, message, &outputs, started);
+
+    Ok(outputs
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+/// download_segment_range 응답
+#[derive(Serialize)]
+struct SegmentRangeResult {
+    /// 세그먼트를 저장한 임시 폴더 (같은 화질로 VOD 전체를 받을 때 쓰는 폴더)
+    temp_dir: String,
+    /// VOD 전체 세그먼트 수
+    total_segments: usize,
+    /// 받은 세그먼트 번호
+    downloaded: Vec<usize>,
+    /// 받지 못한 세그먼트 번호
+    failed: Vec<usize>,
+}
+
+/// VOD 전체 세그먼트 중 `first..=last` 번호(0부터)만 임시 폴더에 받기
+///
+/// 어느 세그먼트가 깨졌는지 확인하거나 일부만 다시 받을 때 쓴다. 파일은 VOD 전체
+/// 다운로드와 같은 임시 폴더/이름으로 저장되어, 이후 전체 다운로드가 그대로 이어받는다.
+#[tauri::command]
+async fn download_segment_range(
+    app: tauri::AppHandle,
+    video_id: String,
+    output_dir: String,
+    quality_id: Option<String>,
+    first: usize,
+    last: usize,
+) -> Result<SegmentRangeResult, String> {
+    let info = load_video_info(app.clone(), &video_id).await?;
+    let quality_key = quality_id.clone().unwrap_or_else(|| "best".into());
+    let temp_dir = downloader::vod_temp_dir(&output_dir, &video_id, "", "", &quality_key);
+    let manifest = ResumeManifest::load(&temp_dir).await;
+    let quality_id = match quality_id {
+        Some(q) => Some(q),
+        None => match manifest.as_ref().and_then(|m| m.quality_id.clone()) {
+            Some(q) => Some(q),
+            None => downloader::highest_quality_id(&info).await?,
+        },
+    };
+    let quality_ref = quality_id.as_deref();
+    if let Some(previous) = &manifest {
+        if !previous.matches_quality(quality_ref, false) {
+            return Err("이전 다운로드와 화질이 다릅니다".into());
+        }
+    }
+
+    let segments = resolve_segments(&info, "", "", quality_ref).await?;
+    // 범위가 틀렸으면 매니페스트를 남기거나 다운로드로 등록하기 전에 실패
+    downloader::check_segment_range(first, last, segments.len())?;
+    if manifest.is_none() {
+        // 이후 전체 다운로드가 같은 화질로 이어받도록 기록
+        ResumeManifest {
+            quality_id: quality_id.clone(),
+            segment_count: segments.len(),
+            ..Default::default()
+        }
+        .save(&temp_dir)
+        .await?;
+    }
+
+    let cancel_state = app.state::<CancelState>();
+    let token = cancel_state.register_download(&video_id);
+    token.status.set_stage("queued");
+    let limits = app.state::<DownloadLimits>();
+    let _vod_permit = limits
+        .vods
+        .acquire()
+        .await
+        .map_err(|e| format!("다운로드 대기 실패: {}", e))?;
+    token.status.set_stage("downloading");
+    eprintln!(
+        "🧩 Downloading segments {}..={} of {} into {}",
+        first,
+        last,
+        segments.len(),
+        temp_dir.display()
+    );
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    let segment_config = SegmentDownloadConfig {
+        cancel: Some(token.cancel.clone()),
+        status: Some(token.status.clone()),
+        ..settings.segment_config(true)
+    };
+    let failed = downloader::download_segment_range(
+        &app,
+        &segments,
+        first,
+        last,
+        &temp_dir,
+        &segment_config,
+    )
+    .await?;
+    if token.is_cancelled() {
+        return Err(downloader::CANCELLED_ERROR.into());
+    }
+
+    Ok(SegmentRangeResult {
+        temp_dir: temp_dir.to_string_lossy().to_string(),
+        total_segments: segments.len(),
+        downloaded: (first..=last).filter(|i| !failed.contains(i)).collect(),
+        failed,
+    })
+}
+
+/// 일부 세그먼트가 빠진 채 완료된 다운로드에서 빠진 세그먼트만 다시 받아 결과 파일 재생성
+///
+/// 임시 폴더에 남은 매니페스트로 같은 구간/화질의 세그먼트 목록을 다시 만들고,
+/// 없는 세그먼트만 받은 뒤 병합과 리먹싱을 다시 실행한다.
+#[tauri::command]
+async fn retry_failed_segments(
+    app: tauri::AppHandle,
+    video_id: String,
+    output_dir: String,
+) -> Result<String, String> {
+    let started = std::time::Instant::now();
+    // 세그먼트가 빠진 채 완료된 다운로드의 임시 폴더 (결과 파일 경로가 기록된 것)
+    let mut candidates = Vec::new();
+    for dir in downloader::find_vod_temp_dirs(&output_dir, &video_id).await {
+        if let Some(manifest) = ResumeManifest::load(&dir).await {
+            if manifest.output_path.is_some() {
+                candidates.push((dir, manifest));
+            }
+        }
+    }
+    if candidates.len() > 1 {
+        return Err(format!(
+            "다시 받을 수 있는 다운로드가 {}개 있습니다. 필요 없는 임시 폴더를 정리해주세요",
+            candidates.len()
+        ));
+    }
+    let (temp_dir, manifest) = candidates
+        .pop()
+        .ok_or("다시 받을 임시 폴더(매니페스트)가 없습니다")?;
+    let ffmpeg_path = find_ffmpeg(&app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+
+    let cancel_state = app.state::<CancelState>();
+    let token = cancel_state.register_download(&video_id);
+    token.status.set_stage("queued");
+    let limits = app.state::<DownloadLimits>();
+    let _vod_permit = limits
+        .vods
+        .acquire()
+        .await
+        .map_err(|e| format!("다운로드 대기 실패: {}", e))?;
+    token.status.set_stage("info");
+    let info = load_video_info(app.clone(), &video_id).await?;
+    let quality_ref = manifest.quality_id.as_deref();
+    let segments = if manifest.audio_only {
+        downloader::resolve_audio_segments(&info, &manifest.start_time, &manifest.end_time).await?
+    } else {
+        resolve_segments(&info, &manifest.start_time, &manifest.end_time, quality_ref).await?
+    };
+    if segments.len() != manifest.segment_count {
+        return Err(format!(
+            "세그먼트 목록이 처음 다운로드와 다릅니다 ({}개 → {}개). 처음부터 다시 받아주세요",
+            manifest.segment_count,
+            segments.len()
+        ));
+    }
+    let existing = downloader::scan_segment_indices(&temp_dir).await?;
+    eprintln!(
+        "🔁 Retrying {} missing segment(s) of {}",
+        segments.len().saturating_sub(existing.len()),
+        segments.len()
+    );
+
+    // 이미 받은 세그먼트는 건너뛰므로 빠진 것만 요청된다
+    token.status.set_stage("downloading");
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    let resolver = if manifest.audio_only {
+        downloader::audio_segment_resolver(&info, &manifest.start_time, &manifest.end_time)
+    } else {
+        segment_resolver(&info, &manifest.start_time, &manifest.end_time, quality_ref)
+    };
+    let segment_config = SegmentDownloadConfig {
+        cancel: Some(token.cancel.clone()),
+        status: Some(token.status.clone()),
+        ..settings.segment_config(true)
+    };
+    let failed_segments =
+        download_segments(&app, &segments, &temp_dir, &segment_config, Some(&resolver)).await?;
+
+    token.status.set_stage("merging");
+    let combined_path = merge_segments(&app, segments.len(), &temp_dir).await?;
+    token.status.set_stage("remuxing");
+    let output_path = match &manifest.output_path {
+        Some(path) => PathBuf::from(path),
+        None => build_output_filename(
+            &info,
+            &manifest.start_time,
+            &manifest.end_time,
+            &output_dir,
+            None,
+        ),
+    };
+    let start_secs = time_to_sec(&manifest.start_time);
+    let end_secs = if manifest.end_time.is_empty() {
+        info.duration as f64
+    } else {
+        time_to_sec(&manifest.end_time)
+    };
+    let chapters = downloader::chapters_in_range(&info.chapters, start_secs, end_secs);
+    if manifest.audio_only {
+        token
+            .until_cancelled(downloader::remux_audio_with_ffmpeg(
+                &app,
+                &ffmpeg_path,
+                &combined_path,
+                &output_path,
+            ))
+            .await?;
+    } else {
+        token
+            .until_cancelled(remux_with_ffmpeg(
+                &app,
+                &ffmpeg_path,
+                &combined_path,
+                &output_path,
+                &chapters,
+                (end_secs - start_secs).max(0.0),
+            ))
+            .await?;
+    }
+    settings.apply_file_mode(&output_path);
+
+    token.status.set_stage("cleanup");
+    let message = if failed_segments.is_empty() {
+        let _ = cleanup_temp(&temp_dir).await;
+        "다운로드 완료! (빠진 세그먼트를 모두 받았습니다)".to_string()
+    } else {
+        let _ = tokio::fs::remove_file(&combined_path).await;
+        format!(
+            "다운로드 완료 (세그먼트 {}개 여전히 누락: {:?})",
+            failed_segments.len(),
+            failed_segments
+        )
+    };
+    let outputs = std::slice::from_ref(&output_path);
+    emit_complete(&app, &info.title, message, outputs, started);
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// salvage_partial_download 응답
+#[derive(Serialize)]
+struct SalvageResult {
+    output_path: String,
+    /// 결과 파일의 실제 길이 (초, 읽지 못하면 None)
+    duration_secs: Option<f64>,
+    /// 임시 폴더에 있던 세그먼트 수 / 전체 세그먼트 수
+    segments_present: usize,
+    segments_total: usize,
+}
+
+/// 취소되었거나 일부 실패한 다운로드의 임시 폴더에 있는 세그먼트만으로 결과 파일 만들기
+///
+/// 결과는 `이름_partial.mp4`로 저장하고, 임시 폴더는 나중에 이어받을 수 있도록 남긴다.
+#[tauri::command]
+async fn salvage_partial_download(
+    app: tauri::AppHandle,
+    video_id: String,
+    output_dir: String,
+) -> Result<SalvageResult, String> {
+    let ffmpeg_path = find_ffmpeg(&app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+    let mut temp_dirs = downloader::find_vod_temp_dirs(&output_dir, &video_id).await;
+    if temp_dirs.len() > 1 {
+        return Err(format!(
+            "이 VOD의 임시 폴더가 {}개 있습니다. 필요 없는 임시 폴더를 정리해주세요",
+            temp_dirs.len()
+        ));
+    }
+    let temp_dir = temp_dirs.pop().ok_or("받아 둔 임시 폴더가 없습니다")?;
+    let manifest = ResumeManifest::load(&temp_dir).await.unwrap_or_default();
+
+    let indices = downloader::scan_segment_indices(&temp_dir).await?;
+    if indices.is_empty() {
+        return Err("받아 둔 세그먼트가 없습니다".into());
+    }
+    let segments_total = indices
+        .iter()
+        .max()
+        .map_or(0, |max| max + 1)
+        .max(manifest.segment_count);
+
+    // 병합/리먹싱도 VOD 다운로드와 같은 동시 실행 수 제한을 따른다
+    let limits = app.state::<DownloadLimits>();
+    let _vod_permit = limits
+        .vods
+        .acquire()
+        .await
+        .map_err(|e| format!("다운로드 대기 실패: {}", e))?;
+
+    let info = load_video_info(app.clone(), &video_id).await?;
+    let full_path =
+        build_output_filename(&info, &manifest.start_time, &manifest.end_time, &output_dir, None);
+    let stem = full_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| video_id.clone());
+    let output_path = full_path.with_file_name(format!("{}_partial.mp4", stem));
+
+    let combined_path = merge_segments(&app, segments_total, &temp_dir).await?;
+    let result =
+        downloader::salvage_remux_with_ffmpeg(&app, &ffmpeg_path, &combined_path, &output_path)
+            .await;
+    // 이어받기에는 병합 파일이 필요 없으므로 지움
+    let _ = tokio::fs::remove_file(&combined_path).await;
+    result?;
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    settings.apply_file_mode(&output_path);
+
+    let duration_secs = downloader::probe_duration(&ffmpeg_path, &output_path).await;
+    eprintln!(
+        "🩹 Salvaged {}/{} segments into {} ({:?}s)",
+        indices.len(),
+        segments_total,
+        output_path.display(),
+        duration_secs
+    );
+
+    Ok(SalvageResult {
+        output_path: output_path.to_string_lossy().to_string(),
+        duration_secs,
+        segments_present: indices.len(),
+        segments_total,
+    })
+}
+
+/// 이미 받은 파일이 온전한지 확인 (열림/영상·오디오 스트림/끝부분/길이)
+#[tauri::command]
+async fn verify_file(
+    app: tauri::AppHandle,
+    path: String,
+    expected_duration: Option<f64>,
+) -> Result<downloader::VerifyReport, String> {
+    let ffmpeg_path = find_ffmpeg(&app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+    let report =
+        downloader::verify_media_file(&ffmpeg_path, Path::new(&path), expected_duration).await;
+    if report.passed {
+        eprintln!("✅ Verified {}", path);
+    } else {
+        eprintln!("❌ Verify failed for {}: {:?}", path, report.problems);
+    }
+    Ok(report)
+}
+
+/// 폴더 안의 영상 파일을 모두 검사
+#[tauri::command]
+async fn verify_directory(
+    app: tauri::AppHandle,
+    dir: String,
+) -> Result<Vec<downloader::VerifyReport>, String> {
+    let ffmpeg_path = find_ffmpeg(&app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+    let reports = downloader::verify_media_dir(&ffmpeg_path, Path::new(&dir)).await?;
+    let failed = reports.iter().filter(|r| !r.passed).count();
+    eprintln!(
+        "🔎 Verified {} file(s) in {}: {} failed",
+        reports.len(),
+        dir,
+        failed
+    );
+    Ok(reports)
+}
+
+/// 선택한 화질의 첫 세그먼트만 받아 재생 가능한지 미리 확인
+///
+/// 성공하면 ffmpeg가 읽은 영상 스트림 정보(코덱/해상도 등)를 반환한다.
+#[tauri::command]
+async fn test_quality(
+    app: tauri::AppHandle,
+    video_id: String,
+    quality_id: String,
+) -> Result<String, String> {
+    let ffmpeg_path = find_ffmpeg(&app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+
+    let info = load_video_info(app.clone(), &video_id).await?;
+    let segments = resolve_segments(&info, "", "", Some(&quality_id)).await?;
+
+    let temp_dir = std::env::temp_dir().join(format!("chzzk_test_{}", video_id));
+    let stream = downloader::probe_first_segment(&info, &segments, &ffmpeg_path, &temp_dir).await?;
+    eprintln!("✅ Quality {} OK: {}", quality_id, stream);
+
+    Ok(stream)
+}
+
+/// DASH VOD의 세그먼트 계산 과정 진단 (받지 않고 구간/타임라인 정보만 반환)
+///
+/// "다운로드할 세그먼트가 없습니다" 오류가 날 때 원인을 확인하는 용도.
+#[tauri::command]
+async fn diagnose_dash_segments(
+    app: tauri::AppHandle,
+    video_id: String,
+    start_time: String,
+    end_time: String,
+    quality_id: Option<String>,
+) -> Result<downloader::DashSegmentReport, String> {
+    let info = load_video_info(app, &video_id).await?;
+    let (Some(dash_video_id), Some(dash_in_key)) = (&info.dash_video_id, &info.dash_in_key) else {
+        return Err("DASH 형식 VOD가 아닙니다".into());
+    };
+    let (_, report) = downloader::parse_dash_segments_with_report(
+        dash_video_id,
+        dash_in_key,
+        &start_time,
+        &end_time,
+        quality_id.as_deref(),
+    )
+    .await?;
+    eprintln!("🔎 DASH segments for {}: {:?}", video_id, report);
+    Ok(report)
+}
+
+/// export_segments로 쓰는 JSON 파일 내용
+#[derive(Serialize)]
+struct SegmentExport {
+    video_id: String,
+    start_time: String,
+    end_time: String,
+    quality_id: Option<String>,
+    /// 받을 순서대로의 세그먼트 URL (초기화 세그먼트가 있으면 맨 앞)
+    segments: Vec<String>,
+}
+
+/// 구간의 세그먼트 URL 목록을 파일로 내보내기 (받지 않음, 다른 다운로드 도구용)
+///
+/// `path`가 `.json`이면 [`SegmentExport`] JSON, 그 밖에는 한 줄에 URL 하나씩 쓴다
+/// (aria2c `-i` 입력으로 바로 쓸 수 있음). 반환값은 세그먼트 수.
+/// 암호화된 HLS 세그먼트는 이 앱에서만 복호화할 수 있으므로 내보내지 않는다.
+#[tauri::command]
+async fn export_segments(
+    app: tauri::AppHandle,
+    video_id: String,
+    start_time: String,
+    end_time: String,
+    quality_id: Option<String>,
+    path: String,
+) -> Result<usize, String> {
+    let info = load_video_info(app, &video_id).await?;
+    let segments = resolve_segments(&info, &start_time, &end_time, quality_id.as_deref()).await?;
+    if segments.is_empty() {
+        return Err("다운로드할 세그먼트가 없습니다".into());
+    }
+    if segments
+        .iter()
+        .any(|url| downloader::is_encrypted_segment(url))
+    {
+        return Err("암호화된 HLS 세그먼트는 내보낼 수 없습니다".into());
+    }
+
+    let path = PathBuf::from(path);
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let count = segments.len();
+    let content = if is_json {
+        let export = SegmentExport {
+            video_id: video_id.clone(),
+            start_time,
+            end_time,
+            quality_id,
+            segments,
+        };
+        serde_json::to_string_pretty(&export).map_err(|e| format!("JSON 직렬화 실패: {}", e))?
+    } else {
+        segments.join("\n") + "\n"
+    };
+    fs::write(&path, content).map_err(|e| format!("세그먼트 목록 쓰기 실패: {}", e))?;
+    eprintln!(
+        "📤 Exported {} segment URLs for {} to {}",
+        count,
+        video_id,
+        path.display()
+    );
+    Ok(count)
+}
+
+/// preview_output_filename 입력 (fetch_video_info로 받은 값과 다운로드 구간)
+#[derive(Deserialize)]
+struct FilenamePreviewFields {
+    channel: String,
+    title: String,
+    /// 공개 일시 ("YYYY-MM-DD HH:MM:SS")
+    publish_date: Option<String>,
+    #[serde(default)]
+    start_time: String,
+    #[serde(default)]
+    end_time: String,
+    output_dir: String,
+    /// VOD 길이 (초). 끝 시각 없이 분할 저장할 때 파일 수 계산에 사용
+    duration_secs: Option<u64>,
+}
+
+/// preview_output_filename 응답
+#[derive(Serialize)]
+struct FilenamePreview {
+    /// 만들어질 파일 경로 (분할/분리 저장이면 여러 개)
+    paths: Vec<String>,
+    /// 그중 이미 있는 파일 (받으면 덮어쓴다)
+    existing: Vec<String>,
+}
+
+/// 다운로드 전에 만들어질 파일 경로 미리보기 (파일은 만들지 않음)
+///
+/// 파일 이름 템플릿, 파일명 정리, download_vod 옵션(분할/분리/오디오만/리먹싱 없이)을
+/// 다운로드와 같게 적용하고, 이미 있는 파일도 알려준다.
+#[tauri::command]
+async fn preview_output_filename(
+    fields: FilenamePreviewFields,
+    options: Option<VodDownloadOptions>,
+) -> FilenamePreview {
+    let options = options.unwrap_or_default();
+    let info = VideoInfo {
+        channel: fields.channel,
+        title: fields.title,
+        publish_date: fields.publish_date,
+        duration: fields.duration_secs.unwrap_or(0),
+        ..Default::default()
+    };
+    let batch = options.batch_index.zip(options.batch_total);
+    let mut output_path = build_output_filename(
+        &info,
+        &fields.start_time,
+        &fields.end_time,
+        &fields.output_dir,
+        batch,
+    );
+
+    let paths = if options.no_remux {
+        output_path.set_extension("ts");
+        vec![output_path]
+    } else if let Some(minutes) = options.split_minutes.filter(|m| *m > 0) {
+        let end_secs = if fields.end_time.is_empty() {
+            info.duration as f64
+        } else {
+            time_to_sec(&fields.end_time)
+        };
+        let range_secs = (end_secs - time_to_sec(&fields.start_time)).max(0.0);
+        downloader::split_parts(&output_path, range_secs, minutes as f64 * 60.0)
+            .into_iter()
+            .map(|p| p.path)
+            .collect()
+    } else if options.audio_only_download {
+        output_path.set_extension("m4a");
+        vec![output_path]
+    } else if options.separate_tracks {
+        let (video_path, audio_path) = downloader::separate_track_paths(&output_path);
+        vec![video_path, audio_path]
+    } else if options.local_playlist {
+        output_path.set_extension("m3u8");
+        let segment_dir = downloader::local_playlist_segment_dir(&output_path);
+        vec![output_path, segment_dir]
+    } else {
+        vec![output_path]
+    };
+
+    FilenamePreview {
+        existing: paths
+            .iter()
+            .filter(|p| p.exists())
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+        paths: paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+    }
+}
+
+/// 트림 UI용: VOD에서 지정할 수 있는 시작/끝 범위
+///
+/// DASH는 타임라인에서 계산한 세그먼트 경계도 함께 반환한다 (다시보기 VOD는 duration과 다를 수 있음).
+#[tauri::command]
+async fn get_vod_bounds(
+    app: tauri::AppHandle,
+    video_id: String,
+    quality_id: Option<String>,
+) -> Result<downloader::VodBounds, String> {
+    let info = load_video_info(app, &video_id).await?;
+    let mut bounds = downloader::VodBounds {
+        duration_secs: info.duration as f64,
+        first_segment_secs: None,
+        last_segment_secs: None,
+        timeline_end_secs: None,
+    };
+    if let (Some(dash_video_id), Some(dash_in_key)) = (&info.dash_video_id, &info.dash_in_key) {
+        let (first, last, end) =
+            downloader::dash_timeline_bounds(dash_video_id, dash_in_key, quality_id.as_deref())
+                .await?;
+        bounds.first_segment_secs = first;
+        bounds.last_segment_secs = last;
+        bounds.timeline_end_secs = Some(end);
+    }
+    Ok(bounds)
+}
+
+/// 이미 병합된 파일(combined.raw)로 ffmpeg 단계만 다시 실행
+///
+/// 리먹싱이 실패하면 임시 폴더가 남아 있으므로 다시 받지 않고 재시도할 수 있다.
+#[tauri::command]
+async fn remux_existing(
+    app: tauri::AppHandle,
+    combined_path: String,
+    output_path: String,
+    options: Option<RemuxOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let combined_path = PathBuf::from(combined_path);
+    let output_path = PathBuf::from(output_path);
+
+    if !combined_path.is_file() {
+        return Err(format!(
+            "병합 파일을 찾을 수 없습니다: {}",
+            combined_path.to_string_lossy()
+        ));
+    }
+
+    convert_combined(&app, &combined_path, &output_path, options).await?;
+
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "complete".into(),
+            current: 1,
+            total: 1,
+            message: "리먹싱 완료!".into(),
+        },
+    );
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// 병합 파일을 옵션에 따라 트랜스코딩하거나 리먹싱해 output_path로 저장
+async fn convert_combined(
+    app: &tauri::AppHandle,
+    combined_path: &Path,
+    output_path: &Path,
+    options: RemuxOptions,
+) -> Result<(), String> {
+    let ffmpeg_path = find_ffmpeg(app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+
+    let transcode = match (options.transcode, &options.scale) {
+        (None, Some(_)) => Some(TranscodeOptions::default()),
+        (t, _) => t,
+    };
+    if let Some(transcode) = &transcode {
+        downloader::transcode_with_ffmpeg(
+            app,
+            &ffmpeg_path,
+            combined_path,
+            output_path,
+            transcode,
+            options.scale.as_ref(),
+            0.0,
+        )
+        .await?;
+    } else {
+        remux_with_ffmpeg(app, &ffmpeg_path, combined_path, output_path, &[], 0.0).await?;
+    }
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    settings.apply_file_mode(output_path);
+    Ok(())
+}
+
+/// 남아 있는 임시 폴더(temp_<id>)의 세그먼트로 다운로드를 마무리
+///
+/// 크래시 등으로 중단된 다운로드를 다시 받지 않고 병합 + 리먹싱한 뒤 임시 폴더를 정리한다.
+/// 세그먼트 번호에 빠진 곳이 있으면 그대로 진행하되 완료 메시지로 알린다.
+#[tauri::command]
+async fn finalize_temp_dir(
+    app: tauri::AppHandle,
+    temp_dir: String,
+    output_path: String,
+    options: Option<RemuxOptions>,
+) -> Result<String, String> {
+    let temp_dir = PathBuf::from(temp_dir);
+    let output_path = PathBuf::from(output_path);
+
+    let indices = downloader::scan_segment_indices(&temp_dir).await?;
+    let Some(&last) = indices.last() else {
+        return Err(format!(
+            "세그먼트 파일(seg_*.m4s)이 없습니다: {}",
+            temp_dir.to_string_lossy()
+        ));
+    };
+    let segment_count = last + 1;
+    let missing: Vec<usize> = (0..segment_count)
+        .filter(|i| indices.binary_search(i).is_err())
+        .collect();
+    if !missing.is_empty() {
+        eprintln!(
+            "⚠️ {} segment(s) missing in {:?}: {:?}",
+            missing.len(),
+            temp_dir,
+            missing
+        );
+    }
+
+    // 병합/리먹싱도 VOD 다운로드와 같은 동시 실행 수 제한을 따른다
+    let limits = app.state::<DownloadLimits>();
+    let _vod_permit = limits
+        .vods
+        .acquire()
+        .await
+        .map_err(|e| format!("다운로드 대기 실패: {}", e))?;
+
+    let combined_path = merge_segments(&app, segment_count, &temp_dir).await?;
+    convert_combined(&app, &combined_path, &output_path, options.unwrap_or_default()).await?;
+    cleanup_temp(&temp_dir).await?;
+
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "complete".into(),
+            current: 1,
+            total: 1,
+            message: if missing.is_empty() {
+                "복구 완료!".into()
+            } else {
+                format!(
+                    "복구 완료 (세그먼트 {}개 누락: {:?})",
+                    missing.len(),
+                    missing
+                )
+            },
+        },
+    );
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// 기존 파일이 끝난 지점을 세그먼트 경계로 보고 그 다음 세그먼트부터 고르기 위한 여유 (초)
+const EXTEND_BOUNDARY_SLACK_SECS: f64 = 0.5;
+
+/// 중간에 끊긴 결과 파일(병합/리먹싱까지 끝난 파일)을 이어서 끝까지 받기
+///
+/// 기존 파일 길이를 ffmpeg로 확인해 `start_time` + 길이부터 `end_time`(비우면 VOD 끝)까지의
+/// 세그먼트만 받은 뒤, 기존 파일 뒤에 이어 붙여 다시 리먹싱한다. 파일명이 이 VOD의 것이고
+/// 해상도가 같은 화질이 있어야 진행한다. 결과는 기존 파일과 같은 이름의 .mp4로 저장한다.
+#[tauri::command]
+async fn extend_vod_download(
+    app: tauri::AppHandle,
+    video_id: String,
+    existing_path: String,
+    start_time: Option<String>,
+    end_time: Option<String>,
+    quality_id: Option<String>,
+) -> Result<String, String> {
+    let existing = PathBuf::from(&existing_path);
+    if !existing.is_file() {
+        return Err(format!("파일을 찾을 수 없습니다: {}", existing_path));
+    }
+    let ffmpeg_path = find_ffmpeg(&app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+
+    let cancel_state = app.state::<CancelState>();
+    let token = cancel_state.register_download(&video_id);
+    token.status.set_stage("queued");
+    let limits = app.state::<DownloadLimits>();
+    let _vod_permit = limits
+        .vods
+        .acquire()
+        .await
+        .map_err(|e| format!("다운로드 대기 실패: {}", e))?;
+    token.status.set_stage("info");
+
+    // 1. 같은 VOD/화질의 파일인지 확인
+    let info = load_video_info(app.clone(), &video_id).await?;
+    let vod_stem = build_output_filename(&info, "", "", "", None)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let existing_stem = existing
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if vod_stem.is_empty() || !existing_stem.starts_with(&vod_stem) {
+        return Err(format!(
+            "이 VOD의 파일이 아닙니다 (예상 파일명: {}...)",
+            vod_stem
+        ));
+    }
+
+    let height = downloader::probe_video_height(&ffmpeg_path, &existing)
+        .await
+        .ok_or("기존 파일의 해상도를 확인할 수 없습니다")?;
+    let variants = downloader::quality_variants(&info).await?;
+    let quality_id = match quality_id {
+        Some(q) => {
+            if !variants.iter().any(|v| v.id == q && v.height == height) {
+                return Err(format!("기존 파일({}p)과 다른 화질입니다", height));
+            }
+            q
+        }
+        None => variants
+            .into_iter()
+            .find(|v| v.height == height)
+            .map(|v| v.id)
+            .ok_or_else(|| format!("기존 파일과 같은 화질({}p)을 찾을 수 없습니다", height))?,
+    };
+
+    // 2. 이어 받을 구간 계산
+    let have_secs = downloader::probe_duration(&ffmpeg_path, &existing)
+        .await
+        .ok_or("기존 파일 길이를 확인할 수 없습니다")?;
+    let start_secs = time_to_sec(start_time.as_deref().unwrap_or("").trim());
+    let end_time = end_time.unwrap_or_default();
+    let end_secs = if end_time.trim().is_empty() {
+        info.duration as f64
+    } else {
+        time_to_sec(end_time.trim())
+    };
+    let resume_secs = start_secs + have_secs;
+    if resume_secs + 1.0 >= end_secs {
+        return Err("이미 끝까지 받은 파일입니다".into());
+    }
+    eprintln!(
+        "⏩ Extending {:?} from {:.1}s to {:.1}s",
+        existing, resume_secs, end_secs
+    );
+
+    let resume_time = format!("{:.3}", resume_secs + EXTEND_BOUNDARY_SLACK_SECS);
+    let segments = resolve_segments(&info, &resume_time, &end_time, Some(&quality_id)).await?;
+    if segments.is_empty() {
+        return Err("다운로드할 세그먼트가 없습니다".into());
+    }
+
+    // 3. 남은 세그먼트 다운로드 → 병합 → 리먹싱
+    token.status.set_stage("downloading");
+    let temp_dir = existing.with_file_name(format!("temp_extend_{}", video_id));
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    let resolver = segment_resolver(&info, &resume_time, &end_time, Some(&quality_id));
+    let segment_config = SegmentDownloadConfig {
+        cancel: Some(token.cancel.clone()),
+        status: Some(token.status.clone()),
+        ..settings.segment_config(false)
+    };
+    download_segments(&app, &segments, &temp_dir, &segment_config, Some(&resolver)).await?;
+
+    token.status.set_stage("merging");
+    let combined_path = merge_segments(&app, segments.len(), &temp_dir).await?;
+    if token.is_cancelled() {
+        return Err(downloader::CANCELLED_ERROR.into());
+    }
+    token.status.set_stage("remuxing");
+    let tail_path = temp_dir.join("tail.mp4");
+    remux_with_ffmpeg(
+        &app,
+        &ffmpeg_path,
+        &combined_path,
+        &tail_path,
+        &[],
+        end_secs - resume_secs,
+    )
+    .await?;
+
+    // 4. 기존 파일 + 나머지를 이어 붙여 기존 이름의 .mp4로 교체
+    let joined_path = temp_dir.join("joined.mp4");
+    downloader::concat_with_ffmpeg(
+        &app,
+        &ffmpeg_path,
+        &[existing.clone(), tail_path],
+        &joined_path,
+    )
+    .await?;
+    let output_path = existing.with_extension("mp4");
+    fs::rename(&joined_path, &output_path).map_err(|e| format!("결과 파일 이동 실패: {}", e))?;
+    settings.apply_file_mode(&output_path);
     let _ = cleanup_temp(&temp_dir).await;
 
     let _ = app.emit(
@@ -645,29 +3562,264 @@ async fn download_vod(
             stage: "complete".into(),
             current: 1,
             total: 1,
-            message: "다운로드 완료!".into(),
+            message: "이어받기 완료!".into(),
         },
     );
 
     Ok(output_path.to_string_lossy().to_string())
 }
 
+/// 영상 없이 자막 트랙만 .vtt 파일로 저장
+#[tauri::command]
+async fn download_subtitles(
+    app: tauri::AppHandle,
+    video_id: String,
+    track_id: String,
+    output_dir: String,
+) -> Result<String, String> {
+    let info = load_video_info(app.clone(), &video_id).await?;
+    let tracks = downloader::list_subtitle_tracks(&info).await?;
+
+    if tracks.is_empty() {
+        return Err("이 VOD에는 자막이 없습니다".into());
+    }
+
+    let track = tracks
+        .iter()
+        .find(|t| t.id == track_id)
+        .ok_or(format!("자막 트랙 '{}'를 찾을 수 없습니다", track_id))?;
+
+    let output_path = downloader::build_subtitle_filename(&info, track, &output_dir);
+    downloader::download_subtitle_track(track, &output_path).await?;
+    let settings = load_settings(app).await.unwrap_or_default();
+    settings.apply_file_mode(&output_path);
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// VOD를 저화질로 받아 일정 간격마다 스크린샷(JPEG)만 저장
+#[tauri::command]
+async fn extract_frames(
+    app: tauri::AppHandle,
+    video_id: String,
+    interval_secs: u32,
+    output_dir: String,
+    start_time: Option<String>,
+    end_time: Option<String>,
+) -> Result<String, String> {
+    if interval_secs == 0 {
+        return Err("추출 간격은 1초 이상이어야 합니다".into());
+    }
+
+    let ffmpeg_path = find_ffmpeg(&app)
+        .await
+        .ok_or("ffmpeg를 찾을 수 없습니다. 먼저 ffmpeg를 설치해주세요.")?;
+
+    // 세그먼트를 받으므로 VOD 다운로드와 같은 동시 실행 수 제한을 따른다
+    let limits = app.state::<DownloadLimits>();
+    let _vod_permit = limits
+        .vods
+        .acquire()
+        .await
+        .map_err(|e| format!("다운로드 대기 실패: {}", e))?;
+
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "info".into(),
+            current: 0,
+            total: 1,
+            message: "비디오 정보를 가져오는 중...".into(),
+        },
+    );
+
+    let info = load_video_info(app.clone(), &video_id).await?;
+    let start_time = start_time.unwrap_or_default();
+    let end_time = end_time.unwrap_or_default();
+
+    // 프레임 추출에는 화질이 중요하지 않으므로 가장 낮은 화질 사용
+    let quality = downloader::lowest_quality_id(&info).await?;
+    let segments = resolve_segments(&info, &start_time, &end_time, quality.as_deref()).await?;
+
+    if segments.is_empty() {
+        return Err("다운로드할 세그먼트가 없습니다".into());
+    }
+
+    let temp_dir = PathBuf::from(&output_dir).join(format!("temp_frames_{}", video_id));
+    let resolver = segment_resolver(&info, &start_time, &end_time, quality.as_deref());
+    let settings = load_settings(app.clone()).await.unwrap_or_default();
+    download_segments(
+        &app,
+        &segments,
+        &temp_dir,
+        &settings.segment_config(false),
+        Some(&resolver),
+    )
+    .await?;
+    let combined_path = merge_segments(&app, segments.len(), &temp_dir).await?;
+
+    // 영상 파일명과 같은 이름의 "_frames" 폴더에 저장
+    let output_path = build_output_filename(&info, &start_time, &end_time, &output_dir, None);
+    let stem = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let frames_dir = output_path.with_file_name(format!("{}_frames", stem));
+    downloader::extract_frames_with_ffmpeg(&app, &ffmpeg_path, &combined_path, &frames_dir, interval_secs)
+        .await?;
+    settings.apply_dir_mode(&frames_dir);
+
+    let _ = cleanup_temp(&temp_dir).await;
+
+    let _ = app.emit(
+        "download-progress",
+        DownloadProgress {
+            stage: "complete".into(),
+            current: 1,
+            total: 1,
+            message: "프레임 추출 완료!".into(),
+        },
+    );
+
+    Ok(frames_dir.to_string_lossy().to_string())
+}
+
+/// 창을 닫을 때 진행 중인 세그먼트 쓰기를 기다리는 최대 시간
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(10);
+/// 창을 닫을 때 병합/ffmpeg 처리 중인 다운로드가 끝나기를 기다리는 최대 시간
+const SHUTDOWN_FINISH_GRACE: std::time::Duration = std::time::Duration::from_secs(300);
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(CancelState::default())
+        .setup(|app| {
+            // 저장된 연결 설정을 시작 시 적용
+            let settings = tauri::async_runtime::block_on(load_settings(app.handle().clone()))
+                .unwrap_or_default();
+            match settings.network_options() {
+                Ok(network) => downloader::set_network_options(network),
+                Err(e) => eprintln!("⚠️ Ignoring network settings: {}", e),
+            }
+            if let Err(e) = downloader::set_max_requests(settings.max_requests) {
+                eprintln!("⚠️ Ignoring max_requests setting: {}", e);
+            }
+            downloader::set_filename_template(settings.filename_template.clone());
+            downloader::set_merge_buffer_mb(settings.merge_buffer_mb);
+            downloader::set_verbose_logging(settings.verbose_logging);
+            downloader::set_prefer_fastest_cdn(settings.prefer_fastest_cdn);
+            app.manage(DownloadLimits::new(
+                settings.max_concurrent_vods,
+                settings.max_segment_requests,
+            ));
+            match settings.remote_addr() {
+                Ok(Some(addr)) => {
+                    tauri::async_runtime::spawn(remote::serve(
+                        app.handle().clone(),
+                        addr,
+                        settings.remote_token(),
+                        settings.remote_output_dir.clone(),
+                    ));
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("⚠️ Remote control disabled: {}", e),
+            }
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if window.label() != "main" {
+                    return;
+                }
+                let state = window.state::<CancelState>();
+                if state
+                    .active_segments
+                    .load(std::sync::atomic::Ordering::SeqCst)
+                    == 0
+                    && state.active_downloads() == 0
+                {
+                    return;
+                }
+
+                // 새 세그먼트는 받지 않고, 쓰는 중인 세그먼트와
+                // 병합/ffmpeg 처리가 끝난 뒤 창을 닫는다
+                eprintln!("🛑 Close requested - finishing in-flight segments and remuxes");
+                api.prevent_close();
+                state
+                    .shutdown
+                    .store(true, std::sync::atomic::Ordering::SeqCst);
+
+                let window = window.clone();
+                tauri::async_runtime::spawn(async move {
+                    let started = std::time::Instant::now();
+                    loop {
+                        let state = window.state::<CancelState>();
+                        let writing = state
+                            .active_segments
+                            .load(std::sync::atomic::Ordering::SeqCst)
+                            > 0
+                            && started.elapsed() < SHUTDOWN_GRACE;
+                        let finishing = state.active_downloads() > 0
+                            && started.elapsed() < SHUTDOWN_FINISH_GRACE;
+                        if !writing && !finishing {
+                            break;
+                        }
+                        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                    }
+                    let _ = window.destroy();
+                });
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             download_vod,
             download_clip_cmd,
+            download_clip_batch,
             check_ffmpeg,
             install_ffmpeg,
+            cancel_ffmpeg_install,
+            cancel_all_downloads,
+            cancel_and_delete,
+            get_download_status,
+            diagnose,
+            get_app_paths,
+            debug_fetch_api,
             fetch_video_info,
             fetch_clip_info,
+            cancel_info_fetch,
+            get_throughput,
+            list_channel_clips,
+            fetch_thumbnail_sprites,
+            extract_frames,
+            download_subtitles,
+            remux_existing,
+            download_vod_ranges,
+            retry_failed_segments,
+            download_segment_range,
+            salvage_partial_download,
+            verify_file,
+            verify_directory,
+            test_quality,
+            diagnose_dash_segments,
+            export_segments,
+            delete_download,
+            get_vod_bounds,
+            preview_output_filename,
+            finalize_temp_dir,
+            extend_vod_download,
             save_credentials,
             load_credentials,
+            export_config,
+            import_config,
+            load_settings,
+            save_settings,
+            import_cookies_file,
+            verify_and_save_cookies,
             open_login_webview,
-            close_login_webview
+            close_login_webview,
+            refresh_credentials
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,353 @@
+//! 원격 제어용 최소 HTTP API (설정에서 켠 경우에만 실행)
+//!
+//! - `GET /api/downloads`: 진행 중/대기 중인 다운로드 목록
+//! - `GET /api/downloads/{video_id}`: 다운로드 하나의 상태
+//! - `POST /api/downloads`: 다운로드 추가 (본문은 [`EnqueueRequest`] JSON)
+//! - `DELETE /api/downloads/{video_id}`: 다운로드 취소
+//!
+//! 토큰이 설정되어 있으면 `Authorization: Bearer <토큰>` 헤더가 있어야 한다.
+//! 브라우저가 보낸 요청(`Origin` 헤더)은 받지 않고, POST 본문은 `application/json`이어야 한다.
+//! 그래야 토큰 없이 localhost로 열어도 웹 페이지가 몰래 다운로드를 추가할 수 없다.
+
+use crate::downloader::{CancelState, DownloadStatus};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 요청 줄 + 헤더 최대 크기
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// 요청 본문 최대 크기
+const MAX_BODY_BYTES: usize = 64 * 1024;
+/// 요청을 다 받을 때까지 기다리는 시간
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// POST /api/downloads 본문 (download_vod 인자와 같음, output_dir 생략 시 설정값)
+///
+/// output_dir는 설정한 기본 저장 폴더이거나 그 안의 폴더여야 한다 (상대 경로는 기본 저장 폴더 기준).
+#[derive(Deserialize)]
+struct EnqueueRequest {
+    video_id: String,
+    #[serde(default)]
+    start_time: String,
+    #[serde(default)]
+    end_time: String,
+    output_dir: Option<String>,
+    quality_id: Option<String>,
+    options: Option<crate::VodDownloadOptions>,
+}
+
+/// GET /api/downloads 항목
+#[derive(Serialize)]
+struct RemoteDownload {
+    video_id: String,
+    #[serde(flatten)]
+    status: DownloadStatus,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    origin: Option<String>,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+/// `addr`에서 요청을 받기 시작 (앱이 끝날 때까지 반환하지 않음)
+pub async fn serve(
+    app: AppHandle,
+    addr: SocketAddr,
+    token: Option<String>,
+    default_output_dir: Option<String>,
+) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("⚠️ Remote control server failed to bind {}: {}", addr, e);
+            return;
+        }
+    };
+    eprintln!("🌐 Remote control listening on http://{}", addr);
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                eprintln!("⚠️ Remote control accept failed: {}", e);
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        let app = app.clone();
+        let token = token.clone();
+        let default_output_dir = default_output_dir.clone();
+        tokio::spawn(async move {
+            let result = handle_connection(
+                &app,
+                stream,
+                token.as_deref(),
+                default_output_dir.as_deref(),
+            )
+            .await;
+            if let Err(e) = result {
+                eprintln!("⚠️ Remote request from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    app: &AppHandle,
+    mut stream: TcpStream,
+    token: Option<&str>,
+    default_output_dir: Option<&str>,
+) -> Result<(), String> {
+    let request = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => request,
+        Ok(Err(e)) => {
+            write_response(&mut stream, 400, &error_body(&e)).await?;
+            return Err(e);
+        }
+        Err(_) => return Err("요청 시간 초과".into()),
+    };
+
+    // 웹 페이지에서 보낸 요청은 거부 (CSRF 방지)
+    if request.origin.is_some() {
+        eprintln!("🌐 {} {} → 403 (Origin header)", request.method, request.path);
+        return write_response(
+            &mut stream,
+            403,
+            &error_body("브라우저에서 보낸 요청은 허용되지 않습니다"),
+        )
+        .await;
+    }
+
+    let authorized = match token {
+        Some(token) => request
+            .authorization
+            .as_deref()
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|v| v.trim() == token),
+        None => true,
+    };
+    let (status, body) = if authorized {
+        route(app, &request, default_output_dir).await
+    } else {
+        (401, error_body("인증 토큰이 올바르지 않습니다"))
+    };
+    eprintln!("🌐 {} {} → {}", request.method, request.path, status);
+    write_response(&mut stream, status, &body).await
+}
+
+async fn route(
+    app: &AppHandle,
+    request: &Request,
+    default_output_dir: Option<&str>,
+) -> (u16, serde_json::Value) {
+    let path = request.path.split('?').next().unwrap_or("");
+    let video_id = path
+        .strip_prefix("/api/downloads/")
+        .filter(|id| !id.is_empty() && !id.contains('/'));
+    let state = app.state::<CancelState>();
+
+    match (request.method.as_str(), path, video_id) {
+        ("GET", "/api/downloads", _) => {
+            let downloads: Vec<RemoteDownload> = state
+                .list_downloads()
+                .into_iter()
+                .map(|(video_id, status)| RemoteDownload { video_id, status })
+                .collect();
+            (200, serde_json::json!(downloads))
+        }
+        ("POST", "/api/downloads", _) => {
+            let is_json = request
+                .content_type
+                .as_deref()
+                .and_then(|v| v.split(';').next())
+                .is_some_and(|v| v.trim().eq_ignore_ascii_case("application/json"));
+            if !is_json {
+                return (415, error_body("Content-Type은 application/json이어야 합니다"));
+            }
+            enqueue(app, &request.body, default_output_dir)
+        }
+        ("GET", _, Some(id)) => match state.download_status(id) {
+            Some(status) => (200, serde_json::json!(status)),
+            None => (404, error_body("진행 중인 다운로드가 없습니다")),
+        },
+        ("DELETE", _, Some(id)) => match state.cancel_download(id) {
+            Some(_) => {
+                eprintln!("🚫 Remote cancel for {}", id);
+                (200, serde_json::json!({ "video_id": id, "cancelled": true }))
+            }
+            None => (404, error_body("진행 중인 다운로드가 없습니다")),
+        },
+        _ => (404, error_body("알 수 없는 경로입니다")),
+    }
+}
+
+/// 다운로드를 백그라운드로 시작하고 바로 응답 (진행 상태는 GET으로 조회)
+fn enqueue(
+    app: &AppHandle,
+    body: &[u8],
+    default_output_dir: Option<&str>,
+) -> (u16, serde_json::Value) {
+    let request: EnqueueRequest = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return (400, error_body(&format!("요청 본문 해석 실패: {}", e))),
+    };
+    let Some(default_output_dir) = default_output_dir else {
+        return (400, error_body("원격 다운로드의 기본 저장 폴더가 설정되지 않았습니다"));
+    };
+    let output_dir = match resolve_output_dir(default_output_dir, request.output_dir.as_deref()) {
+        Ok(output_dir) => output_dir,
+        Err(e) => return (403, error_body(&e)),
+    };
+
+    let video_id = request.video_id.clone();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = crate::download_vod(
+            app,
+            request.video_id.clone(),
+            request.start_time,
+            request.end_time,
+            output_dir,
+            request.quality_id,
+            request.options,
+        )
+        .await;
+        match result {
+            Ok(path) => eprintln!("✅ Remote download {} saved to {}", request.video_id, path),
+            Err(e) => eprintln!("❌ Remote download {} failed: {}", request.video_id, e),
+        }
+    });
+    (202, serde_json::json!({ "video_id": video_id }))
+}
+
+/// 요청한 output_dir를 기본 저장 폴더 안으로 제한
+///
+/// 상대 경로는 기본 저장 폴더 기준으로 풀고, `..`가 들어가거나 폴더 밖을 가리키면 거부한다.
+fn resolve_output_dir(default_output_dir: &str, requested: Option<&str>) -> Result<String, String> {
+    let base = std::path::Path::new(default_output_dir);
+    let Some(requested) = requested.map(str::trim).filter(|d| !d.is_empty()) else {
+        return Ok(default_output_dir.to_string());
+    };
+    let requested = std::path::Path::new(requested);
+    if requested
+        .components()
+        .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        return Err("output_dir에 '..'를 쓸 수 없습니다".into());
+    }
+    let output_dir = base.join(requested);
+    if !output_dir.starts_with(base) {
+        return Err("output_dir는 기본 저장 폴더 안이어야 합니다".into());
+    }
+    Ok(output_dir.to_string_lossy().to_string())
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err("요청 헤더가 너무 깁니다".into());
+        }
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("요청 읽기 실패: {}", e))?;
+        if n == 0 {
+            return Err("요청을 다 받기 전에 연결이 끊어졌습니다".into());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("").to_string();
+    let path = request_line.next().unwrap_or("").to_string();
+
+    let mut content_length = 0;
+    let mut authorization = None;
+    let mut origin = None;
+    let mut content_type = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().map_err(|_| "잘못된 Content-Length")?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("origin") {
+            origin = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.to_string());
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err("요청 본문이 너무 깁니다".into());
+    }
+
+    let mut body = buf[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("요청 읽기 실패: {}", e))?;
+        if n == 0 {
+            return Err("요청을 다 받기 전에 연결이 끊어졌습니다".into());
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    Ok(Request {
+        method,
+        path,
+        authorization,
+        origin,
+        content_type,
+        body,
+    })
+}
+
+fn error_body(message: &str) -> serde_json::Value {
+    serde_json::json!({ "error": message })
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    body: &serde_json::Value,
+) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        415 => "Unsupported Media Type",
+        _ => "Not Found",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|e| format!("응답 쓰기 실패: {}", e))
+}